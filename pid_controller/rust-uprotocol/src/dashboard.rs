@@ -0,0 +1,93 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::Arc;
+use log::info;
+use serde_json::json;
+
+use crate::http_server::HttpServer;
+use crate::logging;
+use crate::uprotocol_handler::UProtocolHandler;
+
+/// Start the small HTTP server exposing live PID state for a browser dashboard on `address`
+/// (e.g. "0.0.0.0:8080").
+///
+/// Endpoints:
+/// - `GET /state`: `{"current_velocity": f64, "desired_velocity": f64, "current_time": f64, "pid_active": bool}`
+/// - `GET /metrics`: the `TrackingMetrics` of the current run, e.g.
+///   `{"data_points": 42, "min_error": f64, "max_error": f64, "avg_error": f64, "rms_error": f64,
+///     "min_acceleration": f64, "max_acceleration": f64, "avg_acceleration": f64,
+///     "overshoot": f64, "settling_time": f64}`
+/// - `GET /telemetry`: the `Telemetry` snapshot, e.g.
+///   `{"current_velocity": f64, "desired_velocity": f64, "speed_limit": null, "current_time": f64,
+///     "engaged": bool, "pid_active": bool, "acceleration": f64, "closest_obstacle_distance": null,
+///     "throttle": f64, "steer": f64, "brake": f64, "regime": "cruise"}`
+/// - `GET /subscriptions`: one entry per input stream, e.g.
+///   `[{"stream": "lidar", "uri": "//EGOVehicle/0/2/8003", "subscribed": true, "received": 0, "last_received_ms": null}]`
+/// - `GET /obstacles`: the in-path detections of the latest lidar frame, e.g.
+///   `[{"x": f64, "y": f64, "z": f64, "intensity": f64, "forward_distance": f64, "lateral_offset": f64}]`
+///
+/// Any other path returns 404.
+pub fn start_server(handler: Arc<UProtocolHandler>, address: &str) -> Result<HttpServer, Box<dyn std::error::Error + Send + Sync>> {
+    let server = HttpServer::start("Dashboard", address, move |url| {
+        route(&handler, url).map(|body| (body, "application/json"))
+    })?;
+    info!(target: logging::TELEMETRY, "Dashboard listening on http://{}", address);
+    Ok(server)
+}
+
+fn route(handler: &UProtocolHandler, url: &str) -> Option<String> {
+    match url {
+        "/state" => {
+            let (current_velocity, desired_velocity, current_time, pid_active) = handler.get_state();
+            Some(json!({
+                "current_velocity": current_velocity,
+                "desired_velocity": desired_velocity,
+                "current_time": current_time,
+                "pid_active": pid_active,
+            }).to_string())
+        }
+        "/metrics" => serde_json::to_string(&handler.tracking_metrics()).ok(),
+        "/telemetry" => serde_json::to_string(&handler.snapshot()).ok(),
+        "/subscriptions" => serde_json::to_string(&handler.subscription_status()).ok(),
+        "/obstacles" => Some(handler.path_obstacles_json()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_server::tests::get;
+    use crate::pid_controller::PIDController;
+    use crate::uprotocol_handler::tests::{mock_handler, tick};
+
+    #[tokio::test]
+    async fn serves_the_handler_state_as_json() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        tick(&transport, 1.5, 12.0).await;
+        let server = start_server(Arc::new(handler), "127.0.0.1:0").unwrap();
+
+        let response = get(&server, "/state");
+        assert!(response.contains("Content-Type: application/json"), "{}", response);
+        let body: serde_json::Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body, json!({ "current_velocity": 12.0, "desired_velocity": 0.0, "current_time": 1.5, "pid_active": false }));
+        for url in ["/metrics", "/telemetry", "/subscriptions", "/obstacles"] {
+            assert!(get(&server, url).starts_with("HTTP/1.1 200"), "{}", url);
+        }
+        assert!(get(&server, "/").starts_with("HTTP/1.1 404"));
+    }
+}
@@ -0,0 +1,91 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+//! Deliberate velocity stream faults for robustness tests, only built into the unit tests with
+//! the `test-fault-injection` feature. `UProtocolHandler::inject_velocity_faults` arms a fault for
+//! the next N velocity messages, which then run through the regular validation paths.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use bytes::Bytes;
+use log::warn;
+use up_rust::UMessage;
+
+use crate::logging;
+
+/// Fault applied to a velocity message before the listener processes it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VelocityFault {
+    /// Discard the message, as if it was lost
+    Drop,
+    /// Process the message only after the delay
+    Delay(Duration),
+    /// Replace the velocity with NaN
+    NaN,
+    /// Process the message twice, so the second tick repeats the time of the first
+    DuplicateTimestamp,
+}
+
+/// Fault still to apply and the number of messages it applies to
+#[derive(Debug, Default)]
+pub struct FaultInjector {
+    pending: Mutex<Option<(VelocityFault, u32)>>,
+}
+
+impl FaultInjector {
+    /// Apply `fault` to the next `count` messages, replacing a fault still pending (0 = clear)
+    pub fn inject(&self, fault: VelocityFault, count: u32) {
+        *self.pending.lock().unwrap() = (count > 0).then_some((fault, count));
+    }
+
+    /// Number of messages the pending fault still applies to
+    pub fn remaining(&self) -> u32 {
+        self.pending.lock().unwrap().map_or(0, |(_, count)| count)
+    }
+
+    fn next(&self) -> Option<VelocityFault> {
+        let mut pending = self.pending.lock().unwrap();
+        let (fault, count) = pending.as_mut()?;
+        let fault = *fault;
+        *count -= 1;
+        if *count == 0 {
+            *pending = None;
+        }
+        Some(fault)
+    }
+
+    /// The messages to process in place of `message`: none, the message itself (possibly late
+    /// or with a NaN velocity) or the message twice
+    pub async fn apply(&self, mut message: UMessage) -> Vec<UMessage> {
+        let Some(fault) = self.next() else {
+            return vec![message];
+        };
+        warn!(target: logging::VELOCITY, "FAULT INJECTION: {:?} on velocity message", fault);
+        match fault {
+            VelocityFault::Drop => Vec::new(),
+            VelocityFault::Delay(delay) => {
+                tokio::time::sleep(delay).await;
+                vec![message]
+            }
+            VelocityFault::NaN => {
+                message.payload = Some(Bytes::from_static(b"NaN"));
+                vec![message]
+            }
+            VelocityFault::DuplicateTimestamp => vec![message.clone(), message],
+        }
+    }
+}
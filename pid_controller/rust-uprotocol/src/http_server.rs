@@ -0,0 +1,114 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use log::{info, error};
+use tiny_http::{Header, Response, Server};
+
+use crate::logging;
+
+/// Body and content type of a successful response
+pub type Reply = (String, &'static str);
+
+/// Small HTTP server answering GET requests on a background thread, shared by the dashboard
+/// and the metrics exporter. Stops on `shutdown` or when dropped.
+pub struct HttpServer {
+    name: &'static str,
+    server: Arc<Server>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl HttpServer {
+    /// Bind `name` to `address` (e.g. "0.0.0.0:8080") and answer every request with `handle(url)`;
+    /// None answers 404
+    pub fn start<F>(name: &'static str, address: &str, handle: F) -> Result<Self, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: Fn(&str) -> Option<Reply> + Send + 'static,
+    {
+        let server = Arc::new(Server::http(address)?);
+        let server_for_worker = Arc::clone(&server);
+
+        let worker = std::thread::spawn(move || {
+            for request in server_for_worker.incoming_requests() {
+                let response = match handle(request.url()) {
+                    Some((body, content_type)) => Response::from_string(body)
+                        .with_header(Header::from_bytes("Content-Type", content_type).unwrap()),
+                    None => Response::from_string("Not Found").with_status_code(404),
+                };
+
+                if let Err(e) = request.respond(response) {
+                    error!(target: logging::TELEMETRY, "Failed to answer {} request: {}", name, e);
+                }
+            }
+        });
+
+        Ok(Self { name, server, worker: Some(worker) })
+    }
+
+    /// Address the server is bound to, e.g. the port picked for "127.0.0.1:0"
+    #[cfg(test)]
+    pub fn local_addr(&self) -> std::net::SocketAddr {
+        self.server.server_addr().to_ip().unwrap()
+    }
+
+    /// Stop accepting requests and wait for the worker thread to finish
+    pub fn shutdown(&mut self) {
+        self.server.unblock();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+            info!(target: logging::TELEMETRY, "{} stopped", self.name);
+        }
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    /// Raw `GET url` against `server`, returning the whole response
+    pub(crate) fn get(server: &HttpServer, url: &str) -> String {
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", url).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn answers_with_the_handler_reply() {
+        let mut server = HttpServer::start("Test", "127.0.0.1:0", |url| {
+            (url == "/hello").then(|| ("world".to_string(), "text/plain"))
+        }).unwrap();
+
+        let found = get(&server, "/hello");
+        assert!(found.starts_with("HTTP/1.1 200"), "{}", found);
+        assert!(found.contains("Content-Type: text/plain"));
+        assert!(found.ends_with("world"));
+        assert!(get(&server, "/other").starts_with("HTTP/1.1 404"));
+
+        server.shutdown();
+        assert!(server.worker.is_none(), "the worker thread has been joined");
+    }
+}
@@ -0,0 +1,214 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+//! Log targets of the controller subsystems, filterable like modules with `RUST_LOG` or
+//! `--log-level`, e.g. `RUST_LOG=debug,pid::velocity=warn` keeps everything but the per-tick
+//! velocity messages.
+
+use std::str::FromStr;
+use std::sync::Mutex;
+use log::LevelFilter;
+
+/// Velocity messages received from the vehicle
+pub const VELOCITY: &str = "pid::velocity";
+/// Clock messages received from the vehicle
+pub const CLOCK: &str = "pid::clock";
+/// Target speed, speed limit and the effective setpoint
+pub const TARGET: &str = "pid::target";
+/// Engage/disengage, manual brake detection and re-engagement
+pub const ENGAGE: &str = "pid::engage";
+/// Lidar messages and in-path obstacles
+pub const LIDAR: &str = "pid::lidar";
+/// Emergency and collision-avoidance braking, road friction and the actuation fail-safe
+pub const EMERGENCY: &str = "pid::emergency";
+/// PID steps, driver control values and the published status
+pub const CONTROL: &str = "pid::control";
+/// Acceleration and pedal commands sent to the actuators
+pub const ACTUATION: &str = "pid::actuation";
+/// PID configuration, presets and reloads
+pub const CONFIG: &str = "pid::config";
+/// Results files and the run summary
+pub const RESULTS: &str = "pid::results";
+/// Subscriptions, payload formats, rejected inputs and operator commands
+pub const MESSAGES: &str = "pid::messages";
+/// Recording and replay of the inbound messages
+pub const RECORDING: &str = "pid::recording";
+/// Dashboard and metrics endpoints
+pub const TELEMETRY: &str = "pid::telemetry";
+
+/// Subsystem names accepted by `--log-level` and their log targets
+pub const SUBSYSTEMS: &[(&str, &str)] = &[
+    ("velocity", VELOCITY),
+    ("clock", CLOCK),
+    ("target", TARGET),
+    ("engage", ENGAGE),
+    ("lidar", LIDAR),
+    ("emergency", EMERGENCY),
+    ("control", CONTROL),
+    ("actuation", ACTUATION),
+    ("config", CONFIG),
+    ("results", RESULTS),
+    ("messages", MESSAGES),
+    ("recording", RECORDING),
+    ("telemetry", TELEMETRY),
+];
+
+/// Unit of the velocities in log messages; the controller itself always works in m/s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeedUnit {
+    MetersPerSecond,
+    KilometersPerHour,
+    MilesPerHour,
+}
+
+impl SpeedUnit {
+    /// Value in this unit of 1 m/s
+    fn per_meter_per_second(self) -> f64 {
+        match self {
+            SpeedUnit::MetersPerSecond => 1.0,
+            SpeedUnit::KilometersPerHour => 3.6,
+            SpeedUnit::MilesPerHour => 3600.0 / 1609.344,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            SpeedUnit::MetersPerSecond => "m/s",
+            SpeedUnit::KilometersPerHour => "km/h",
+            SpeedUnit::MilesPerHour => "mph",
+        }
+    }
+}
+
+impl FromStr for SpeedUnit {
+    type Err = String;
+
+    fn from_str(unit: &str) -> Result<Self, Self::Err> {
+        match unit.trim().to_ascii_lowercase().as_str() {
+            "m/s" | "mps" => Ok(SpeedUnit::MetersPerSecond),
+            "km/h" | "kmh" => Ok(SpeedUnit::KilometersPerHour),
+            "mph" => Ok(SpeedUnit::MilesPerHour),
+            _ => Err(format!("Unknown speed unit '{}', expected m/s, km/h or mph", unit)),
+        }
+    }
+}
+
+/// Display unit and number of decimals of the logged velocities, see `speed`
+static SPEED_FORMAT: Mutex<(SpeedUnit, usize)> = Mutex::new((SpeedUnit::MetersPerSecond, 2));
+
+/// Show the logged velocities in `unit` with `precision` decimals (default m/s with 2 decimals)
+pub fn set_speed_format(unit: SpeedUnit, precision: usize) {
+    *SPEED_FORMAT.lock().unwrap() = (unit, precision);
+}
+
+/// Format a velocity (or velocity difference) given in m/s for a log message, e.g. `45.0 km/h`
+pub fn speed(meters_per_second: f64) -> String {
+    let (unit, precision) = *SPEED_FORMAT.lock().unwrap();
+    format_speed(meters_per_second, unit, precision)
+}
+
+/// `speed` with an explicit unit and precision
+pub fn format_speed(meters_per_second: f64, unit: SpeedUnit, precision: usize) -> String {
+    format!("{:.*} {}", precision, meters_per_second * unit.per_meter_per_second(), unit.symbol())
+}
+
+/// Initialize the logger from `RUST_LOG`, with `subsystem=level` entries (e.g. `velocity=warn`)
+/// overriding the level of single subsystems
+pub fn init(levels: &[String]) -> Result<(), String> {
+    with_levels(env_logger::Builder::from_default_env(), levels)?
+        .try_init()
+        .map_err(|e| e.to_string())
+}
+
+/// Add the `subsystem=level` entries to `builder` as filters of the subsystem targets
+fn with_levels(mut builder: env_logger::Builder, levels: &[String]) -> Result<env_logger::Builder, String> {
+    for entry in levels {
+        let (subsystem, level) = entry.split_once('=')
+            .ok_or_else(|| format!("Invalid log level '{}', expected SUBSYSTEM=LEVEL", entry))?;
+        let target = SUBSYSTEMS.iter()
+            .find(|(name, _)| *name == subsystem.trim())
+            .map(|(_, target)| *target)
+            .ok_or_else(|| format!(
+                "Unknown log subsystem '{}', expected one of: {}",
+                subsystem,
+                SUBSYSTEMS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+            ))?;
+        let level = LevelFilter::from_str(level.trim())
+            .map_err(|_| format!("Invalid log level '{}' for {}", level, subsystem))?;
+        builder.filter_module(target, level);
+    }
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::{Level, Log, Metadata};
+
+    fn enabled(logger: &env_logger::Logger, target: &str, level: Level) -> bool {
+        logger.enabled(&Metadata::builder().target(target).level(level).build())
+    }
+
+    #[test]
+    fn subsystem_levels_filter_their_targets_only() {
+        let mut base = env_logger::Builder::new();
+        base.filter_level(LevelFilter::Debug);
+        let logger = with_levels(base, &["velocity=warn".to_string(), " emergency = trace ".to_string()])
+            .unwrap()
+            .build();
+
+        assert!(!enabled(&logger, VELOCITY, Level::Debug), "velocity debug is silenced");
+        assert!(enabled(&logger, VELOCITY, Level::Warn));
+        assert!(enabled(&logger, EMERGENCY, Level::Trace));
+        assert!(enabled(&logger, LIDAR, Level::Debug), "other subsystems keep the base level");
+        assert!(!enabled(&logger, LIDAR, Level::Trace));
+    }
+
+    #[test]
+    fn invalid_subsystem_levels_are_rejected() {
+        for entry in ["velocity", "speed=warn", "velocity=loud"] {
+            assert!(with_levels(env_logger::Builder::new(), &[entry.to_string()]).is_err(), "{}", entry);
+        }
+    }
+
+    #[test]
+    fn speeds_are_formatted_in_each_display_unit() {
+        assert_eq!(format_speed(12.5, SpeedUnit::MetersPerSecond, 2), "12.50 m/s");
+        assert_eq!(format_speed(12.5, SpeedUnit::KilometersPerHour, 1), "45.0 km/h");
+        assert_eq!(format_speed(12.5, SpeedUnit::MilesPerHour, 2), "27.96 mph");
+        assert_eq!(format_speed(-2.0, SpeedUnit::KilometersPerHour, 0), "-7 km/h");
+    }
+
+    #[test]
+    fn speed_units_parse_their_symbols() {
+        for (unit, expected) in [("m/s", SpeedUnit::MetersPerSecond), ("MPS", SpeedUnit::MetersPerSecond),
+                                 ("km/h", SpeedUnit::KilometersPerHour), (" kmh ", SpeedUnit::KilometersPerHour),
+                                 ("mph", SpeedUnit::MilesPerHour)] {
+            assert_eq!(unit.parse::<SpeedUnit>(), Ok(expected), "{}", unit);
+        }
+        assert!("knots".parse::<SpeedUnit>().is_err());
+    }
+
+    #[test]
+    fn subsystem_targets_are_distinct() {
+        let mut targets: Vec<_> = SUBSYSTEMS.iter().map(|(_, target)| *target).collect();
+        targets.sort();
+        targets.dedup();
+        assert_eq!(targets.len(), SUBSYSTEMS.len());
+        assert!(targets.iter().all(|target| target.starts_with("pid::")));
+    }
+}
@@ -14,17 +14,32 @@
 // limitations under the License.
 //
 
-use log::info;
+use log::{info, warn};
 use clap::Parser;
 use up_transport_zenoh::{UPTransportZenoh, zenoh_config};
 use up_rust::{LocalUriProvider, StaticUriProvider};
 use zenoh::{Config};
 
-use pid_controller::PIDController;
-use uprotocol_handler::UProtocolHandler;
+use pid_config::PidConfig;
+use uprotocol_handler::{ActuationOutput, ClockSource, ControlLoop, UProtocolHandler, WireSchema};
 
+mod logging;
+mod pid_config;
 mod pid_controller;
+mod recording;
+mod resources;
 mod uprotocol_handler;
+#[cfg(feature = "dashboard")]
+mod dashboard;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(any(feature = "dashboard", feature = "metrics"))]
+mod http_server;
+#[cfg(all(test, feature = "test-fault-injection"))]
+mod fault_injection;
+#[cfg(test)]
+#[path = "testing/vehicle_model.rs"]
+mod vehicle_model;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -39,12 +54,92 @@ struct Args {
     delta: f64,
     #[clap(long, default_value = None)]
     router: Option<String>,
+    /// PID config file (defaults to ./pid_config.json when present, built-in values otherwise)
+    #[clap(long, default_value = None)]
+    config: Option<String>,
+    /// Keep result files of the last N runs with timestamped names (0 = overwrite)
+    #[clap(long, default_value_t = 0)]
+    keep_log_runs: usize,
+    /// Log level of one subsystem on top of RUST_LOG, e.g. `velocity=warn`; repeatable
+    #[clap(long = "log-level", value_name = "SUBSYSTEM=LEVEL")]
+    log_levels: Vec<String>,
+    /// Unit of the velocities in log messages (`m/s`, `km/h` or `mph`); the control math stays in m/s
+    #[clap(long, default_value = "m/s")]
+    speed_unit: String,
+    /// Decimals of the velocities in log messages
+    #[clap(long, default_value_t = 2)]
+    speed_precision: usize,
+    /// Run the PID on every Nth velocity message only (1 = every message)
+    #[clap(long, default_value_t = 1)]
+    decimation: u32,
+    /// Also save the results every N seconds while running (0 = only on shutdown)
+    #[clap(long, default_value_t = 0)]
+    results_interval: u64,
+    /// Warn about control ticks slower than N milliseconds (0 = never)
+    #[clap(long, default_value_t = 0)]
+    tick_budget_ms: u64,
+    /// Publish zero acceleration for the first N ticks after engaging
+    #[clap(long, default_value_t = 0)]
+    warmup_ticks: u32,
+    /// Republish the last acceleration after N milliseconds without actuation message (0 = off)
+    #[clap(long, default_value_t = 0)]
+    heartbeat_ms: u64,
+    /// Report "at speed" to the operator within N m/s of the desired velocity (0 = off)
+    #[clap(long, default_value_t = 0.0)]
+    at_speed_band: f64,
+    /// Milliseconds the velocity must stay inside/outside the at-speed band before the status flips
+    #[clap(long, default_value_t = 500)]
+    at_speed_dwell_ms: u64,
+    /// Brake and disengage after N consecutive failed actuation sends (0 = off)
+    #[clap(long, default_value_t = 0)]
+    fail_safe_after: u32,
+    /// Deceleration in m/s² commanded when the fail-safe engages
+    #[clap(long, default_value_t = -2.0, allow_hyphen_values = true)]
+    fail_safe_accel: f64,
+    /// Publish the control regime on change for HMI display
+    #[clap(long)]
+    publish_regime: bool,
+    /// Milliseconds a new control regime must persist before it is published
+    #[clap(long, default_value_t = 300)]
+    regime_debounce_ms: u64,
+    /// Run the control ticks on a dedicated task instead of in the velocity listener
+    #[clap(long)]
+    async_control_loop: bool,
+    /// Take the control tick time from the wall clock instead of the clock stream
+    #[clap(long)]
+    wall_clock: bool,
+    /// Refuse to engage until the self-test (input streams, subscriptions, PID config) passes
+    #[clap(long)]
+    require_self_test: bool,
+    /// Milliseconds within which clock and velocity must have produced a valid message for the self-test
+    #[clap(long, default_value_t = 2000)]
+    self_test_timeout_ms: u64,
+    /// Actuation quantity to publish: `acceleration` request (cc_throttle), `pedals` (throttle/brake
+    /// values on cc_pedals) or `both`
+    #[clap(long, default_value = "acceleration", value_parser = ["acceleration", "pedals", "both"])]
+    actuation_output: String,
+    /// Publish the actuation as ROS2 AckermannDriveStamped-shaped JSON for a ROS2 bridge
+    #[clap(long)]
+    ackermann: bool,
+    /// Record the raw inbound messages of every stream to this directory for offline replay
+    #[clap(long, default_value = None)]
+    record: Option<String>,
+    /// Feed a recording made with --record through the controller instead of subscribing, then exit
+    #[clap(long, default_value = None)]
+    replay: Option<String>,
+    /// Reload the PID config file when it changes and apply it to the running controller
+    #[clap(long)]
+    watch_config: bool,
+    #[cfg(feature = "dashboard")]
+    #[clap(long, default_value = "0.0.0.0:8080")]
+    dashboard: String,
+    #[cfg(feature = "metrics")]
+    #[clap(long, default_value = "0.0.0.0:9100")]
+    metrics: String,
 }
 
 // Helper function to create a Zenoh configuration
-pub(crate) fn get_zenoh_config() -> zenoh_config::Config {
-    let args = Args::parse();
-
+pub(crate) fn get_zenoh_config(args: &Args) -> zenoh_config::Config {
     let zenoh_string = if let Some(router) = &args.router {
         format!("{{ mode: 'peer', connect: {{ endpoints: [ 'tcp/{}:7447' ] }} }}", router)
     } else {
@@ -56,20 +151,37 @@ pub(crate) fn get_zenoh_config() -> zenoh_config::Config {
     zenoh_config
 }
 
+// Load the PID configuration: an explicit path must exist, the default file is optional.
+// PID_* environment variables take precedence over the file.
+fn load_pid_config(path: Option<&str>) -> Result<PidConfig, String> {
+    let config = match path {
+        Some(path) => PidConfig::load(path),
+        None if std::path::Path::new(pid_config::DEFAULT_CONFIG_FILE).exists() => {
+            PidConfig::load(pid_config::DEFAULT_CONFIG_FILE)
+        }
+        None => {
+            info!(target: logging::CONFIG, "No {} found, using built-in PID defaults", pid_config::DEFAULT_CONFIG_FILE);
+            Ok(PidConfig::default())
+        }
+    };
+    config.and_then(PidConfig::with_env_overrides)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
     // Initialize logging
-    env_logger::init();
+    logging::init(&args.log_levels)?;
+    logging::set_speed_format(args.speed_unit.parse()?, args.speed_precision);
 
     info!("*** Started PID Controller with uProtocol");
 
-    let kp = 0.05;
-    let ki = kp / 8.0;
-    let kd = kp / 10.0;
+    let pid_config = load_pid_config(args.config.as_deref())?;
 
-    println!("PID => Kp={}, Ki={}, Kd={}", kp, ki, kd);
+    println!("PID => Kp={}, Ki={}, Kd={}", pid_config.kp, pid_config.ki, pid_config.kd);
 
-    let pid = PIDController::new(kp, ki, kd);
+    let pid = pid_config.build_controller();
 
     // Create a uProtocol URI provider for the PID controller
     // This defines the identity of this node in the uProtocol network
@@ -78,24 +190,105 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize uProtocol transport with Zenoh
     let transport = UPTransportZenoh::builder(uri_provider.get_authority())
         .expect("invalid authority name")
-        .with_config(get_zenoh_config())
+        .with_config(get_zenoh_config(&args))
         .build()
         .await?;
 
     let handler = UProtocolHandler::new(pid, transport)?;
+    handler.set_keep_log_runs(args.keep_log_runs);
+    handler.set_decimation_factor(args.decimation);
+    handler.set_results_interval(std::time::Duration::from_secs(args.results_interval));
+    handler.set_tick_budget(std::time::Duration::from_millis(args.tick_budget_ms));
+    handler.set_warmup_ticks(args.warmup_ticks);
+    handler.set_heartbeat_interval(std::time::Duration::from_millis(args.heartbeat_ms));
+    handler.set_at_speed_band(args.at_speed_band, std::time::Duration::from_millis(args.at_speed_dwell_ms));
+    handler.set_fail_safe(args.fail_safe_after, args.fail_safe_accel);
+    handler.set_regime_publishing(args.publish_regime, std::time::Duration::from_millis(args.regime_debounce_ms));
+    handler.set_self_test(args.require_self_test, std::time::Duration::from_millis(args.self_test_timeout_ms));
+    handler.set_pid_config(pid_config.clone());
+    if args.async_control_loop {
+        handler.set_control_loop(ControlLoop::Async);
+    }
+    if args.wall_clock {
+        handler.set_clock_source(ClockSource::WallClock);
+    }
+    handler.set_actuation_output(match args.actuation_output.as_str() {
+        "pedals" => ActuationOutput::ControlValues,
+        "both" => ActuationOutput::Both,
+        _ => ActuationOutput::Acceleration,
+    });
+    if args.ackermann {
+        handler.set_wire_schema(WireSchema::Ackermann);
+    }
+    if let Some(dir) = &args.record {
+        handler.record_to(dir)?;
+    }
+
+    if let Some(dir) = &args.replay {
+        handler.replay_from(dir).await?;
+        handler.store_results();
+        handler.show_results();
+        return Ok(());
+    }
 
     handler.start().await?;
 
+    let engaged_transitions = handler.subscribe_engaged();
+    std::thread::spawn(move || {
+        for engaged in engaged_transitions {
+            info!(target: logging::ENGAGE, "Cruise control {}", if engaged { "engaged" } else { "disengaged" });
+        }
+    });
+
     println!("PID controller running with uProtocol (CTRL-C to terminate)...");
 
     // Set up Ctrl+C handler
     let handler_clone = std::sync::Arc::new(handler);
     let handler_for_signal = handler_clone.clone();
-    
+
+    #[cfg(feature = "dashboard")]
+    let mut dashboard = dashboard::start_server(handler_clone.clone(), &args.dashboard)
+        .map_err(|e| e.to_string())?;
+
+    #[cfg(feature = "metrics")]
+    let mut metrics_server = metrics::start_server(&args.metrics)
+        .map_err(|e| e.to_string())?;
+
+    let mut config_watcher = if args.watch_config {
+        let path = args.config.clone().unwrap_or_else(|| pid_config::DEFAULT_CONFIG_FILE.to_string());
+        let handler_for_reload = handler_clone.clone();
+        Some(pid_config::ConfigWatcher::start(path, pid_config, std::time::Duration::from_secs(1), move |old, new| {
+            handler_for_reload.apply_pid_config(old, new);
+        }))
+    } else {
+        None
+    };
+
+    if args.require_self_test {
+        // Give the streams one timeout to deliver, then tell the operator why engage requests are rejected
+        let handler_for_self_test = handler_clone.clone();
+        let timeout = std::time::Duration::from_millis(args.self_test_timeout_ms);
+        tokio::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            match handler_for_self_test.self_test() {
+                Ok(()) => info!(target: logging::ENGAGE, "Self-test passed, cruise control can engage"),
+                Err(report) => warn!(target: logging::ENGAGE, "Self-test failed ({}), engage requests are rejected until it passes", report),
+            }
+        });
+    }
+
     tokio::spawn(async move {
         tokio::signal::ctrl_c().await.expect("Failed to listen for ctrl+c");
         
         println!("\nShutting down...");
+
+        #[cfg(feature = "dashboard")]
+        dashboard.shutdown();
+        #[cfg(feature = "metrics")]
+        metrics_server.shutdown();
+        if let Some(watcher) = config_watcher.as_mut() {
+            watcher.shutdown();
+        }
         
         handler_for_signal.store_results();
         handler_for_signal.show_results();
@@ -0,0 +1,187 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::OnceLock;
+use log::{info, error};
+use prometheus::{Encoder, Gauge, IntCounter, Registry, TextEncoder};
+
+use crate::http_server::{HttpServer, Reply};
+use crate::logging;
+
+/// Prometheus metrics of the PID node, updated from `publish_acc`
+pub struct PidMetrics {
+    registry: Registry,
+    pub current_velocity: Gauge,
+    pub desired_velocity: Gauge,
+    pub acceleration: Gauge,
+    pub integral_term: Gauge,
+    pub emergency_brake_events: IntCounter,
+    pub messages_processed: IntCounter,
+}
+
+impl PidMetrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let current_velocity = Gauge::new("pid_current_velocity", "Current vehicle velocity (m/s)").unwrap();
+        let desired_velocity = Gauge::new("pid_desired_velocity", "Desired target velocity (m/s)").unwrap();
+        let acceleration = Gauge::new("pid_acceleration", "Last published acceleration command (m/s²)").unwrap();
+        let integral_term = Gauge::new("pid_integral_term", "Integral contribution Ki * accumulated error").unwrap();
+        let emergency_brake_events = IntCounter::new("pid_emergency_brake_events_total", "Number of emergency brake events").unwrap();
+        let messages_processed = IntCounter::new("pid_messages_processed_total", "Number of velocity messages processed by the PID").unwrap();
+
+        registry.register(Box::new(current_velocity.clone())).unwrap();
+        registry.register(Box::new(desired_velocity.clone())).unwrap();
+        registry.register(Box::new(acceleration.clone())).unwrap();
+        registry.register(Box::new(integral_term.clone())).unwrap();
+        registry.register(Box::new(emergency_brake_events.clone())).unwrap();
+        registry.register(Box::new(messages_processed.clone())).unwrap();
+
+        Self {
+            registry,
+            current_velocity,
+            desired_velocity,
+            acceleration,
+            integral_term,
+            emergency_brake_events,
+            messages_processed,
+        }
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            error!(target: logging::TELEMETRY, "Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// Process-wide metrics instance
+pub fn metrics() -> &'static PidMetrics {
+    #[cfg(test)]
+    if let Some(metrics) = tests::THREAD_METRICS.get() {
+        return metrics;
+    }
+    static METRICS: OnceLock<PidMetrics> = OnceLock::new();
+    METRICS.get_or_init(PidMetrics::new)
+}
+
+/// Start the HTTP exporter serving `GET /metrics` for Prometheus to scrape on `address` (e.g. "0.0.0.0:9100")
+pub fn start_server(address: &str) -> Result<HttpServer, Box<dyn std::error::Error + Send + Sync>> {
+    let server = HttpServer::start("Metrics exporter", address, route)?;
+    info!(target: logging::TELEMETRY, "Prometheus metrics available on http://{}/metrics", address);
+    Ok(server)
+}
+
+fn route(url: &str) -> Option<Reply> {
+    (url == "/metrics").then(|| (metrics().encode(), "text/plain; version=0.0.4"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use crate::http_server::tests::get;
+    use crate::pid_controller::PIDController;
+    use crate::resources;
+    use crate::uprotocol_handler::tests::{actuations, mock_handler, tick, uri};
+
+    thread_local! {
+        /// Metrics returned by `metrics()` on this thread, so parallel tests do not share the gauges
+        pub(super) static THREAD_METRICS: Cell<Option<&'static PidMetrics>> = const { Cell::new(None) };
+    }
+
+    /// Give the current thread (and the single-threaded test runtime on it) its own metrics
+    fn isolated_metrics() -> &'static PidMetrics {
+        let metrics = Box::leak(Box::new(PidMetrics::new()));
+        THREAD_METRICS.set(Some(metrics));
+        metrics
+    }
+
+    fn exported_value(exported: &str, name: &str) -> f64 {
+        exported.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))
+            .unwrap_or_else(|| panic!("{} missing in\n{}", name, exported))
+            .parse()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn gauges_hold_the_values_of_the_last_tick() {
+        let metrics = isolated_metrics();
+        let (_handler, transport) = mock_handler(PIDController::new(0.5, 0.1, 0.0)).await;
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        for (time, velocity) in [(1.0, 10.0), (1.1, 10.5), (1.2, 11.0)] {
+            tick(&transport, time, velocity).await;
+        }
+
+        let exported = metrics.encode();
+        assert_eq!(exported_value(&exported, "pid_current_velocity"), 11.0);
+        assert_eq!(exported_value(&exported, "pid_desired_velocity"), 20.0);
+        assert_eq!(exported_value(&exported, "pid_acceleration"), *actuations(&transport).last().unwrap());
+        // Ki 0.1 times the errors of the two PID steps, 9.5 and 9.0 m/s over 0.1 s each
+        assert!((exported_value(&exported, "pid_integral_term") - 0.185).abs() < 1e-9, "{}", exported);
+        assert_eq!(exported_value(&exported, "pid_messages_processed_total"), 3.0);
+        assert_eq!(exported_value(&exported, "pid_emergency_brake_events_total"), 0.0);
+        assert!(exported.contains("# TYPE pid_current_velocity gauge"));
+    }
+
+    #[tokio::test]
+    async fn acceleration_gauge_skips_rate_limited_ticks() {
+        let metrics = isolated_metrics();
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_min_publish_interval(std::time::Duration::from_secs(60));
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        tick(&transport, 1.0, 10.0).await;
+        tick(&transport, 1.1, 10.0).await;
+
+        let published = actuations(&transport);
+        assert_eq!(published.len(), 1, "the second tick is rate limited");
+        assert_eq!(exported_value(&metrics.encode(), "pid_acceleration"), published[0]);
+        assert!(handler.control_state().last_acceleration.unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn an_emergency_stop_counts_as_one_event() {
+        let metrics = isolated_metrics();
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_emergency_brake_profile(0.5);
+        let (handler, transport) = mock_handler(pid).await;
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        tick(&transport, 1.0, 10.0).await;
+
+        handler.inject_lidar(crate::pid_controller::tests::obstacle_at(2.0));
+        for (time, velocity) in [(1.1, 10.0), (1.4, 7.0), (1.7, 3.0), (2.0, 0.5), (2.1, 0.0)] {
+            tick(&transport, time, velocity).await;
+        }
+        assert!(!handler.is_engaged(), "the stop profile ran to standstill");
+        assert_eq!(exported_value(&metrics.encode(), "pid_emergency_brake_events_total"), 1.0);
+    }
+
+    #[test]
+    fn exporter_serves_only_the_metrics_path() {
+        let server = HttpServer::start("Metrics exporter", "127.0.0.1:0", route).unwrap();
+        let response = get(&server, "/metrics");
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"), "{}", response);
+        assert!(response.contains("# TYPE pid_integral_term gauge"));
+        assert!(get(&server, "/").starts_with("HTTP/1.1 404"));
+    }
+}
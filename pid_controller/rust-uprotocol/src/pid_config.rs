@@ -0,0 +1,569 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+
+use crate::logging;
+use crate::pid_controller::{BrakeBlend, Corridor, PIDController};
+
+/// Default file name looked up in the working directory when no config path is given
+pub const DEFAULT_CONFIG_FILE: &str = "pid_config.json";
+
+/// Environment variables overriding single values of the file, e.g. in containers without a mounted config
+const ENV_OVERRIDES: [(&str, fn(&mut PidConfig) -> &mut f64); 4] = [
+    ("PID_KP", |config| &mut config.kp),
+    ("PID_KI", |config| &mut config.ki),
+    ("PID_KD", |config| &mut config.kd),
+    ("PID_MAX_ACCEL", |config| &mut config.comfort_limit),
+];
+
+/// PID node configuration, typically loaded from `pid_config.json`.
+/// Every field is optional in the file and falls back to the built-in defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PidConfig {
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    pub integral_leak: f64,       // 1/s, 0.0 = no leak
+    pub derivative_tau: f64,      // seconds, 0.0 = unfiltered derivative
+    pub soft_start_duration: f64, // seconds, 0.0 = disabled
+    pub coast_band: f64,          // m/s², 0.0 = brake on any deceleration
+    pub hold_feedforward: bool,   // learned hold acceleration inside the target tolerance band
+    pub max_steer_rate: f64,      // steer units per second, 0.0 = unlimited
+    pub comfort_limit: f64,       // m/s², limit of the regular PID output
+    pub max_normal_brake: f64,    // 0.0-1.0, brake cap outside emergencies, 1.0 = none
+    pub setpoint_ramp_rate: f64,  // m/s², 0.0 = step to the setpoint
+    pub predict_with_steering: bool, // steering bends the obstacle corridor as well as reducing speed
+    pub deadtime: f64,            // seconds of actuator deadtime to compensate, 0.0 = disabled
+    pub emergency: EmergencyConfig,
+    pub manual_brake: ManualBrakeConfig,
+    pub creep: CreepConfig,
+    pub stop_hold: StopHoldConfig,
+    pub corridor: Corridor,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmergencyConfig {
+    pub emergency_stop_distance: f64,  // meters
+    pub slow_down_distance: f64,       // meters
+    pub max_braking_acceleration: f64, // m/s², negative
+    pub actuator_delay: f64,           // seconds until a brake command takes effect
+    pub obstacle_window: usize,        // lidar frames an obstacle must persist for
+    pub hard_floor: f64,               // meters, brake immediately below this distance
+    pub confirm_frames: usize,         // consecutive emergency observations before disengaging
+    pub post_emergency_cooldown: f64,  // seconds of limited acceleration after an emergency
+    pub cooldown_acceleration: f64,    // m/s², acceleration cap when the cooldown starts
+    pub brake_ramp_time: f64,          // seconds to ramp to max braking, 0.0 = no stop profile
+    pub max_braking_time: f64,         // seconds of braking at standstill before holding, 0.0 = no cutout
+    pub safety_limit: f64,             // m/s², largest deceleration of emergency braking, >= comfort_limit
+    pub brake_blend: BrakeBlend,       // combination of collision-avoidance braking and PID output
+    pub slow_down_hysteresis: f64,     // meters beyond slow_down_distance an obstacle must reach to leave the zone
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ManualBrakeConfig {
+    pub brake_threshold: f64, // m/s², negative
+    pub speed_tolerance: f64, // m/s
+    pub reengage_clearance: Option<f64>, // meters of clear path to re-engage, null = emergency.slow_down_distance
+    pub bumpless_transfer: bool,         // continue from the current acceleration after re-engaging
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CreepConfig {
+    pub min_throttle: f64,    // 0.0-1.0, 0.0 = disabled
+    pub speed_threshold: f64, // m/s
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StopHoldConfig {
+    pub brake_level: f64,     // 0.0-1.0, 0.0 = disabled
+    pub speed_threshold: f64, // m/s
+}
+
+impl Default for PidConfig {
+    fn default() -> Self {
+        let kp = 0.05;
+        Self {
+            kp,
+            ki: kp / 8.0,
+            kd: kp / 10.0,
+            integral_leak: 0.0,
+            derivative_tau: 0.0,
+            soft_start_duration: 0.0,
+            coast_band: 0.0,
+            hold_feedforward: false,
+            max_steer_rate: 0.0,
+            comfort_limit: 1.5,
+            max_normal_brake: 1.0,
+            setpoint_ramp_rate: 0.0,
+            predict_with_steering: false,
+            deadtime: 0.0,
+            emergency: EmergencyConfig::default(),
+            manual_brake: ManualBrakeConfig::default(),
+            creep: CreepConfig::default(),
+            stop_hold: StopHoldConfig::default(),
+            corridor: Corridor::default(),
+        }
+    }
+}
+
+impl Default for EmergencyConfig {
+    fn default() -> Self {
+        Self {
+            emergency_stop_distance: 3.0,
+            slow_down_distance: 15.0,
+            max_braking_acceleration: -10.0,
+            actuator_delay: 0.0,
+            obstacle_window: 1,
+            hard_floor: 0.0,
+            confirm_frames: 1,
+            post_emergency_cooldown: 0.0,
+            cooldown_acceleration: 0.5,
+            brake_ramp_time: 0.0,
+            max_braking_time: 0.0,
+            safety_limit: 10.0,
+            brake_blend: BrakeBlend::Override,
+            slow_down_hysteresis: 0.0,
+        }
+    }
+}
+
+impl Default for ManualBrakeConfig {
+    fn default() -> Self {
+        Self {
+            brake_threshold: -2.0,
+            speed_tolerance: 2.0,
+            reengage_clearance: None,
+            bumpless_transfer: false,
+        }
+    }
+}
+
+impl Default for CreepConfig {
+    fn default() -> Self {
+        Self {
+            min_throttle: 0.0,
+            speed_threshold: 0.5,
+        }
+    }
+}
+
+impl Default for StopHoldConfig {
+    fn default() -> Self {
+        Self {
+            brake_level: 0.0,
+            speed_threshold: 0.1,
+        }
+    }
+}
+
+impl PidConfig {
+    /// Load and validate a config file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read PID config '{}': {}", path.display(), e))?;
+        Self::from_json(&content)
+            .map_err(|e| format!("Invalid PID config '{}': {}", path.display(), e))
+    }
+
+    /// Parse and validate a config from JSON text
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        let config: PidConfig = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Apply the `PID_KP`, `PID_KI`, `PID_KD` and `PID_MAX_ACCEL` environment variables on top of
+    /// this config (env over file over defaults) and validate the result
+    pub fn with_env_overrides(self) -> Result<Self, String> {
+        self.with_overrides(|name| std::env::var(name).ok())
+    }
+
+    /// Like `with_env_overrides`, reading the variables through `lookup`
+    pub fn with_overrides(mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        for (name, field) in ENV_OVERRIDES {
+            let Some(value) = lookup(name) else {
+                continue;
+            };
+            let parsed = value.trim().parse::<f64>()
+                .map_err(|e| format!("{}={:?} is not a number: {}", name, value, e))?;
+            info!(target: logging::CONFIG, "PID config: {} overrides {} with {}", name, field(&mut self), parsed);
+            *field(&mut self) = parsed;
+        }
+        self.validate().map_err(|e| format!("Invalid PID config after environment overrides: {}", e))?;
+        Ok(self)
+    }
+
+    /// Check that every value is usable by the controller
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, gain) in [("kp", self.kp), ("ki", self.ki), ("kd", self.kd)] {
+            if !gain.is_finite() || gain < 0.0 {
+                return Err(format!("{} must be a finite, non-negative number (got {})", name, gain));
+            }
+        }
+
+        if !self.integral_leak.is_finite() || self.integral_leak < 0.0 {
+            return Err(format!("integral_leak must be non-negative (got {})", self.integral_leak));
+        }
+
+        if !self.derivative_tau.is_finite() || self.derivative_tau < 0.0 {
+            return Err(format!("derivative_tau must be non-negative (got {})", self.derivative_tau));
+        }
+
+        if !self.soft_start_duration.is_finite() || self.soft_start_duration < 0.0 {
+            return Err(format!("soft_start_duration must be non-negative (got {})", self.soft_start_duration));
+        }
+
+        if !self.coast_band.is_finite() || self.coast_band < 0.0 {
+            return Err(format!("coast_band must be non-negative (got {})", self.coast_band));
+        }
+
+        if !self.max_steer_rate.is_finite() || self.max_steer_rate < 0.0 {
+            return Err(format!("max_steer_rate must be non-negative (got {})", self.max_steer_rate));
+        }
+
+        if !self.max_normal_brake.is_finite() || !(0.0..=1.0).contains(&self.max_normal_brake) {
+            return Err(format!("max_normal_brake must be between 0.0 and 1.0 (got {})", self.max_normal_brake));
+        }
+
+        if !self.setpoint_ramp_rate.is_finite() || self.setpoint_ramp_rate < 0.0 {
+            return Err(format!("setpoint_ramp_rate must be non-negative (got {})", self.setpoint_ramp_rate));
+        }
+
+        if !self.deadtime.is_finite() || self.deadtime < 0.0 {
+            return Err(format!("deadtime must be non-negative (got {})", self.deadtime));
+        }
+
+        let emergency = &self.emergency;
+        if !self.comfort_limit.is_finite() || self.comfort_limit <= 0.0 {
+            return Err(format!("comfort_limit must be positive (got {})", self.comfort_limit));
+        }
+        if !emergency.safety_limit.is_finite() || emergency.safety_limit < self.comfort_limit {
+            return Err(format!("emergency.safety_limit ({}) must be at least comfort_limit ({})",
+                               emergency.safety_limit, self.comfort_limit));
+        }
+        if !emergency.emergency_stop_distance.is_finite() || emergency.emergency_stop_distance <= 0.0 {
+            return Err(format!("emergency.emergency_stop_distance must be positive (got {})",
+                               emergency.emergency_stop_distance));
+        }
+        if !emergency.slow_down_distance.is_finite() || emergency.slow_down_distance <= emergency.emergency_stop_distance {
+            return Err(format!("emergency.slow_down_distance ({}) must be greater than emergency.emergency_stop_distance ({})",
+                               emergency.slow_down_distance, emergency.emergency_stop_distance));
+        }
+        if !emergency.max_braking_acceleration.is_finite() || emergency.max_braking_acceleration >= 0.0 {
+            return Err(format!("emergency.max_braking_acceleration must be negative (got {})",
+                               emergency.max_braking_acceleration));
+        }
+        if !emergency.actuator_delay.is_finite() || emergency.actuator_delay < 0.0 {
+            return Err(format!("emergency.actuator_delay must be non-negative (got {})", emergency.actuator_delay));
+        }
+        if !emergency.slow_down_hysteresis.is_finite() || emergency.slow_down_hysteresis < 0.0 {
+            return Err(format!("emergency.slow_down_hysteresis must be non-negative (got {})",
+                               emergency.slow_down_hysteresis));
+        }
+        if emergency.obstacle_window == 0 {
+            return Err("emergency.obstacle_window must be at least 1".to_string());
+        }
+        if emergency.confirm_frames == 0 {
+            return Err("emergency.confirm_frames must be at least 1".to_string());
+        }
+        if !emergency.hard_floor.is_finite() || emergency.hard_floor < 0.0 || emergency.hard_floor > emergency.emergency_stop_distance {
+            return Err(format!("emergency.hard_floor ({}) must be between 0 and emergency.emergency_stop_distance ({})",
+                               emergency.hard_floor, emergency.emergency_stop_distance));
+        }
+        if !emergency.post_emergency_cooldown.is_finite() || emergency.post_emergency_cooldown < 0.0 {
+            return Err(format!("emergency.post_emergency_cooldown must be non-negative (got {})",
+                               emergency.post_emergency_cooldown));
+        }
+        if !emergency.cooldown_acceleration.is_finite() || emergency.cooldown_acceleration < 0.0 {
+            return Err(format!("emergency.cooldown_acceleration must be non-negative (got {})",
+                               emergency.cooldown_acceleration));
+        }
+
+        if !emergency.brake_ramp_time.is_finite() || emergency.brake_ramp_time < 0.0 {
+            return Err(format!("emergency.brake_ramp_time must be non-negative (got {})",
+                               emergency.brake_ramp_time));
+        }
+
+        if !emergency.max_braking_time.is_finite() || emergency.max_braking_time < 0.0 {
+            return Err(format!("emergency.max_braking_time must be non-negative (got {})",
+                               emergency.max_braking_time));
+        }
+
+        let manual_brake = &self.manual_brake;
+        if !manual_brake.brake_threshold.is_finite() || manual_brake.brake_threshold >= 0.0 {
+            return Err(format!("manual_brake.brake_threshold must be negative (got {})", manual_brake.brake_threshold));
+        }
+        if !manual_brake.speed_tolerance.is_finite() || manual_brake.speed_tolerance <= 0.0 {
+            return Err(format!("manual_brake.speed_tolerance must be positive (got {})", manual_brake.speed_tolerance));
+        }
+        if let Some(clearance) = manual_brake.reengage_clearance {
+            if !clearance.is_finite() || clearance < 0.0 {
+                return Err(format!("manual_brake.reengage_clearance must be non-negative (got {})", clearance));
+            }
+        }
+
+        let creep = &self.creep;
+        if !creep.min_throttle.is_finite() || !(0.0..=1.0).contains(&creep.min_throttle) {
+            return Err(format!("creep.min_throttle must be between 0.0 and 1.0 (got {})", creep.min_throttle));
+        }
+        if !creep.speed_threshold.is_finite() || creep.speed_threshold < 0.0 {
+            return Err(format!("creep.speed_threshold must be non-negative (got {})", creep.speed_threshold));
+        }
+
+        let stop_hold = &self.stop_hold;
+        if !stop_hold.brake_level.is_finite() || !(0.0..=1.0).contains(&stop_hold.brake_level) {
+            return Err(format!("stop_hold.brake_level must be between 0.0 and 1.0 (got {})", stop_hold.brake_level));
+        }
+        if !stop_hold.speed_threshold.is_finite() || stop_hold.speed_threshold < 0.0 {
+            return Err(format!("stop_hold.speed_threshold must be non-negative (got {})", stop_hold.speed_threshold));
+        }
+
+        let corridor = &self.corridor;
+        for (name, min, max) in [("width", corridor.min_width, corridor.max_width), ("range", corridor.min_range, corridor.max_range)] {
+            if !min.is_finite() || !max.is_finite() || min <= 0.0 || max < min {
+                return Err(format!("corridor.min_{} ({}) must be positive and at most corridor.max_{} ({})",
+                                   name, min, name, max));
+            }
+        }
+        for (name, per_speed) in [("width_per_speed", corridor.width_per_speed), ("range_per_speed", corridor.range_per_speed)] {
+            if !per_speed.is_finite() || per_speed < 0.0 {
+                return Err(format!("corridor.{} must be non-negative (got {})", name, per_speed));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build a controller with this configuration
+    pub fn build_controller(&self) -> PIDController {
+        let mut pid = PIDController::new_with_emergency_config(
+            self.kp,
+            self.ki,
+            self.kd,
+            self.emergency.emergency_stop_distance,
+            self.emergency.slow_down_distance,
+            self.emergency.max_braking_acceleration,
+        );
+        self.apply_to(&mut pid);
+        pid
+    }
+
+    /// Apply every value to an existing controller through its setters, keeping the PID state
+    pub fn apply_to(&self, pid: &mut PIDController) {
+        pid.set_gains(self.kp, self.ki, self.kd);
+        pid.set_emergency_config(
+            self.emergency.emergency_stop_distance,
+            self.emergency.slow_down_distance,
+            self.emergency.max_braking_acceleration,
+        );
+        if let Err(e) = pid.set_acceleration_limits(self.comfort_limit, self.emergency.safety_limit) {
+            error!(target: logging::CONFIG, "{}", e);
+        }
+        pid.set_integral_leak(self.integral_leak);
+        pid.set_derivative_filter(self.derivative_tau);
+        pid.set_actuator_delay(self.emergency.actuator_delay);
+        pid.set_obstacle_smoothing(self.emergency.obstacle_window, self.emergency.hard_floor);
+        pid.set_emergency_confirmation(self.emergency.confirm_frames);
+        pid.set_post_emergency_cooldown(self.emergency.post_emergency_cooldown, self.emergency.cooldown_acceleration);
+        pid.set_emergency_brake_profile(self.emergency.brake_ramp_time);
+        pid.set_max_braking_time(self.emergency.max_braking_time);
+        pid.set_brake_blend(self.emergency.brake_blend);
+        pid.set_slow_down_hysteresis(self.emergency.slow_down_hysteresis);
+        pid.set_soft_start(self.soft_start_duration);
+        pid.set_coast_band(self.coast_band);
+        pid.set_hold_feedforward(self.hold_feedforward);
+        pid.set_max_steer_rate(self.max_steer_rate);
+        pid.set_max_normal_brake(self.max_normal_brake);
+        pid.set_setpoint_ramp(self.setpoint_ramp_rate);
+        pid.set_predict_with_steering(self.predict_with_steering);
+        pid.set_deadtime(self.deadtime);
+        pid.set_manual_brake_config(self.manual_brake.brake_threshold, self.manual_brake.speed_tolerance);
+        pid.set_reengage_clearance(self.manual_brake.reengage_clearance);
+        pid.set_bumpless_transfer(self.manual_brake.bumpless_transfer);
+        pid.set_creep_config(self.creep.min_throttle, self.creep.speed_threshold);
+        pid.set_stop_hold(self.stop_hold.brake_level, self.stop_hold.speed_threshold);
+        pid.set_corridor(self.corridor);
+    }
+
+    /// Human readable list of the values that differ from `other`, e.g. `kp: 0.05 -> 0.08`
+    pub fn diff(&self, other: &PidConfig) -> Vec<String> {
+        let mut changes = Vec::new();
+        if let (Ok(old), Ok(new)) = (serde_json::to_value(self), serde_json::to_value(other)) {
+            Self::diff_values("", &old, &new, &mut changes);
+        }
+        changes
+    }
+
+    fn diff_values(path: &str, old: &serde_json::Value, new: &serde_json::Value, changes: &mut Vec<String>) {
+        match (old, new) {
+            (serde_json::Value::Object(old), serde_json::Value::Object(new)) => {
+                for (key, old_value) in old {
+                    let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    if let Some(new_value) = new.get(key) {
+                        Self::diff_values(&child, old_value, new_value, changes);
+                    }
+                }
+            }
+            _ if old != new => changes.push(format!("{}: {} -> {}", path, old, new)),
+            _ => {}
+        }
+    }
+}
+
+/// Polls a config file for changes and hands every valid new version to a callback.
+/// Invalid versions are logged and skipped, so the previously applied values stay in effect.
+pub struct ConfigWatcher {
+    stop: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ConfigWatcher {
+    /// Check `path` every `poll_interval` and call `apply(old, new)` when its content changed
+    pub fn start<F>(path: impl Into<PathBuf>, current: PidConfig, poll_interval: Duration, apply: F) -> Self
+    where
+        F: Fn(&PidConfig, &PidConfig) + Send + 'static,
+    {
+        let path = path.into();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_for_worker = Arc::clone(&stop);
+
+        let worker = std::thread::spawn(move || {
+            let modified = |path: &Path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+            let mut last_modified = modified(&path);
+            let mut current = current;
+
+            while !stop_for_worker.load(Ordering::Relaxed) {
+                std::thread::sleep(poll_interval);
+                let now_modified = modified(&path);
+                if now_modified == last_modified {
+                    continue;
+                }
+                last_modified = now_modified;
+
+                match PidConfig::load(&path).and_then(PidConfig::with_env_overrides) {
+                    Ok(config) if config == current => debug!(target: logging::CONFIG, "CONFIG RELOAD: {} touched, values unchanged", path.display()),
+                    Ok(config) => {
+                        apply(&current, &config);
+                        current = config;
+                    }
+                    Err(e) => error!(target: logging::CONFIG, "CONFIG RELOAD: Rejected, keeping the previous values: {}", e),
+                }
+            }
+        });
+
+        info!(target: logging::CONFIG, "Watching PID config for changes");
+        Self { stop, worker: Some(worker) }
+    }
+
+    /// Stop polling and wait for the worker thread to finish
+    pub fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Poll `condition` for up to two seconds
+    fn wait_for(condition: impl Fn() -> bool) -> bool {
+        (0..200).any(|_| {
+            std::thread::sleep(Duration::from_millis(10));
+            condition()
+        })
+    }
+
+    #[test]
+    fn config_change_mid_run_updates_the_gains() {
+        let dir = std::env::temp_dir().join(format!("pid_config_reload_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("pid_config.json");
+        std::fs::write(&path, r#"{ "kp": 0.05, "ki": 0.006, "kd": 0.0 }"#).unwrap();
+
+        let initial = PidConfig::load(&path).unwrap();
+        let controller = Arc::new(Mutex::new(initial.build_controller()));
+        let controller_for_reload = Arc::clone(&controller);
+        let mut watcher = ConfigWatcher::start(&path, initial, Duration::from_millis(10), move |_, new| {
+            new.apply_to(&mut controller_for_reload.lock().unwrap());
+        });
+
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(&path, r#"{ "kp": 0.08, "ki": 0.01, "kd": 0.002 }"#).unwrap();
+        assert!(wait_for(|| controller.lock().unwrap().get_gains() == (0.08, 0.01, 0.002)));
+
+        // An invalid version is rejected and the applied gains stay in effect
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(&path, r#"{ "kp": -1.0, "ki": 0.01, "kd": 0.002 }"#).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(controller.lock().unwrap().get_gains(), (0.08, 0.01, 0.002));
+
+        watcher.shutdown();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn diff_lists_the_changed_values() {
+        let old = PidConfig::default();
+        let new = PidConfig { kp: old.kp + 0.5, ..old.clone() };
+        assert_eq!(old.diff(&new), vec![format!("kp: {} -> {}", old.kp, new.kp)]);
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn environment_overrides_take_precedence_over_the_file() {
+        let file = PidConfig::from_json(r#"{ "kp": 0.05, "ki": 0.006, "kd": 0.001 }"#).unwrap();
+        let env = HashMap::from([("PID_KP", "0.2"), ("PID_KD", " 0.03 "), ("PID_MAX_ACCEL", "1.2")]);
+
+        let config = file.with_overrides(|name| env.get(name).map(|value| value.to_string())).unwrap();
+        assert_eq!((config.kp, config.ki, config.kd, config.comfort_limit), (0.2, 0.006, 0.03, 1.2));
+
+        let pid = config.build_controller();
+        assert_eq!(pid.get_gains(), (0.2, 0.006, 0.03));
+        assert_eq!(pid.get_acceleration_limits().0, 1.2);
+    }
+
+    #[test]
+    fn invalid_environment_overrides_are_rejected() {
+        for value in ["fast", "-0.1"] {
+            let result = PidConfig::default().with_overrides(|name| (name == "PID_KI").then(|| value.to_string()));
+            assert!(result.is_err(), "PID_KI={}", value);
+        }
+    }
+}
@@ -14,9 +14,323 @@
 // limitations under the License.
 //
 
-use log::{info, debug};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+#[cfg(test)]
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use log::{info, debug, warn};
+use serde::{Deserialize, Serialize};
+use crate::logging;
+use pid_core::{self, PidGains, PidState, StepMode, MAX_PID_ACCELERATION};
 use crate::uprotocol_handler::{LidarMeasurement, PointCoords};
 
+/// Smoothing factor of the learned hold acceleration (per tick at target)
+const HOLD_LEARNING_RATE: f64 = 0.05;
+
+/// Clock jitter (s) tolerated before a backwards step of the time is reported as an error
+const NEGATIVE_DT_TOLERANCE: f64 = 0.001;
+
+/// Errors reported by `PIDController`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PidError {
+    /// The clock went backwards by more than the 1 ms tolerance (NEGATIVE_DT_TOLERANCE)
+    NegativeDeltaTime { dt: f64, current: f64, previous: f64 },
+    /// Velocity filter coefficient outside (0.0, 1.0]
+    #[allow(dead_code)]
+    InvalidVelocityFilter { alpha: f64 },
+    /// Comfort limit not positive or above the safety limit
+    InvalidAccelerationLimits { comfort: f64, safety: f64 },
+    /// NaN or infinite input, rejected before it reaches the PID state
+    NonFiniteInput { input: &'static str, value: f64 },
+}
+
+impl fmt::Display for PidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PidError::NegativeDeltaTime { dt, current, previous } => write!(
+                f, "Significant negative delta_time: {:.6} seconds. current_time={:.6}, previous_time={:.6}",
+                dt, current, previous),
+            PidError::InvalidVelocityFilter { alpha } => write!(
+                f, "Velocity filter alpha must be in (0.0, 1.0], got {}", alpha),
+            PidError::InvalidAccelerationLimits { comfort, safety } => write!(
+                f, "Acceleration limits must satisfy 0 < comfort <= safety, got comfort={} safety={}", comfort, safety),
+            PidError::NonFiniteInput { input, value } => write!(
+                f, "Non-finite {}: {}", input, value),
+        }
+    }
+}
+
+impl std::error::Error for PidError {}
+
+/// Source of the wall clock time, e.g. for activation timestamps; replaceable by `MockClock` in tests
+pub trait TimeSource: Send + Sync {
+    /// Time since the Unix epoch
+    fn now(&self) -> Duration;
+}
+
+/// The system clock, used by default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+    }
+}
+
+/// Clock that only moves when told to, for deterministic tests
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: Mutex<Duration>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new(start: Duration) -> Self {
+        Self { now: Mutex::new(start) }
+    }
+
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, step: Duration) {
+        *self.now.lock().unwrap() += step;
+    }
+}
+
+#[cfg(test)]
+impl TimeSource for MockClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Direction of travel the controller is regulating.
+/// In `Reverse`, velocities and setpoints are negative (e.g. -2.0 m/s when backing up).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DriveDirection {
+    Forward,
+    Reverse,
+}
+
+impl DriveDirection {
+    /// Sign that maps world-frame velocity/acceleration into the direction of travel
+    pub fn sign(&self) -> f64 {
+        match self {
+            DriveDirection::Forward => 1.0,
+            DriveDirection::Reverse => -1.0,
+        }
+    }
+}
+
+/// Bundled tuning for a driving style, applied with `PIDController::apply_preset`.
+/// Emergency braking and obstacle settings are safety related and not part of a preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    /// Soft gains, filtered derivative, gentle engage ramp and coasting on small decelerations
+    Comfort,
+    /// Built-in defaults
+    Normal,
+    /// Stiffer gains for quick target tracking, no engage ramp or coasting
+    Sport,
+}
+
+/// Tunables set by a `Preset`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PresetValues {
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    pub derivative_tau: f64,      // seconds
+    pub soft_start_duration: f64, // seconds
+    pub coast_band: f64,          // m/s²
+    pub max_steer_rate: f64,      // steer units per second
+}
+
+impl Preset {
+    /// Parameter set of this preset
+    pub fn values(&self) -> PresetValues {
+        match self {
+            Preset::Comfort => PresetValues {
+                kp: 0.03,
+                ki: 0.00375,
+                kd: 0.003,
+                derivative_tau: 0.2,
+                soft_start_duration: 3.0,
+                coast_band: 0.3,
+                max_steer_rate: 1.0,
+            },
+            Preset::Normal => PresetValues {
+                kp: 0.05,
+                ki: 0.00625,
+                kd: 0.005,
+                derivative_tau: 0.0,
+                soft_start_duration: 0.0,
+                coast_band: 0.0,
+                max_steer_rate: 0.0,
+            },
+            Preset::Sport => PresetValues {
+                kp: 0.08,
+                ki: 0.01,
+                kd: 0.008,
+                derivative_tau: 0.0,
+                soft_start_duration: 0.0,
+                coast_band: 0.0,
+                max_steer_rate: 0.0,
+            },
+        }
+    }
+}
+
+// Define vehicle path constraints
+const PATH_WIDTH: f64 = 3.0; // meters (lane width with some margin), default corridor width
+const MIN_HEIGHT: f64 = 0.3; // meters (ignore ground-level objects)
+const MAX_HEIGHT: f64 = 2.5; // meters (ignore overhead objects)
+const MAX_RANGE: f64 = 30.0; // meters (reasonable detection range), default corridor range
+const MIN_CURVED_STEER: f64 = 0.01; // Below this steering the curved path is treated as straight
+const SAFE_HEADWAY_TIME: f64 = 2.0; // seconds, "two second rule" used for the following distance log
+const MIN_HEADWAY_SPEED: f64 = 0.1; // m/s, below this the headway time is undefined
+const ACCELERATION_WINDOW: usize = 5; // Velocity samples used to estimate the current acceleration
+const RESISTANCE_TIME_CONSTANT: f64 = 10.0; // seconds, averaging of the driving resistance of the deadtime model
+const MIN_FRICTION_COEFFICIENT: f64 = 0.05; // Lower bound of the road friction estimate (ice)
+const STOPPED_SPEED: f64 = 0.1; // m/s, an emergency stop profile releases below this speed
+const STANDSTILL_HOLD_ACCELERATION: f64 = -1.0; // m/s², brake keeping the vehicle stopped after a braking cutout
+
+/// Width and range of the obstacle detection corridor, growing linearly with the speed
+/// from the minimum to the maximum value. Equal minimum and maximum give a constant corridor (default).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Corridor {
+    pub min_width: f64,       // meters, at standstill
+    pub max_width: f64,       // meters
+    pub width_per_speed: f64, // meters of width added per m/s
+    pub min_range: f64,       // meters, at standstill
+    pub max_range: f64,       // meters
+    pub range_per_speed: f64, // meters of range added per m/s (i.e. seconds of look-ahead)
+}
+
+impl Default for Corridor {
+    fn default() -> Self {
+        Self {
+            min_width: PATH_WIDTH,
+            max_width: PATH_WIDTH,
+            width_per_speed: 0.0,
+            min_range: MAX_RANGE,
+            max_range: MAX_RANGE,
+            range_per_speed: 0.0,
+        }
+    }
+}
+
+impl Corridor {
+    /// Corridor (width, range) in meters at `speed` m/s along the direction of travel
+    pub fn at_speed(&self, speed: f64) -> (f64, f64) {
+        let speed = speed.max(0.0);
+        let width = (self.min_width + self.width_per_speed * speed).clamp(self.min_width, self.max_width);
+        let range = (self.min_range + self.range_per_speed * speed).clamp(self.min_range, self.max_range);
+        (width, range)
+    }
+}
+
+/// Geometry used to decide which lidar detections lie in the vehicle's path
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PathModel {
+    /// Straight corridor along the x axis (default)
+    Straight,
+    /// Arc predicted from the steering input using a bicycle model
+    Curved {
+        wheelbase: f64,       // meters
+        max_steer_angle: f64, // radians of front wheel angle at full steering input
+    },
+}
+
+impl PathModel {
+    /// Curved model with typical passenger car geometry (2.9m wheelbase, 70° max wheel angle as in CARLA)
+    pub fn curved() -> Self {
+        PathModel::Curved {
+            wheelbase: 2.9,
+            max_steer_angle: 70.0_f64.to_radians(),
+        }
+    }
+}
+
+/// Vehicle path predicted from the steering input, shared by the cornering speed reduction and
+/// the obstacle detection corridor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PredictedPath {
+    pub speed_factor: f64,        // Factor applied to the target speed for cornering
+    pub turn_radius: Option<f64>, // Signed radius, positive turning right; None = straight corridor
+}
+
+/// Phase of the emergency stop profile (only used when an emergency ramp time is configured)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmergencyBrakePhase {
+    /// No emergency stop in progress
+    Inactive,
+    /// Braking increases from the initial emergency value to max braking
+    Ramp { start_time: f64, initial_acceleration: f64 },
+    /// Max braking until the vehicle has stopped
+    Hold,
+}
+
+/// What to do with a tick that arrives sooner than `min_sample_time` after the previous one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SampleTimeMode {
+    /// Drop the tick and return the previous result
+    Skip,
+    /// Return the previous result and average the velocities of the held-back ticks into the next update
+    Accumulate,
+}
+
+/// How collision-avoidance braking in the slow-down zone combines with the PID output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrakeBlend {
+    /// Gentle braking replaces the PID output once the obstacle is in the nearer half of the zone
+    Override,
+    /// The stronger of the PID output and the distance-scaled obstacle braking
+    Conservative,
+    /// Shift from the PID output to the obstacle braking across the zone, continuous at both edges
+    Weighted,
+}
+
+/// What the controller is doing on a tick, e.g. for an HMI icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlRegime {
+    /// Regular speed regulation
+    Cruise,
+    /// Reduced target speed or gentle braking for an obstacle in the path
+    ObstacleSlowdown,
+    /// Emergency braking, cruise control disengages
+    EmergencyBraking,
+    /// Driver brake detected, or cruise control suspended by it
+    DriverOverride,
+    /// Holding the stopped vehicle (stop hold or braking cutout)
+    StandstillHold,
+}
+
+/// Lidar detection inside the vehicle path corridor, as considered for obstacle braking
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathObstacle {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub intensity: f64,
+    pub forward_distance: f64, // meters along the predicted path
+    pub lateral_offset: f64,   // meters from the path center line
+}
+
+/// Whether the velocity is within `band` (m/s) of the desired velocity, e.g. for "at target" status
+pub fn within_speed_band(desired_velocity: f64, current_velocity: f64, band: f64) -> bool {
+    (desired_velocity - current_velocity).abs() <= band
+}
+
 #[derive(Debug, Clone)]
 pub struct PIDResult {
     pub acceleration: f64,      // Keep for compatibility (m/s²)
@@ -27,6 +341,11 @@ pub struct PIDResult {
     pub manual_brake_detected: bool,
     pub cruise_should_disengage: bool,
     pub cruise_can_reengage: bool,
+    pub at_target: bool,        // Within target_speed_tolerance of the setpoint
+    pub steer: f64,             // Steer passthrough, rate limited by max_steer_rate
+    pub regime: ControlRegime,
+    pub saturated: bool,        // An acceleration limit clipped the PID output
+    pub requested_acceleration: f64, // PID output before the limits (m/s², equals acceleration unless saturated)
 }
 
 impl PIDResult {
@@ -41,6 +360,11 @@ impl PIDResult {
             manual_brake_detected: false,
             cruise_should_disengage: false,
             cruise_can_reengage: false,
+            at_target: false,
+            steer: 0.0,
+            regime: ControlRegime::Cruise,
+            saturated: false,
+            requested_acceleration: acceleration,
         }
     }
     
@@ -55,6 +379,11 @@ impl PIDResult {
             manual_brake_detected: false,
             cruise_should_disengage: true,
             cruise_can_reengage: false,
+            at_target: false,
+            steer: 0.0,
+            regime: ControlRegime::EmergencyBraking,
+            saturated: false,
+            requested_acceleration: acceleration,
         }
     }
     
@@ -69,6 +398,11 @@ impl PIDResult {
             manual_brake_detected: true,
             cruise_should_disengage: true,
             cruise_can_reengage: false,
+            at_target: false,
+            steer: 0.0,
+            regime: ControlRegime::DriverOverride,
+            saturated: false,
+            requested_acceleration: acceleration,
         }
     }
     
@@ -76,59 +410,246 @@ impl PIDResult {
         self.cruise_can_reengage = true;
         self
     }
+
+    /// Emergency braking that keeps cruise control engaged, so the PID keeps ticking (braking only)
+    /// until the emergency stop profile releases
+    pub fn braking_only(mut self) -> Self {
+        self.cruise_should_disengage = false;
+        self
+    }
+
+    pub fn with_regime(mut self, regime: ControlRegime) -> Self {
+        self.regime = regime;
+        self
+    }
+
+    pub fn with_at_target(mut self, at_target: bool) -> Self {
+        self.at_target = at_target;
+        self
+    }
+
+    /// Record the acceleration requested before the limits; saturated when it was clipped.
+    /// Must be applied before `in_direction`.
+    pub fn with_saturation(mut self, requested_acceleration: f64) -> Self {
+        self.requested_acceleration = requested_acceleration;
+        self.saturated = requested_acceleration != self.acceleration;
+        self
+    }
+
+    /// Recompute the pedals so decelerations within `coast_band` m/s² coast instead of braking.
+    /// Must be applied before `in_direction`.
+    pub fn with_coast_band(mut self, coast_band: f64) -> Self {
+        let (throttle, brake) = pid_core::acceleration_to_pedals_with_coast(self.acceleration, coast_band);
+        self.throttle = throttle;
+        self.brake = brake;
+        self
+    }
+
+    /// Raise the throttle to at least `min_throttle` (releasing the brake), e.g. to overcome
+    /// static friction when pulling away. Must be applied before `in_direction`.
+    pub fn with_throttle_floor(mut self, min_throttle: f64) -> Self {
+        if self.throttle < min_throttle {
+            self.throttle = min_throttle;
+            self.brake = 0.0;
+            self.acceleration = pid_core::pedals_to_acceleration(min_throttle, 0.0);
+        }
+        self
+    }
+
+    /// Limit the brake to `max_brake` (0.0-1.0) and recompute the acceleration from it.
+    /// Works on results in the world frame, `direction` restores the sign of the acceleration.
+    pub fn with_brake_cap(mut self, max_brake: f64, direction: DriveDirection) -> Self {
+        if self.brake > max_brake {
+            self.brake = max_brake;
+            self.acceleration = pid_core::pedals_to_acceleration(0.0, max_brake) * direction.sign();
+        }
+        self
+    }
+
+    /// Convert a result computed in the direction of travel into the world frame.
+    /// Throttle/brake stay as computed (throttle in reverse gear when reversing),
+    /// only the sign of the published acceleration flips.
+    pub fn in_direction(mut self, direction: DriveDirection) -> Self {
+        self.acceleration *= direction.sign();
+        self.requested_acceleration *= direction.sign();
+        self
+    }
     
     /// Convert acceleration (m/s²) to throttle/brake values (0.0-1.0)
     /// Uses smart scaling based on speed error for cruise control
     fn acceleration_to_throttle_brake(acceleration: f64) -> (f64, f64) {
-        if acceleration > 0.0 {
-            // Positive acceleration -> throttle
-            // Use progressive scaling: small accelerations get small throttle
-            let throttle = if acceleration <= 0.5 {
-                // For gentle acceleration (0-0.5 m/s²), use 0-20% throttle
-                acceleration * 0.4  // 0.5 * 0.4 = 0.2 (20%)
-            } else if acceleration <= 1.5 {
-                // For moderate acceleration (0.5-1.5 m/s²), use 20-60% throttle
-                0.2 + (acceleration - 0.5) * 0.4  // 20% + up to 40% more
-            } else {
-                // For higher acceleration (1.5+ m/s²), use 60-100% throttle
-                0.6 + (acceleration - 1.5) * 0.267  // 60% + remaining to 100%
-            };
-            (throttle.min(1.0).max(0.0), 0.0)
-        } else {
-            // Negative acceleration -> brake
-            let abs_decel = -acceleration;
-            let brake = if abs_decel <= 0.5 {
-                // For gentle braking (0-0.5 m/s²), use 0-15% brake
-                abs_decel * 0.3  // 0.5 * 0.3 = 0.15 (15%)
-            } else if abs_decel <= 2.0 {
-                // For moderate braking (0.5-2.0 m/s²), use 15-50% brake
-                0.15 + (abs_decel - 0.5) * 0.233  // 15% + up to 35% more
-            } else {
-                // For hard braking (2.0+ m/s²), use 50-100% brake
-                0.5 + (abs_decel - 2.0) * 0.083  // 50% + remaining to 100%
-            };
-            (0.0, brake.min(1.0).max(0.0))
+        pid_core::acceleration_to_pedals(acceleration)
+    }
+
+    /// Inverse of `acceleration_to_throttle_brake`: recover the acceleration (m/s²) from pedal values.
+    /// Brake takes precedence when both pedals are applied. Saturated pedals map to the last breakpoint.
+    pub fn throttle_brake_to_acceleration(throttle: f64, brake: f64) -> f64 {
+        pid_core::pedals_to_acceleration(throttle, brake)
+    }
+
+    /// Difference between an acceleration and its pedal round trip (forward map, then inverse).
+    /// Zero (within floating point error) for accelerations inside the non-saturated pedal range.
+    #[cfg(test)]
+    pub fn round_trip_error(acceleration: f64) -> f64 {
+        let (throttle, brake) = Self::acceleration_to_throttle_brake(acceleration);
+        (Self::throttle_brake_to_acceleration(throttle, brake) - acceleration).abs()
+    }
+
+    /// Verify the pedal map invariants: each curve starts at (0, 0), ends at full pedal
+    /// and is strictly increasing, so the map is continuous and monotonic across breakpoints
+    #[cfg(test)]
+    pub fn check_pedal_map() -> Result<(), String> {
+        for (name, curve) in [("throttle", &pid_core::THROTTLE_CURVE), ("brake", &pid_core::BRAKE_CURVE)] {
+            if curve[0] != (0.0, 0.0) {
+                return Err(format!("{} curve must start at (0, 0)", name));
+            }
+            if curve[curve.len() - 1].1 != 1.0 {
+                return Err(format!("{} curve must end at full pedal", name));
+            }
+            for pair in curve.windows(2) {
+                if pair[1].0 <= pair[0].0 || pair[1].1 <= pair[0].1 {
+                    return Err(format!("{} curve is not strictly increasing between {:?} and {:?}", 
+                                       name, pair[0], pair[1]));
+                }
+            }
         }
+        Ok(())
     }
 }
 
+/// Every tunable of a `PIDController`, used to record the exact parameters of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControllerSnapshot {
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    pub integral_leak: f64,
+    pub derivative_tau: f64,
+    pub comfort_limit: f64,
+    pub safety_limit: f64,
+    pub emergency_stop_distance: f64,
+    pub slow_down_distance: f64,
+    pub max_braking_acceleration: f64,
+    pub friction_coefficient: f64,
+    pub actuator_delay: f64,
+    pub manual_brake_threshold: f64,
+    pub target_speed_tolerance: f64,
+    pub reengage_clearance: Option<f64>,
+    pub bumpless_transfer: bool,
+    pub drive_direction: DriveDirection,
+    pub min_sample_time: f64,
+    pub min_effective_dt: f64,
+    pub sample_time_mode: SampleTimeMode,
+    pub velocity_filter_alpha: f64,
+    pub path_model: PathModel,
+    pub predict_with_steering: bool,
+    pub corridor: Corridor,
+    pub obstacle_window_size: usize,
+    pub emergency_hard_floor: f64,
+    pub emergency_confirm_frames: usize,
+    pub post_emergency_cooldown: f64,
+    pub cooldown_acceleration: f64,
+    pub soft_start_duration: f64,
+    pub coast_band: f64,
+    pub emergency_ramp_time: f64,
+    pub max_braking_time: f64,
+    pub brake_blend: BrakeBlend,
+    pub slow_down_hysteresis: f64,
+    pub hold_feedforward: bool,
+    pub creep_min_throttle: f64,
+    pub creep_speed_threshold: f64,
+    pub max_steer_rate: f64,
+    pub max_normal_brake: f64,
+    pub setpoint_ramp_rate: f64,
+    pub deadtime: f64,
+    pub stop_hold_brake: f64,
+    pub stop_hold_speed: f64,
+}
+
 pub struct PIDController {
-    kp: f64,
-    ki: f64,
-    kd: f64,
-    velocity_error: f64,
-    previous_error: f64,
-    accumulated_error: f64,
+    gains: PidGains,
+    pid_state: PidState,                // Error terms of the core PID step
     previous_time: f64,
     // Emergency brake configuration
     emergency_stop_distance: f64,
     slow_down_distance: f64,
     max_braking_acceleration: f64,
+    friction_coefficient: f64,          // Road friction relative to dry asphalt (1.0), scales braking
+    safety_limit: f64,                  // m/s², largest deceleration emergency paths may command
+    actuator_delay: f64,                // Seconds before a brake command takes effect
     // Manual brake detection
     previous_velocity: f64,
     manual_brake_threshold: f64, // Deceleration threshold to detect manual braking
     cruise_suspended: bool,      // Track if cruise control is temporarily suspended
     target_speed_tolerance: f64, // How close to target speed before re-engaging
+    reengage_clearance: Option<f64>, // Meters of clear path needed to re-engage (None = slow_down_distance)
+    bumpless_transfer: bool,     // Start the PID from the current acceleration after a re-engage
+    bumpless_acceleration: Option<f64>, // Acceleration to continue from on the next PID step
+    velocity_history: VecDeque<(f64, f64)>, // Recent (time, travel speed) samples
+    drive_direction: DriveDirection, // Forward (default) or Reverse for parking maneuvers
+    // Sample time enforcement
+    min_sample_time: f64,              // Minimum seconds between PID updates (0.0 = disabled)
+    min_effective_dt: f64,             // Time step used for duplicate or slightly backwards timestamps
+    sample_time_mode: SampleTimeMode,  // Skip or accumulate ticks arriving too fast
+    last_pid_result: Option<PIDResult>, // Returned for ticks rejected by min_sample_time
+    accumulated_velocity: (f64, u32),   // Sum and count of the velocities held back in Accumulate mode
+    // Measured velocity low-pass filter
+    velocity_filter_alpha: f64,         // 1.0 = no filtering, smaller = smoother
+    filtered_velocity: Option<f64>,     // Last filtered velocity (None until first sample)
+    path_model: PathModel,              // Geometry of the obstacle detection corridor
+    predict_with_steering: bool,        // Bend the corridor with the steering even for a Straight path model
+    corridor: Corridor,                 // Speed dependent width and range of the corridor
+    corridor_speed: f64,                // m/s, travel speed of the last tick, sizes the corridor
+    // Obstacle persistence filter
+    obstacle_window: VecDeque<f64>,     // Recent closest in-path distances (f64::MAX = none)
+    obstacle_window_size: usize,        // Number of lidar observations considered (1 = no smoothing)
+    emergency_hard_floor: f64,          // Detections closer than this brake without persistence check
+    emergency_confirm_frames: usize,    // Consecutive emergency observations before disengaging (1 = immediately)
+    emergency_frames: usize,            // Current run of consecutive emergency observations
+    // Post-emergency cooldown
+    post_emergency_cooldown: f64,       // Seconds during which acceleration stays capped (0.0 = disabled)
+    cooldown_acceleration: f64,         // Acceleration cap right after the event, m/s²
+    last_emergency_time: Option<f64>,   // Last tick with emergency or collision-avoidance braking
+    // Emergency stop profile
+    emergency_ramp_time: f64,           // Seconds to ramp to max braking (0.0 = single urgency-based value)
+    emergency_phase: EmergencyBrakePhase,
+    // Continuous braking cutout
+    max_braking_time: f64,              // Seconds of emergency braking at standstill before holding (0.0 = disabled)
+    braking_onset: Option<f64>,         // Start of the current continuous emergency braking
+    emergency_braking_tick: bool,       // The last tick applied emergency braking
+    standstill_hold: bool,              // Stopped after a braking cutout, emergency braking is no longer recomputed
+    brake_blend: BrakeBlend,            // Combination of collision-avoidance braking and PID output
+    slow_down_hysteresis: f64,          // Meters beyond the slow-down distance an obstacle must reach to leave the zone
+    in_slow_down: bool,                 // The last lidar frame had an obstacle in the slow-down zone
+    // Stop hold
+    stop_hold_brake: f64,               // Brake (0.0-1.0) holding the stopped vehicle without a setpoint (0.0 = disabled)
+    stop_hold_speed: f64,               // m/s, the vehicle counts as stopped below this speed
+    stop_held: bool,                    // The last tick applied the stop hold brake
+    saturated: bool,                    // The last PID step was clipped by an acceleration limit
+    // Soft start after activation
+    soft_start_duration: f64,           // Seconds to ramp the acceleration limit up after engage (0.0 = disabled)
+    activation_time: Option<f64>,       // Time of the first tick after the last reset
+    coast_band: f64,                    // Decelerations down to this many m/s² coast without braking
+    // Hold feedforward
+    hold_feedforward: bool,             // Use a learned hold acceleration inside the target tolerance band
+    hold_acceleration: Option<f64>,     // Learned steady-state acceleration along the direction of travel
+    // Creep
+    creep_min_throttle: f64,            // Throttle floor when pulling away (0.0 = disabled)
+    creep_speed_threshold: f64,         // m/s, creep below this speed
+    // Steer passthrough
+    max_steer_rate: f64,                // Max change of the steer output per second (0.0 = unlimited)
+    previous_steer: Option<(f64, f64)>, // Last steer output and its time
+    max_normal_brake: f64,              // Brake cap (0.0-1.0) outside emergency and manual braking (1.0 = none)
+    // Setpoint ramp
+    setpoint_ramp_rate: f64,            // m/s², max change of the effective setpoint (0.0 = step to the setpoint)
+    ramped_setpoint: Option<f64>,       // Effective setpoint, None until the first tick after engage
+    // Deadtime compensation (Smith predictor)
+    deadtime: f64,                      // Seconds between a command and its effect on the velocity (0.0 = disabled)
+    commands_in_flight: VecDeque<(f64, f64)>, // (duration, acceleration) of the PID commands within the deadtime
+    resistance: f64,                    // m/s², slowly averaged command that does not accelerate the vehicle
+    last_command: Option<f64>,          // PID acceleration of the previous step, along the direction of travel
+    non_finite_inputs: u64,             // Ticks rejected for a NaN/Inf input
+    time_source: Arc<dyn TimeSource>,   // Wall clock, the system clock unless replaced for tests
 }
 
 impl PIDController {
@@ -145,20 +666,74 @@ impl PIDController {
         max_braking_acceleration: f64
     ) -> Self {
         PIDController {
-            kp,
-            ki,
-            kd,
-            velocity_error: 0.0,
-            previous_error: 0.0,
-            accumulated_error: 0.0,
+            gains: PidGains { kp, ki, kd, integral_leak: 0.0, derivative_tau: 0.0, comfort_limit: MAX_PID_ACCELERATION },
+            pid_state: PidState::default(),
             previous_time: 0.0,
             emergency_stop_distance,
             slow_down_distance,
             max_braking_acceleration,
+            friction_coefficient: 1.0,
+            safety_limit: 10.0,
+            actuator_delay: 0.0,
             previous_velocity: 0.0,
             manual_brake_threshold: -2.0, // Detect manual braking at -2 m/s² or more
             cruise_suspended: false,
             target_speed_tolerance: 2.0,   // Re-engage when within 2 m/s of target
+            reengage_clearance: None,
+            bumpless_transfer: false,
+            bumpless_acceleration: None,
+            velocity_history: VecDeque::new(),
+            drive_direction: DriveDirection::Forward,
+            min_sample_time: 0.0,
+            min_effective_dt: 0.001,
+            sample_time_mode: SampleTimeMode::Skip,
+            last_pid_result: None,
+            accumulated_velocity: (0.0, 0),
+            velocity_filter_alpha: 1.0,
+            filtered_velocity: None,
+            path_model: PathModel::Straight,
+            predict_with_steering: false,
+            corridor: Corridor::default(),
+            corridor_speed: 0.0,
+            obstacle_window: VecDeque::new(),
+            obstacle_window_size: 1,
+            emergency_hard_floor: 0.0,
+            emergency_confirm_frames: 1,
+            emergency_frames: 0,
+            post_emergency_cooldown: 0.0,
+            cooldown_acceleration: 0.5,
+            last_emergency_time: None,
+            emergency_ramp_time: 0.0,
+            emergency_phase: EmergencyBrakePhase::Inactive,
+            max_braking_time: 0.0,
+            brake_blend: BrakeBlend::Override,
+            slow_down_hysteresis: 0.0,
+            in_slow_down: false,
+            braking_onset: None,
+            emergency_braking_tick: false,
+            standstill_hold: false,
+            stop_hold_brake: 0.0,
+            stop_hold_speed: STOPPED_SPEED,
+            stop_held: false,
+            saturated: false,
+            soft_start_duration: 0.0,
+            activation_time: None,
+            coast_band: 0.0,
+            hold_feedforward: false,
+            hold_acceleration: None,
+            creep_min_throttle: 0.0,
+            creep_speed_threshold: 0.5,
+            max_steer_rate: 0.0,
+            previous_steer: None,
+            max_normal_brake: 1.0,
+            setpoint_ramp_rate: 0.0,
+            ramped_setpoint: None,
+            deadtime: 0.0,
+            commands_in_flight: VecDeque::new(),
+            resistance: 0.0,
+            last_command: None,
+            non_finite_inputs: 0,
+            time_source: Arc::new(SystemClock),
         }
     }
 
@@ -184,10 +759,27 @@ impl PIDController {
         (self.manual_brake_threshold, self.target_speed_tolerance, self.cruise_suspended)
     }
 
+    /// Distance (m) the path must be clear of obstacles before a suspended cruise control may
+    /// re-engage (None = slow_down_distance)
+    pub fn set_reengage_clearance(&mut self, clearance: Option<f64>) {
+        self.reengage_clearance = clearance.map(|clearance| clearance.max(0.0));
+    }
+
+    /// Clear path distance in meters currently required for re-engagement
+    pub fn get_reengage_clearance(&self) -> f64 {
+        self.reengage_clearance.unwrap_or(self.slow_down_distance)
+    }
+
     /// Force cruise control suspension (for testing)
     pub fn suspend_cruise_control(&mut self) {
         self.cruise_suspended = true;
-        info!("Cruise control manually suspended");
+        info!(target: logging::ENGAGE, "Cruise control manually suspended");
+    }
+
+    /// Lift a suspension without waiting for the re-engage conditions
+    pub fn resume_cruise_control(&mut self) {
+        self.cruise_suspended = false;
+        info!(target: logging::ENGAGE, "Cruise control manually resumed");
     }
 
     /// Check if cruise control is currently suspended
@@ -195,230 +787,2384 @@ impl PIDController {
         self.cruise_suspended
     }
 
-    pub fn compute(
-        &mut self, 
-        desired_velocity: f64, 
-        current_velocity: f64, 
-        current_time: f64, 
-        lidar_data: Option<&LidarMeasurement>,
-        throttle_input: f64,  // 0.0-1.0 from driver/control system
-        steer_input: f64,     // 0.0-1.0 steering amount
-        brake_input: f64      // 0.0-1.0 from driver/control system
-    ) -> Result<PIDResult, String> {
-        if self.previous_time == 0.0 {
-            self.previous_time = current_time;
-            self.previous_velocity = current_velocity;
-            return Ok(PIDResult::new(0.0));
-        }
+    /// Bumpless transfer: when cruise control re-engages after a driver brake, initialize the integral
+    /// so the first PID output equals the current (coasting) acceleration instead of jumping
+    pub fn set_bumpless_transfer(&mut self, enabled: bool) {
+        self.bumpless_transfer = enabled;
+    }
 
-        let delta_time = current_time - self.previous_time;
-        self.previous_time = current_time;
-        
-        // Detect manual braking by analyzing velocity change
-        let velocity_change = current_velocity - self.previous_velocity;
-        let actual_acceleration = if delta_time > 0.0 { velocity_change / delta_time } else { 0.0 };
-        
-        // Check for manual braking using actual brake input
-        const BRAKE_THRESHOLD: f64 = 0.1; // 10% brake input triggers manual brake detection
-        let manual_brake_detected = brake_input > BRAKE_THRESHOLD;
-        
-        if manual_brake_detected {
-            info!("MANUAL BRAKE DETECTED: Brake input {:.1}% detected, suspending cruise control", brake_input * 100.0);
-            self.cruise_suspended = true;
-            self.previous_velocity = current_velocity;
-            return Ok(PIDResult::manual_brake(-brake_input * 3.0)); // Convert brake % to deceleration
-        }
-        
-        // Check if cruise control can be re-engaged
-        let speed_difference = (desired_velocity - current_velocity).abs();
-        let can_reengage = self.cruise_suspended && 
-                          speed_difference <= self.target_speed_tolerance &&
-                          current_velocity > 0.0 && // Must be moving
-                          actual_acceleration >= -0.5; // Not braking hard
-        
-        if can_reengage {
-            info!("CRUISE CONTROL RE-ENGAGEMENT: Speed difference {:.1} m/s is within tolerance {:.1} m/s", 
-                  speed_difference, self.target_speed_tolerance);
-            self.cruise_suspended = false;
-        }
-        
-        if self.cruise_suspended {
-            self.previous_velocity = current_velocity;
-            let result = PIDResult::new(0.0); // No PID intervention
-            return Ok(if can_reengage { result.with_reengage_capability() } else { result });
-        }
+    /// Whether bumpless transfer on re-engagement is enabled
+    pub fn get_bumpless_transfer(&self) -> bool {
+        self.bumpless_transfer
+    }
 
-        // Apply steering compensation - reduce desired speed when turning
-        let steering_factor = Self::calculate_steering_compensation(steer_input);
-        let adjusted_desired_velocity = desired_velocity * steering_factor;
-        
-        if steering_factor < 1.0 {
-            let direction = if steer_input > 0.0 { "right" } else { "left" };
-            info!("STEERING COMPENSATION: Reducing target speed from {:.1} to {:.1} m/s due to {:.1}% {} steering", 
-                  desired_velocity, adjusted_desired_velocity, steer_input.abs() * 100.0, direction);
+    /// Select the direction of travel; switching direction clears the PID error history
+    pub fn set_drive_direction(&mut self, direction: DriveDirection) {
+        if self.drive_direction != direction {
+            self.pid_state = PidState::default();
+            info!(target: logging::CONTROL, "Drive direction set to {:?}", direction);
         }
+        self.drive_direction = direction;
+    }
 
-        // Check for obstacles using lidar data and print closest position
-        let mut modified_desired_velocity = adjusted_desired_velocity;
-        if let Some(lidar) = lidar_data {
-            if !lidar.is_empty && !lidar.detections.is_empty() {
-                // Find the closest detection in the vehicle's path
-                let mut closest_distance = f64::MAX;
-                let mut closest_position: Option<&PointCoords> = None;
-                
-                // Define vehicle path constraints
-                const PATH_WIDTH: f64 = 3.0; // meters (lane width with some margin)
-                const MIN_HEIGHT: f64 = 0.3; // meters (ignore ground-level objects)
-                const MAX_HEIGHT: f64 = 2.5; // meters (ignore overhead objects)
-                const MAX_RANGE: f64 = 30.0; // meters (reasonable detection range)
-                
-                for detection in &lidar.detections {
-                    let point = &detection.point;
-                
-                    if point.x > 1.0 && point.x < MAX_RANGE &&  // In front, with 1m minimum
-                       point.y.abs() < PATH_WIDTH / 2.0 &&      // Within lane width
-                       point.z > MIN_HEIGHT && point.z < MAX_HEIGHT { // At vehicle height
-                        
-                        // Use only forward distance for path-blocking obstacles
-                        let forward_distance = point.x;
-                        
-                        if forward_distance < closest_distance {
-                            closest_distance = forward_distance;
-                            closest_position = Some(point);
-                        }
-                    }
-                }
-                
-                if let Some(pos) = closest_position {
-                    info!("LIDAR: Closest obstacle in vehicle path at position: x={:.2}m, y={:.2}m, z={:.2}m, forward_distance={:.2}m", 
-                          pos.x, pos.y, pos.z, closest_distance);
-                    
-                    // Calculate velocity-dependent safety distances
-                    let velocity_factor = (current_velocity / 10.0).max(1.0); // Scale with velocity, min factor of 1
-                    let dynamic_emergency_distance = self.emergency_stop_distance * velocity_factor;
-                    let dynamic_slow_down_distance = self.slow_down_distance * velocity_factor;
-                    
-                    if closest_distance < dynamic_emergency_distance {
-                        info!("EMERGENCY BRAKE: Obstacle in vehicle path at {:.2}m forward distance! (threshold: {:.2}m)", 
-                              closest_distance, dynamic_emergency_distance);
-                        
-                        // Calculate emergency brake intensity based on distance and velocity
-                        let urgency_factor = 1.0 - (closest_distance / dynamic_emergency_distance);
-                        let emergency_acceleration = self.max_braking_acceleration * urgency_factor.max(0.5);
-                        
-                        let reason = format!("Obstacle detected at {:.1}m (emergency threshold: {:.1}m)", 
-                                            closest_distance, dynamic_emergency_distance);
-                        
-                        let result = PIDResult::emergency(emergency_acceleration, reason);
-                        info!("EMERGENCY BRAKE: Applying {:.2} m/s² braking (brake: {:.1}%) - CRUISE CONTROL WILL BE DISENGAGED", 
-                              emergency_acceleration, result.brake * 100.0);
-                        return Ok(result);
-                    } else if closest_distance < dynamic_slow_down_distance {
-                        // Gradual speed reduction with distance-based intensity
-                        let distance_factor = (closest_distance - dynamic_emergency_distance) / 
-                                             (dynamic_slow_down_distance - dynamic_emergency_distance);
-                        let brake_intensity = 1.0 - distance_factor;
-                        
-                        // Apply both speed reduction and gentle braking
-                        modified_desired_velocity = desired_velocity * distance_factor.max(0.2); // Don't go below 20% of desired speed
-                        
-                        info!("COLLISION AVOIDANCE: Reducing speed to {:.2} m/s due to obstacle at {:.2}m forward distance (threshold: {:.2}m)", 
-                              modified_desired_velocity, closest_distance, dynamic_slow_down_distance);
-                        
-                        // If we need aggressive slowing, apply immediate gentle braking
-                        if brake_intensity > 0.5 {
-                            let gentle_brake = self.max_braking_acceleration * 0.3 * brake_intensity;
-                            let result = PIDResult::new(gentle_brake.max(-1.0));
-                            info!("COLLISION AVOIDANCE: Applying gentle braking {:.2} m/s² (brake: {:.1}%)", 
-                                  gentle_brake, result.brake * 100.0);
-                            return Ok(result);
-                        }
-                    }
-                }
-            }
+    /// Get the current direction of travel
+    pub fn get_drive_direction(&self) -> DriveDirection {
+        self.drive_direction
+    }
+
+    /// Configure the first-order low-pass filter on the measured velocity.
+    /// `alpha` in (0.0, 1.0]: filtered = alpha * raw + (1 - alpha) * previous filtered; 1.0 disables filtering.
+    pub fn set_velocity_filter(&mut self, alpha: f64) -> Result<(), PidError> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(PidError::InvalidVelocityFilter { alpha });
         }
+        self.velocity_filter_alpha = alpha;
+        Ok(())
+    }
 
-        if delta_time <= 0.0 {
-            if delta_time < -0.001 {
-                return Err(format!("Significant negative delta_time: {:.6} seconds. current_time={:.6}, previous_time={:.6}", 
-                                 delta_time, current_time, self.previous_time));
-            } else {
-                let result = self.compute_pid(modified_desired_velocity, current_velocity, 0.001)?;
-                self.previous_velocity = current_velocity;
-                return Ok(result);
-            }
+    /// Two acceleration tiers (m/s², magnitudes): regular PID output, soft start and cooldown stay
+    /// within `comfort`, emergency braking may use up to `safety` but no more. Requires 0 < comfort <= safety.
+    pub fn set_acceleration_limits(&mut self, comfort: f64, safety: f64) -> Result<(), PidError> {
+        if !(comfort > 0.0 && comfort.is_finite() && safety >= comfort && safety.is_finite()) {
+            return Err(PidError::InvalidAccelerationLimits { comfort, safety });
         }
+        self.gains.comfort_limit = comfort;
+        self.safety_limit = safety;
+        Ok(())
+    }
 
-        let result = self.compute_pid(modified_desired_velocity, current_velocity, delta_time)?;
-        self.previous_velocity = current_velocity;
-        Ok(result)
+    /// Set the road friction estimate relative to dry asphalt (1.0, default), clamped to
+    /// [0.05, 1.0]. All obstacle braking is scaled down to what the road can transmit and the
+    /// emergency and slow-down distances grow by `1 / friction_coefficient` to match.
+    pub fn set_friction_coefficient(&mut self, friction_coefficient: f64) {
+        self.friction_coefficient = friction_coefficient.clamp(MIN_FRICTION_COEFFICIENT, 1.0);
     }
 
-    fn compute_pid(&mut self, desired_velocity: f64, current_velocity: f64, delta_time: f64) -> Result<PIDResult, String> {
-        // Check if we're significantly over the desired speed (more than 15% overspeed)
-        if current_velocity > desired_velocity + (desired_velocity * 0.15) {
-            // Apply gentle negative acceleration (braking) when we need to slow down
-            let speed_excess = current_velocity - desired_velocity;
-            
-            // Use a much gentler braking approach
-            let gentle_braking = if speed_excess > 2.0 {
-                -1.0  // Maximum gentle braking for significant overspeed
-            } else {
-                -speed_excess * 0.8  // Proportional gentle braking
-            };
-            let result = PIDResult::new(gentle_braking);
-            info!("SPEED CONTROL: Applying gentle braking {:.2} m/s² (brake: {:.1}%) for speed excess {:.1} m/s", 
-                  gentle_braking, result.brake * 100.0, speed_excess);
-            return Ok(result);
-        }
-        
-        // Normal PID control for acceleration and gentle deceleration
-        self.previous_error = self.velocity_error;
-        self.velocity_error = desired_velocity - current_velocity;
-        self.accumulated_error += self.velocity_error * delta_time;
-        let derivative_error = (self.velocity_error - self.previous_error) / delta_time;
-        let acceleration = (self.kp * self.velocity_error)
-            + (self.ki * self.accumulated_error)
-            + (self.kd * derivative_error);
-        
-        // Limit acceleration to gentler values
-        let limited_acceleration = acceleration.max(-1.5).min(1.5); // Much gentler limits: -1.5 to +3 m/s²
-        let result = PIDResult::new(limited_acceleration);
-        
-        if limited_acceleration > 0.0 {
-            debug!("PID CONTROL: Throttle {:.1}% ({:.2} m/s²)", result.throttle * 100.0, limited_acceleration);
-        } else if limited_acceleration < 0.0 {
-            debug!("PID CONTROL: Brake {:.1}% ({:.2} m/s²)", result.brake * 100.0, limited_acceleration);
-        }
-        
-        Ok(result)
+    /// Get the road friction estimate
+    #[allow(dead_code)]
+    pub fn get_friction_coefficient(&self) -> f64 {
+        self.friction_coefficient
     }
 
-    /// Calculate speed reduction factor based on steering input
-    /// More steering = more speed reduction for safer cornering
-    /// steer_input: -1.0 (full left) to 1.0 (full right)
-    fn calculate_steering_compensation(steer_input: f64) -> f64 {
-        // Use absolute value since turning left or right both require speed reduction
-        let abs_steering = steer_input.abs();
-        
-        const MAX_SPEED_REDUCTION: f64 = 0.8; // Maximum 20% speed reduction at full steering
-        const STEERING_SENSITIVITY: f64 = 0.3; // Start reducing at 30% steering (0.3 abs value)
-        
-        if abs_steering <= STEERING_SENSITIVITY {
-            1.0 // No speed reduction for gentle steering
-        } else {
-            // Progressive speed reduction: 30% steering = 100% speed, 100% steering = 80% speed
-            let reduction_factor = (abs_steering - STEERING_SENSITIVITY) / (1.0 - STEERING_SENSITIVITY);
-            1.0 - (reduction_factor * (1.0 - MAX_SPEED_REDUCTION))
-        }
+    /// `max_braking_acceleration` achievable at the current road friction
+    fn achievable_braking(&self) -> f64 {
+        self.max_braking_acceleration * self.friction_coefficient
     }
 
-    pub fn reset(&mut self) {
-        self.velocity_error = 0.0;
-        self.previous_error = 0.0;
-        self.accumulated_error = 0.0;
-        self.previous_time = 0.0;
-        self.previous_velocity = 0.0;
-        self.cruise_suspended = false;
+    /// Get the (comfort, safety) acceleration limits
+    pub fn get_acceleration_limits(&self) -> (f64, f64) {
+        (self.gains.comfort_limit, self.safety_limit)
+    }
+
+    /// Get the velocity filter coefficient
+    pub fn get_velocity_filter(&self) -> f64 {
+        self.velocity_filter_alpha
+    }
+
+    /// Replace the wall clock, e.g. with a `MockClock` for deterministic tests
+    #[cfg(test)]
+    pub fn set_time_source(&mut self, time_source: Arc<dyn TimeSource>) {
+        self.time_source = time_source;
+    }
+
+    /// Current wall clock time since the Unix epoch, from the configured time source
+    pub fn wall_time(&self) -> Duration {
+        self.time_source.now()
+    }
+
+    /// Number of `compute` calls rejected because an input was NaN or infinite
+    #[allow(dead_code)]
+    pub fn non_finite_input_count(&self) -> u64 {
+        self.non_finite_inputs
+    }
+
+    /// Last filtered velocity used by the control logic, if any sample was received
+    pub fn filtered_velocity(&self) -> Option<f64> {
+        self.filtered_velocity
+    }
+
+    /// Select the path model used to filter lidar detections
+    pub fn set_path_model(&mut self, path_model: PathModel) {
+        self.path_model = path_model;
+    }
+
+    /// Get the path model used to filter lidar detections
+    pub fn get_path_model(&self) -> PathModel {
+        self.path_model
+    }
+
+    /// Predict the vehicle path from the steering input for both the cornering speed reduction and
+    /// the obstacle corridor, so a turn always slows the vehicle and bends the corridor together.
+    /// Uses the geometry of a `Curved` path model, or `PathModel::curved()` for `Straight`.
+    pub fn set_predict_with_steering(&mut self, enabled: bool) {
+        self.predict_with_steering = enabled;
     }
-}
\ No newline at end of file
+
+    /// Whether the steering bends the obstacle corridor regardless of the path model
+    #[allow(dead_code)]
+    pub fn get_predict_with_steering(&self) -> bool {
+        self.predict_with_steering
+    }
+
+    /// Path predicted from `steer_input` (-1.0 full left to 1.0 full right)
+    pub fn predicted_path(&self, steer_input: f64) -> PredictedPath {
+        let path_model = match self.path_model {
+            PathModel::Straight if self.predict_with_steering => PathModel::curved(),
+            path_model => path_model,
+        };
+        let turn_radius = match path_model {
+            // Bicycle model: signed turn radius, positive when turning right (towards +y)
+            PathModel::Curved { wheelbase, max_steer_angle } if steer_input.abs() > MIN_CURVED_STEER => {
+                Some(wheelbase / (steer_input * max_steer_angle).tan())
+            }
+            _ => None,
+        };
+        PredictedPath {
+            speed_factor: pid_core::steering_compensation(steer_input),
+            turn_radius,
+        }
+    }
+
+    /// Configure the speed dependent width and range of the obstacle detection corridor
+    pub fn set_corridor(&mut self, corridor: Corridor) {
+        self.corridor = corridor;
+    }
+
+    /// Get the obstacle detection corridor configuration
+    pub fn get_corridor(&self) -> Corridor {
+        self.corridor
+    }
+
+    /// Configure the obstacle persistence filter: emergency braking uses the median closest distance
+    /// over the last `window_size` lidar observations, while detections closer than `hard_floor`
+    /// meters still trigger immediately. A window of 1 reacts to every single frame.
+    pub fn set_obstacle_smoothing(&mut self, window_size: usize, hard_floor: f64) {
+        self.obstacle_window_size = window_size.max(1);
+        self.emergency_hard_floor = hard_floor.max(0.0);
+        while self.obstacle_window.len() > self.obstacle_window_size {
+            self.obstacle_window.pop_front();
+        }
+    }
+
+    /// Get obstacle persistence filter configuration (window size, hard floor)
+    pub fn get_obstacle_smoothing(&self) -> (usize, f64) {
+        (self.obstacle_window_size, self.emergency_hard_floor)
+    }
+
+    /// Require an obstacle in the emergency zone for `frames` consecutive lidar observations before
+    /// emergency braking disengages cruise control; earlier observations only brake gently (1 = immediately)
+    pub fn set_emergency_confirmation(&mut self, frames: usize) {
+        self.emergency_confirm_frames = frames.max(1);
+    }
+
+    /// Get the number of consecutive observations confirming an emergency
+    pub fn get_emergency_confirmation(&self) -> usize {
+        self.emergency_confirm_frames
+    }
+
+    /// Time in seconds between a brake command and its effect; the distance travelled meanwhile
+    /// is added to the emergency and slow-down distances
+    pub fn set_actuator_delay(&mut self, actuator_delay: f64) {
+        self.actuator_delay = actuator_delay.max(0.0);
+    }
+
+    /// Get actuator delay in seconds
+    pub fn get_actuator_delay(&self) -> f64 {
+        self.actuator_delay
+    }
+
+    /// Emergency stop profile: once an emergency brake triggers, braking ramps from the urgency-based
+    /// value to `max_braking_acceleration` over `ramp_time` seconds, holds until the vehicle has
+    /// stopped, then releases (0.0 = single urgency-based value per tick)
+    pub fn set_emergency_brake_profile(&mut self, ramp_time: f64) {
+        self.emergency_ramp_time = ramp_time.max(0.0);
+        if self.emergency_ramp_time == 0.0 {
+            self.emergency_phase = EmergencyBrakePhase::Inactive;
+        }
+    }
+
+    /// Get the emergency stop profile ramp time in seconds
+    pub fn get_emergency_brake_profile(&self) -> f64 {
+        self.emergency_ramp_time
+    }
+
+    /// Current phase of the emergency stop profile
+    pub fn emergency_brake_phase(&self) -> EmergencyBrakePhase {
+        self.emergency_phase
+    }
+
+    /// Safety cutout: after `max_braking_time` seconds of continuous emergency braking with the vehicle
+    /// already stopped, hold it with a gentle brake and stop re-evaluating emergency braking
+    /// until it moves again or the controller is reset (0.0 = disabled)
+    pub fn set_max_braking_time(&mut self, max_braking_time: f64) {
+        self.max_braking_time = max_braking_time.max(0.0);
+    }
+
+    /// Select how collision-avoidance braking in the slow-down zone combines with the PID output.
+    /// With `Conservative` or `Weighted` the PID keeps running towards the reduced target speed,
+    /// so the command does not jump when the obstacle moves between zones.
+    pub fn set_brake_blend(&mut self, brake_blend: BrakeBlend) {
+        self.brake_blend = brake_blend;
+    }
+
+    /// Hysteresis of the slow-down zone: once an obstacle has entered it, collision avoidance
+    /// continues until the obstacle is beyond the slow-down distance plus `margin` meters, so
+    /// distance noise at the boundary does not toggle the regime every frame (0.0 = disabled)
+    pub fn set_slow_down_hysteresis(&mut self, margin: f64) {
+        self.slow_down_hysteresis = margin.max(0.0);
+    }
+
+    /// Get the slow-down hysteresis margin in meters
+    #[allow(dead_code)]
+    pub fn get_slow_down_hysteresis(&self) -> f64 {
+        self.slow_down_hysteresis
+    }
+
+    /// Get the collision-avoidance brake blending mode
+    #[allow(dead_code)]
+    pub fn get_brake_blend(&self) -> BrakeBlend {
+        self.brake_blend
+    }
+
+    /// Cap the brake of all non-emergency braking (PID, overspeed, collision avoidance, holds) at
+    /// `max_brake` (0.0-1.0); emergency braking and the driver's brake still reach full brake
+    pub fn set_max_normal_brake(&mut self, max_brake: f64) {
+        self.max_normal_brake = max_brake.max(0.0).min(1.0);
+    }
+
+    /// Setpoint ramp: the effective target moves from the speed at engage towards the setpoint by
+    /// at most `max_rate` m/s² instead of jumping to it, so a large gap does not command full
+    /// acceleration at once (0.0 = disabled)
+    pub fn set_setpoint_ramp(&mut self, max_rate: f64) {
+        self.setpoint_ramp_rate = max_rate.max(0.0);
+    }
+
+    /// Deadtime compensation: with `deadtime` seconds between a command and the first velocity
+    /// change, the PID sees the measured velocity plus the change still expected from the
+    /// commands of the last `deadtime` seconds (a Smith predictor with an integrator plant
+    /// model), so it does not keep pushing while earlier commands are still on their way.
+    /// 0.0 (default) disables it.
+    pub fn set_deadtime(&mut self, deadtime: f64) {
+        self.deadtime = deadtime.max(0.0);
+        if self.deadtime == 0.0 {
+            self.commands_in_flight.clear();
+            self.resistance = 0.0;
+        }
+    }
+
+    /// Get the compensated deadtime in seconds
+    #[allow(dead_code)]
+    pub fn get_deadtime(&self) -> f64 {
+        self.deadtime
+    }
+
+    /// Get the setpoint ramp rate in m/s²
+    pub fn get_setpoint_ramp(&self) -> f64 {
+        self.setpoint_ramp_rate
+    }
+
+    /// Effective setpoint of the ramp (m/s), None when the ramp is disabled or not started
+    pub fn ramped_setpoint(&self) -> Option<f64> {
+        self.ramped_setpoint
+    }
+
+    /// Get the brake cap of non-emergency braking
+    pub fn get_max_normal_brake(&self) -> f64 {
+        self.max_normal_brake
+    }
+
+    /// Get the continuous braking cutout time in seconds
+    pub fn get_max_braking_time(&self) -> f64 {
+        self.max_braking_time
+    }
+
+    /// Whether the controller is holding the vehicle after a continuous braking cutout
+    pub fn is_standstill_hold(&self) -> bool {
+        self.standstill_hold
+    }
+
+    /// Stop hold: below `speed_threshold` m/s with a zero setpoint, or while cruise control is suspended,
+    /// apply `brake_level` (0.0-1.0) so the vehicle cannot roll away on a grade. A positive setpoint
+    /// (or a re-engage, which resets the controller) releases it (brake_level 0.0 = disabled)
+    pub fn set_stop_hold(&mut self, brake_level: f64, speed_threshold: f64) {
+        self.stop_hold_brake = brake_level.max(0.0).min(1.0);
+        self.stop_hold_speed = speed_threshold.max(0.0);
+    }
+
+    /// Get stop hold configuration (brake_level, speed_threshold)
+    pub fn get_stop_hold(&self) -> (f64, f64) {
+        (self.stop_hold_brake, self.stop_hold_speed)
+    }
+
+    /// Whether the last tick held the stopped vehicle with the stop hold brake
+    pub fn is_stop_held(&self) -> bool {
+        self.stop_held
+    }
+
+    /// Cap acceleration for `cooldown` seconds after an emergency or collision-avoidance brake.
+    /// The cap starts at `initial_acceleration` m/s² and rises linearly back to the normal limit.
+    pub fn set_post_emergency_cooldown(&mut self, cooldown: f64, initial_acceleration: f64) {
+        self.post_emergency_cooldown = cooldown.max(0.0);
+        self.cooldown_acceleration = initial_acceleration.max(0.0);
+    }
+
+    /// Get post-emergency cooldown configuration (duration, initial acceleration cap)
+    pub fn get_post_emergency_cooldown(&self) -> (f64, f64) {
+        (self.post_emergency_cooldown, self.cooldown_acceleration)
+    }
+
+    /// Ramp the acceleration limit linearly from 0 to the normal limit over `duration` seconds
+    /// after the controller is (re)activated (0.0 disables the soft start)
+    pub fn set_soft_start(&mut self, duration: f64) {
+        self.soft_start_duration = duration.max(0.0);
+    }
+
+    /// Get soft start ramp duration in seconds
+    pub fn get_soft_start(&self) -> f64 {
+        self.soft_start_duration
+    }
+
+    /// Leaky integrator: the accumulated error decays by `integral_leak * dt` each step so old
+    /// error is slowly forgotten (0.0 = pure integration)
+    pub fn set_integral_leak(&mut self, integral_leak: f64) {
+        self.gains.integral_leak = integral_leak.max(0.0);
+    }
+
+    pub fn get_integral_leak(&self) -> f64 {
+        self.gains.integral_leak
+    }
+
+    /// Low-pass the derivative term with time constant `tau_d` seconds to reduce noise amplification
+    /// (0.0 = raw derivative)
+    pub fn set_derivative_filter(&mut self, tau_d: f64) {
+        self.gains.derivative_tau = tau_d.max(0.0);
+    }
+
+    pub fn get_derivative_filter(&self) -> f64 {
+        self.gains.derivative_tau
+    }
+
+    /// Let PID decelerations down to `coast_band` m/s² coast (no throttle, no brake) instead of braking (0.0 = disabled)
+    pub fn set_coast_band(&mut self, coast_band: f64) {
+        self.coast_band = coast_band.max(0.0);
+    }
+
+    /// Get coast band in m/s²
+    pub fn get_coast_band(&self) -> f64 {
+        self.coast_band
+    }
+
+    /// Inside the target tolerance band, replace the integral term by a hold acceleration learned
+    /// from the steady output while at target, avoiding dips below the setpoint
+    pub fn set_hold_feedforward(&mut self, enabled: bool) {
+        self.hold_feedforward = enabled;
+        if !enabled {
+            self.hold_acceleration = None;
+        }
+    }
+
+    /// Limit how fast the steer passthrough in `PIDResult::steer` may change, in steer units
+    /// per second (a full left-to-right sweep is 2.0), to avoid abrupt steering commands (0.0 = unlimited)
+    pub fn set_max_steer_rate(&mut self, max_steer_rate: f64) {
+        self.max_steer_rate = max_steer_rate.max(0.0);
+    }
+
+    /// Get the steer rate limit per second
+    pub fn get_max_steer_rate(&self) -> f64 {
+        self.max_steer_rate
+    }
+
+    /// Learned hold acceleration (m/s², direction of travel), None until learned or when disabled
+    pub fn hold_acceleration(&self) -> Option<f64> {
+        self.hold_acceleration
+    }
+
+    /// Creep: below `speed_threshold` m/s with a positive setpoint, apply at least `min_throttle`
+    /// (0.0-1.0) so small PID outputs still overcome static friction (min_throttle 0.0 = disabled)
+    pub fn set_creep_config(&mut self, min_throttle: f64, speed_threshold: f64) {
+        self.creep_min_throttle = min_throttle.max(0.0).min(1.0);
+        self.creep_speed_threshold = speed_threshold.max(0.0);
+    }
+
+    /// Get creep configuration (min_throttle, speed_threshold)
+    pub fn get_creep_config(&self) -> (f64, f64) {
+        (self.creep_min_throttle, self.creep_speed_threshold)
+    }
+
+    /// Replace the proportional, integral and derivative gains, keeping the PID state
+    pub fn set_gains(&mut self, kp: f64, ki: f64, kd: f64) {
+        self.gains.kp = kp;
+        self.gains.ki = ki;
+        self.gains.kd = kd;
+    }
+
+    /// Get the (kp, ki, kd) gains
+    pub fn get_gains(&self) -> (f64, f64, f64) {
+        (self.gains.kp, self.gains.ki, self.gains.kd)
+    }
+
+    /// Switch the driving style at once; keeps the PID state so it can be applied while engaged
+    pub fn apply_preset(&mut self, preset: Preset) {
+        let values = preset.values();
+        self.set_gains(values.kp, values.ki, values.kd);
+        self.set_derivative_filter(values.derivative_tau);
+        self.set_soft_start(values.soft_start_duration);
+        self.set_coast_band(values.coast_band);
+        self.set_max_steer_rate(values.max_steer_rate);
+        info!(target: logging::CONFIG, "PRESET: Applied {:?} tuning {:?}", preset, values);
+    }
+
+    /// Snapshot of every tunable for reproducing a run
+    pub fn config_snapshot(&self) -> ControllerSnapshot {
+        ControllerSnapshot {
+            kp: self.gains.kp,
+            ki: self.gains.ki,
+            kd: self.gains.kd,
+            integral_leak: self.gains.integral_leak,
+            derivative_tau: self.gains.derivative_tau,
+            comfort_limit: self.gains.comfort_limit,
+            safety_limit: self.safety_limit,
+            emergency_stop_distance: self.emergency_stop_distance,
+            slow_down_distance: self.slow_down_distance,
+            max_braking_acceleration: self.max_braking_acceleration,
+            friction_coefficient: self.friction_coefficient,
+            actuator_delay: self.actuator_delay,
+            manual_brake_threshold: self.manual_brake_threshold,
+            target_speed_tolerance: self.target_speed_tolerance,
+            reengage_clearance: self.reengage_clearance,
+            bumpless_transfer: self.bumpless_transfer,
+            drive_direction: self.drive_direction,
+            min_sample_time: self.min_sample_time,
+            min_effective_dt: self.min_effective_dt,
+            sample_time_mode: self.sample_time_mode,
+            velocity_filter_alpha: self.velocity_filter_alpha,
+            path_model: self.path_model,
+            predict_with_steering: self.predict_with_steering,
+            corridor: self.corridor,
+            obstacle_window_size: self.obstacle_window_size,
+            emergency_hard_floor: self.emergency_hard_floor,
+            emergency_confirm_frames: self.emergency_confirm_frames,
+            post_emergency_cooldown: self.post_emergency_cooldown,
+            cooldown_acceleration: self.cooldown_acceleration,
+            soft_start_duration: self.soft_start_duration,
+            coast_band: self.coast_band,
+            emergency_ramp_time: self.emergency_ramp_time,
+            max_braking_time: self.max_braking_time,
+            brake_blend: self.brake_blend,
+            slow_down_hysteresis: self.slow_down_hysteresis,
+            hold_feedforward: self.hold_feedforward,
+            creep_min_throttle: self.creep_min_throttle,
+            creep_speed_threshold: self.creep_speed_threshold,
+            max_steer_rate: self.max_steer_rate,
+            max_normal_brake: self.max_normal_brake,
+            setpoint_ramp_rate: self.setpoint_ramp_rate,
+            deadtime: self.deadtime,
+            stop_hold_brake: self.stop_hold_brake,
+            stop_hold_speed: self.stop_hold_speed,
+        }
+    }
+
+    /// Current integral contribution to the PID output (Ki * accumulated error)
+    pub fn integral_term(&self) -> f64 {
+        self.pid_state.integral_term(&self.gains)
+    }
+
+    /// Configure the minimum time between PID updates to protect the derivative term
+    /// from bursty message arrival (0.0 disables the check)
+    pub fn set_min_sample_time(&mut self, min_sample_time: f64, mode: SampleTimeMode) {
+        self.min_sample_time = min_sample_time.max(0.0);
+        self.sample_time_mode = mode;
+    }
+
+    /// Get sample time enforcement configuration
+    pub fn get_min_sample_time(&self) -> (f64, SampleTimeMode) {
+        (self.min_sample_time, self.sample_time_mode)
+    }
+
+    /// Time step (s) the PID uses when a tick has the same timestamp as the previous one or one
+    /// up to 1 ms earlier; must be positive so the derivative term stays finite
+    pub fn set_min_effective_dt(&mut self, min_effective_dt: f64) {
+        if min_effective_dt > 0.0 {
+            self.min_effective_dt = min_effective_dt;
+        }
+    }
+
+    /// Get the time step used for duplicate timestamps
+    pub fn get_min_effective_dt(&self) -> f64 {
+        self.min_effective_dt
+    }
+
+    /// Run one control tick at `current_time` (seconds) and return the actuation command.
+    ///
+    /// The time step is `current_time` minus the time of the previous tick; the first tick after a
+    /// reset only records the time and returns zero acceleration. A time step of zero (duplicate
+    /// timestamp) or a backwards step of at most 1 ms (clock jitter) runs the PID with
+    /// `min_effective_dt` instead. A larger backwards step returns `PidError::NegativeDeltaTime`
+    /// carrying the step and both timestamps; the controller then measures the next tick from
+    /// `current_time`.
+    pub fn compute(
+        &mut self, 
+        desired_velocity: f64, 
+        current_velocity: f64, 
+        current_time: f64, 
+        lidar_data: Option<&LidarMeasurement>,
+        throttle_input: f64,  // 0.0-1.0 from driver/control system
+        steer_input: f64,     // 0.0-1.0 steering amount
+        brake_input: f64      // 0.0-1.0 from driver/control system
+    ) -> Result<PIDResult, PidError> {
+        // A NaN would stay in the accumulated error for good, so such a tick leaves the state untouched
+        let inputs = [
+            ("desired_velocity", desired_velocity),
+            ("current_velocity", current_velocity),
+            ("current_time", current_time),
+            ("throttle_input", throttle_input),
+            ("steer_input", steer_input),
+            ("brake_input", brake_input),
+        ];
+        if let Some((input, value)) = inputs.into_iter().find(|(_, value)| !value.is_finite()) {
+            self.non_finite_inputs += 1;
+            return Err(PidError::NonFiniteInput { input, value });
+        }
+
+        let mut result = self.compute_longitudinal(
+            desired_velocity, current_velocity, current_time, lidar_data, throttle_input, steer_input, brake_input,
+        )?;
+        if !result.emergency_brake_engaged && !result.manual_brake_detected {
+            result = result.with_brake_cap(self.max_normal_brake, self.drive_direction);
+        }
+        result.steer = self.limit_steer_rate(steer_input, current_time);
+        Ok(result)
+    }
+
+    /// Steer passthrough, moving from the previous output towards `steer_input` by at most
+    /// `max_steer_rate` per second
+    fn limit_steer_rate(&mut self, steer_input: f64, current_time: f64) -> f64 {
+        let steer = match self.previous_steer {
+            Some((previous_steer, previous_time)) if self.max_steer_rate > 0.0 => {
+                let max_change = self.max_steer_rate * (current_time - previous_time).max(0.0);
+                steer_input.clamp(previous_steer - max_change, previous_steer + max_change)
+            }
+            _ => steer_input,
+        };
+        self.previous_steer = Some((steer, current_time));
+        steer
+    }
+
+    // Throttle/brake part of `compute`
+    fn compute_longitudinal(
+        &mut self,
+        desired_velocity: f64,
+        current_velocity: f64,
+        current_time: f64,
+        lidar_data: Option<&LidarMeasurement>,
+        throttle_input: f64,
+        steer_input: f64,
+        brake_input: f64,
+    ) -> Result<PIDResult, PidError> {
+        // All control logic below works on the filtered velocity
+        let current_velocity = self.filter_velocity(current_velocity);
+
+        if self.previous_time == 0.0 {
+            self.previous_time = current_time;
+            self.previous_velocity = current_velocity;
+            self.activation_time = Some(current_time);
+            return Ok(PIDResult::new(0.0));
+        }
+
+        // Ticks arriving too fast are measured from the last tick that ran the PID, so a steady
+        // stream faster than min_sample_time still updates the PID once per min_sample_time
+        let delta_time = current_time - self.previous_time;
+        let sample_too_fast = delta_time >= 0.0 && delta_time < self.min_sample_time;
+        if !sample_too_fast {
+            self.previous_time = current_time;
+        }
+        
+        // Detect manual braking by analyzing velocity change
+        let velocity_change = current_velocity - self.previous_velocity;
+        let actual_acceleration = if delta_time > 0.0 { velocity_change / delta_time } else { 0.0 };
+        // Velocity and acceleration along the direction of travel (identical to world frame when driving forward)
+        let direction_sign = self.drive_direction.sign();
+        let travel_speed = current_velocity * direction_sign;
+        let travel_acceleration = actual_acceleration * direction_sign;
+        self.corridor_speed = travel_speed;
+        self.velocity_history.push_back((current_time, travel_speed));
+        while self.velocity_history.len() > ACCELERATION_WINDOW {
+            self.velocity_history.pop_front();
+        }
+
+        // Continuous emergency braking is tracked across ticks; a tick without it restarts the count
+        if !std::mem::take(&mut self.emergency_braking_tick) {
+            self.braking_onset = None;
+        }
+        
+        // Check for manual braking using actual brake input
+        const BRAKE_THRESHOLD: f64 = 0.1; // 10% brake input triggers manual brake detection
+        let manual_brake_detected = brake_input > BRAKE_THRESHOLD;
+        
+        if manual_brake_detected {
+            info!(target: logging::ENGAGE, "MANUAL BRAKE DETECTED: Brake input {:.1}% detected, suspending cruise control", brake_input * 100.0);
+            self.cruise_suspended = true;
+            self.previous_velocity = current_velocity;
+            return Ok(PIDResult::manual_brake(-brake_input * 3.0).in_direction(self.drive_direction)); // Convert brake % to deceleration
+        }
+        
+        // Check if cruise control can be re-engaged
+        let speed_difference = (desired_velocity - current_velocity).abs();
+        let can_reengage = self.cruise_suspended && 
+                          speed_difference <= self.target_speed_tolerance &&
+                          travel_speed > 0.0 && // Must be moving
+                          travel_acceleration >= -0.5 && // Not braking hard
+                          self.path_clear_for_reengage(lidar_data, steer_input);
+        
+        if can_reengage {
+            info!(target: logging::ENGAGE, "CRUISE CONTROL RE-ENGAGEMENT: Speed difference {} is within tolerance {}", 
+                  logging::speed(speed_difference), logging::speed(self.target_speed_tolerance));
+            self.cruise_suspended = false;
+            if self.bumpless_transfer {
+                self.bumpless_acceleration = Some(self.estimated_acceleration());
+            }
+        }
+        
+        if self.cruise_suspended {
+            self.previous_velocity = current_velocity;
+            self.ramped_setpoint = None; // Ramp again from the speed at re-engagement
+            if let Some(result) = self.stop_hold_step(travel_speed, true) {
+                return Ok(result);
+            }
+            let result = PIDResult::new(0.0).with_regime(ControlRegime::DriverOverride); // No PID intervention
+            return Ok(if can_reengage { result.with_reengage_capability() } else { result });
+        }
+
+        let desired_velocity = self.ramp_setpoint(desired_velocity, current_velocity, delta_time);
+
+        // Apply steering compensation - reduce desired speed when turning
+        let steering_factor = self.predicted_path(steer_input).speed_factor;
+        let adjusted_desired_velocity = desired_velocity * steering_factor;
+        
+        if steering_factor < 1.0 {
+            let direction = if steer_input > 0.0 { "right" } else { "left" };
+            info!(target: logging::TARGET, "STEERING COMPENSATION: Reducing target speed from {} to {} due to {:.1}% {} steering", 
+                  logging::speed(desired_velocity), logging::speed(adjusted_desired_velocity), steer_input.abs() * 100.0, direction);
+        }
+
+        // After a braking cutout, keep the vehicle stopped without fighting the plant
+        if self.standstill_hold {
+            if travel_speed > STOPPED_SPEED {
+                info!(target: logging::EMERGENCY, "BRAKE CUTOUT: Vehicle moving at {}, leaving standstill hold", logging::speed(travel_speed));
+                self.standstill_hold = false;
+            } else {
+                self.previous_velocity = current_velocity;
+                return Ok(PIDResult::new(STANDSTILL_HOLD_ACCELERATION)
+                    .with_regime(ControlRegime::StandstillHold)
+                    .in_direction(self.drive_direction));
+            }
+        }
+
+        // Without a setpoint, keep the stopped vehicle from rolling
+        if let Some(result) = self.stop_hold_step(travel_speed, desired_velocity * direction_sign <= 0.0) {
+            self.previous_velocity = current_velocity;
+            return Ok(result);
+        }
+
+        // A running emergency stop profile continues until the vehicle has stopped
+        if let Some(result) = self.emergency_profile_step(travel_speed, current_time) {
+            self.previous_velocity = current_velocity;
+            return Ok(result);
+        }
+
+        // Check for obstacles using lidar data and print closest position
+        let mut modified_desired_velocity = adjusted_desired_velocity;
+        let mut obstacle_slowdown = false;
+        // Collision-avoidance braking and its intensity, blended with the PID output below
+        let mut obstacle_brake: Option<(f64, f64)> = None;
+        // Lidar path analysis only covers the area in front of the vehicle
+        let lidar_data = if self.drive_direction == DriveDirection::Forward { lidar_data } else { None };
+        if let Some(lidar) = lidar_data {
+            // Only an uninterrupted run of emergency observations confirms an obstacle
+            let previous_emergency_frames = std::mem::take(&mut self.emergency_frames);
+            if !lidar.is_empty && !lidar.detections.is_empty() {
+                // Find the closest detection in the vehicle's path
+                let mut closest_distance = f64::MAX;
+                let mut closest_position: Option<&PointCoords> = None;
+                
+                for detection in &lidar.detections {
+                    let point = &detection.point;
+                
+                    // Use only forward distance along the predicted path for path-blocking obstacles
+                    if let Some(forward_distance) = self.path_forward_distance(point, steer_input) {
+                        if forward_distance < closest_distance {
+                            closest_distance = forward_distance;
+                            closest_position = Some(point);
+                        }
+                    }
+                }
+
+                // Emergency braking requires the obstacle to persist over the smoothing window,
+                // except for detections below the hard floor which brake immediately
+                let persistent_distance = self.record_obstacle_distance(closest_distance);
+                let emergency_check_distance = if closest_distance < self.emergency_hard_floor {
+                    closest_distance
+                } else {
+                    persistent_distance
+                };
+                
+                if let Some(pos) = closest_position {
+                    info!(target: logging::LIDAR, "LIDAR: Closest obstacle in vehicle path at position: x={:.2}m, y={:.2}m, z={:.2}m, forward_distance={:.2}m", 
+                          pos.x, pos.y, pos.z, closest_distance);
+                    
+                    // Calculate velocity-dependent safety distances
+                    let (dynamic_emergency_distance, dynamic_slow_down_distance) = self.braking_distances(current_velocity, travel_speed);
+                    // An obstacle already in the zone has to clear the hysteresis margin to leave it
+                    let slow_down_exit_distance = if self.in_slow_down {
+                        dynamic_slow_down_distance + self.slow_down_hysteresis
+                    } else {
+                        dynamic_slow_down_distance
+                    };
+                    self.in_slow_down = false;
+
+                    let safe_distance = Self::safe_following_distance(travel_speed, self.emergency_stop_distance);
+                    match Self::headway_time(closest_distance, travel_speed) {
+                        Some(headway) => info!(target: logging::LIDAR, "LIDAR: Headway {:.2}s at {} (safe following distance {:.1}m, emergency {:.1}m, slow down {:.1}m)",
+                                               headway, logging::speed(travel_speed), safe_distance, dynamic_emergency_distance, dynamic_slow_down_distance),
+                        None => info!(target: logging::LIDAR, "LIDAR: Vehicle (nearly) stopped, headway undefined (safe following distance {:.1}m, emergency {:.1}m, slow down {:.1}m)",
+                                      safe_distance, dynamic_emergency_distance, dynamic_slow_down_distance),
+                    }
+                    
+                    if emergency_check_distance < dynamic_emergency_distance {
+                        self.emergency_frames = previous_emergency_frames + 1;
+                        if self.emergency_frames < self.emergency_confirm_frames && closest_distance >= self.emergency_hard_floor {
+                            // Possibly a lidar artifact: brake gently but keep cruise control engaged
+                            let gentle_brake = (self.achievable_braking() * 0.3).max(-1.0);
+                            let result = PIDResult::new(gentle_brake).with_regime(ControlRegime::ObstacleSlowdown);
+                            self.last_emergency_time = Some(current_time);
+                            info!(target: logging::EMERGENCY, "EMERGENCY BRAKE: Unconfirmed obstacle at {:.2}m ({}/{} observations), applying gentle braking {:.2} m/s²",
+                                  emergency_check_distance, self.emergency_frames, self.emergency_confirm_frames, gentle_brake);
+                            return Ok(result);
+                        }
+
+                        info!(target: logging::EMERGENCY, "EMERGENCY BRAKE: Obstacle in vehicle path at {:.2}m forward distance! (threshold: {:.2}m)", 
+                              emergency_check_distance, dynamic_emergency_distance);
+                        
+                        // Calculate emergency brake intensity based on distance and velocity
+                        let urgency_factor = 1.0 - (emergency_check_distance / dynamic_emergency_distance);
+                        let emergency_acceleration = (self.achievable_braking() * urgency_factor.max(0.5)).max(-self.safety_limit);
+                        
+                        let reason = format!("Obstacle detected at {:.1}m (emergency threshold: {:.1}m)", 
+                                            emergency_check_distance, dynamic_emergency_distance);
+                        
+                        let mut result = PIDResult::emergency(emergency_acceleration, reason);
+                        self.last_emergency_time = Some(current_time);
+                        self.record_emergency_braking(travel_speed, current_time);
+                        if self.emergency_ramp_time > 0.0 {
+                            self.emergency_phase = EmergencyBrakePhase::Ramp {
+                                start_time: current_time,
+                                initial_acceleration: emergency_acceleration,
+                            };
+                            // The stop profile needs the following ticks, cruise control disengages on release
+                            result = result.braking_only();
+                        }
+                        info!(target: logging::EMERGENCY, "EMERGENCY BRAKE: Applying {:.2} m/s² braking (brake: {:.1}%) - {}",
+                              emergency_acceleration, result.brake * 100.0,
+                              if result.cruise_should_disengage { "CRUISE CONTROL WILL BE DISENGAGED" } else { "stop profile running until standstill" });
+                        return Ok(result);
+                    } else if closest_distance < slow_down_exit_distance {
+                        self.in_slow_down = true;
+                        obstacle_slowdown = true;
+                        // Gradual speed reduction with distance-based intensity, none inside the hysteresis margin
+                        let distance_factor = ((closest_distance - dynamic_emergency_distance) /
+                                              (dynamic_slow_down_distance - dynamic_emergency_distance)).min(1.0);
+                        let brake_intensity = 1.0 - distance_factor;
+                        
+                        // Apply both speed reduction and gentle braking. Weighted blending slows down through the
+                        // obstacle braking alone: a reduced target would switch the PID to overspeed braking mid-zone.
+                        if self.brake_blend != BrakeBlend::Weighted {
+                            modified_desired_velocity = desired_velocity * distance_factor.max(0.2); // Don't go below 20% of desired speed
+
+                            info!(target: logging::EMERGENCY, "COLLISION AVOIDANCE: Reducing speed to {} due to obstacle at {:.2}m forward distance (threshold: {:.2}m)", 
+                                  logging::speed(modified_desired_velocity), closest_distance, dynamic_slow_down_distance);
+                        }
+                        
+                        if self.brake_blend != BrakeBlend::Override {
+                            let gentle_brake = (self.achievable_braking() * 0.3 * brake_intensity).max(-1.0);
+                            obstacle_brake = Some((gentle_brake, brake_intensity));
+                        } else if brake_intensity > 0.5 {
+                            // If we need aggressive slowing, apply immediate gentle braking
+                            let gentle_brake = self.achievable_braking() * 0.3 * brake_intensity;
+                            let result = PIDResult::new(gentle_brake.max(-1.0)).with_regime(ControlRegime::ObstacleSlowdown);
+                            self.last_emergency_time = Some(current_time);
+                            info!(target: logging::EMERGENCY, "COLLISION AVOIDANCE: Applying gentle braking {:.2} m/s² (brake: {:.1}%)", 
+                                  gentle_brake, result.brake * 100.0);
+                            return Ok(result);
+                        }
+                    }
+                } else {
+                    // Detections, but none in the path
+                    self.in_slow_down = false;
+                }
+            } else {
+                // Empty frame: nothing in the path
+                self.record_obstacle_distance(f64::MAX);
+                self.in_slow_down = false;
+            }
+        }
+
+        if sample_too_fast {
+            debug!(target: logging::CONTROL, "SAMPLE TIME: delta_time {:.6}s below minimum {:.6}s, {:?} tick", 
+                   delta_time, self.min_sample_time, self.sample_time_mode);
+            if self.sample_time_mode == SampleTimeMode::Accumulate {
+                self.accumulated_velocity.0 += current_velocity;
+                self.accumulated_velocity.1 += 1;
+            }
+            return Ok(self.last_pid_result.clone().unwrap_or_else(|| PIDResult::new(0.0)));
+        }
+        let (accumulated_sum, accumulated_count) = std::mem::take(&mut self.accumulated_velocity);
+        let current_velocity = (accumulated_sum + current_velocity) / (accumulated_count + 1) as f64;
+
+        let regime = if obstacle_slowdown || modified_desired_velocity != adjusted_desired_velocity {
+            ControlRegime::ObstacleSlowdown
+        } else {
+            ControlRegime::Cruise
+        };
+
+        let result = if delta_time <= 0.0 {
+            if delta_time < -NEGATIVE_DT_TOLERANCE {
+                // previous_time has already been advanced, recover the value the delta was taken from
+                return Err(PidError::NegativeDeltaTime {
+                    dt: delta_time,
+                    current: current_time,
+                    previous: current_time - delta_time,
+                });
+            }
+            debug!(target: logging::CONTROL, "SAMPLE TIME: delta_time {:.6}s, using min_effective_dt {:.6}s", delta_time, self.min_effective_dt);
+            self.compute_pid(modified_desired_velocity, current_velocity, self.min_effective_dt)?
+        } else {
+            self.compute_pid(modified_desired_velocity, current_velocity, delta_time)?
+        };
+        let result = self.apply_acceleration_caps(result, current_time).with_regime(regime);
+        let result = match obstacle_brake {
+            Some((obstacle_acceleration, intensity)) => self.blend_obstacle_braking(result, obstacle_acceleration, intensity, current_time),
+            None => result,
+        };
+        self.previous_velocity = current_velocity;
+        Ok(result)
+    }
+
+    /// Combine the PID output with the collision-avoidance braking of an obstacle in the slow-down
+    /// zone; `intensity` rises from 0 at the far edge of the zone to 1 at the emergency distance
+    fn blend_obstacle_braking(&mut self, result: PIDResult, obstacle_acceleration: f64, intensity: f64, current_time: f64) -> PIDResult {
+        let acceleration = match self.brake_blend {
+            BrakeBlend::Override => return result,
+            BrakeBlend::Conservative => result.acceleration.min(obstacle_acceleration),
+            BrakeBlend::Weighted => (1.0 - intensity) * result.acceleration + intensity * obstacle_acceleration,
+        };
+        if intensity > 0.5 {
+            // Same zone in which Override brakes, so the post-emergency cooldown behaves alike
+            self.last_emergency_time = Some(current_time);
+        }
+        info!(target: logging::EMERGENCY, "COLLISION AVOIDANCE: {:?} blend of PID {:.2} m/s² and obstacle braking {:.2} m/s² gives {:.2} m/s²",
+              self.brake_blend, result.acceleration, obstacle_acceleration, acceleration);
+        PIDResult::new(acceleration)
+            .with_at_target(result.at_target)
+            .with_regime(ControlRegime::ObstacleSlowdown)
+    }
+
+    /// Braking of a running emergency stop profile, None when no profile is running. Once the
+    /// vehicle has stopped the brake is released and cruise control disengages
+    fn emergency_profile_step(&mut self, travel_speed: f64, current_time: f64) -> Option<PIDResult> {
+        let acceleration = match self.emergency_phase {
+            EmergencyBrakePhase::Inactive => return None,
+            _ if travel_speed <= STOPPED_SPEED => {
+                info!(target: logging::EMERGENCY, "EMERGENCY BRAKE: Vehicle stopped, releasing emergency stop profile - CRUISE CONTROL WILL BE DISENGAGED");
+                self.emergency_phase = EmergencyBrakePhase::Inactive;
+                let result = PIDResult::emergency(0.0, "Emergency stop profile released at standstill".to_string());
+                return Some(result.in_direction(self.drive_direction));
+            }
+            EmergencyBrakePhase::Ramp { start_time, initial_acceleration } => {
+                let progress = ((current_time - start_time) / self.emergency_ramp_time).min(1.0);
+                if progress >= 1.0 {
+                    self.emergency_phase = EmergencyBrakePhase::Hold;
+                }
+                initial_acceleration + (self.achievable_braking() - initial_acceleration) * progress
+            }
+            EmergencyBrakePhase::Hold => self.achievable_braking(),
+        }.max(-self.safety_limit);
+
+        self.last_emergency_time = Some(current_time);
+        self.record_emergency_braking(travel_speed, current_time);
+        let reason = format!("Emergency stop profile ({:?}) at {}", self.emergency_phase, logging::speed(travel_speed));
+        let result = PIDResult::emergency(acceleration, reason).braking_only();
+        info!(target: logging::EMERGENCY, "EMERGENCY BRAKE: Applying {:.2} m/s² braking (brake: {:.1}%), {:?}",
+              acceleration, result.brake * 100.0, self.emergency_phase);
+        Some(result.in_direction(self.drive_direction))
+    }
+
+    /// Move the effective setpoint towards `desired_velocity` by at most `setpoint_ramp_rate * delta_time`,
+    /// starting from the current velocity (returns `desired_velocity` unchanged when disabled)
+    fn ramp_setpoint(&mut self, desired_velocity: f64, current_velocity: f64, delta_time: f64) -> f64 {
+        if self.setpoint_ramp_rate <= 0.0 {
+            return desired_velocity;
+        }
+        let previous = self.ramped_setpoint.unwrap_or(current_velocity);
+        let max_step = self.setpoint_ramp_rate * delta_time.max(0.0);
+        let ramped = desired_velocity.clamp(previous - max_step, previous + max_step);
+        if ramped != desired_velocity {
+            debug!(target: logging::TARGET, "SETPOINT RAMP: Target {} on the way to {}", logging::speed(ramped), logging::speed(desired_velocity));
+        }
+        self.ramped_setpoint = Some(ramped);
+        ramped
+    }
+
+    /// Stop hold brake while `hold_requested` and the vehicle is stopped, None when disabled or released
+    fn stop_hold_step(&mut self, travel_speed: f64, hold_requested: bool) -> Option<PIDResult> {
+        let hold = self.stop_hold_brake > 0.0 && hold_requested && travel_speed.abs() < self.stop_hold_speed;
+        if hold != self.stop_held {
+            if hold {
+                info!(target: logging::CONTROL, "STOP HOLD: Vehicle stopped at {}, holding brake at {:.1}%", logging::speed(travel_speed), self.stop_hold_brake * 100.0);
+            } else {
+                info!(target: logging::CONTROL, "STOP HOLD: Released at {}", logging::speed(travel_speed));
+            }
+            self.stop_held = hold;
+        }
+        if !hold {
+            return None;
+        }
+        let acceleration = PIDResult::throttle_brake_to_acceleration(0.0, self.stop_hold_brake);
+        Some(PIDResult::new(acceleration).with_regime(ControlRegime::StandstillHold).in_direction(self.drive_direction))
+    }
+
+    /// Track continuous emergency braking and trigger the cutout once it lasted `max_braking_time`
+    /// with the vehicle stopped; the hold takes over from the next tick
+    fn record_emergency_braking(&mut self, travel_speed: f64, current_time: f64) {
+        self.emergency_braking_tick = true;
+        let onset = *self.braking_onset.get_or_insert(current_time);
+
+        let braking_time = current_time - onset;
+        if self.max_braking_time > 0.0 && braking_time >= self.max_braking_time && travel_speed <= STOPPED_SPEED {
+            warn!(target: logging::EMERGENCY, "BRAKE CUTOUT: Emergency braking for {:.1}s at standstill, holding the vehicle instead", braking_time);
+            self.standstill_hold = true;
+            self.emergency_phase = EmergencyBrakePhase::Inactive;
+            self.braking_onset = None;
+        }
+    }
+
+    /// Limit acceleration while the soft start ramp or the post-emergency cooldown is running
+    fn apply_acceleration_caps(&mut self, result: PIDResult, current_time: f64) -> PIDResult {
+        let caps = [
+            ("SOFT START", self.soft_start_cap(current_time)),
+            ("COOLDOWN", self.cooldown_cap(current_time)),
+        ];
+        let Some((label, cap)) = caps.into_iter()
+            .filter_map(|(label, cap)| cap.map(|cap| (label, cap)))
+            .min_by(|a, b| a.1.total_cmp(&b.1)) else {
+            return result;
+        };
+
+        let direction = self.drive_direction;
+        if result.acceleration * direction.sign() <= cap {
+            return result;
+        }
+
+        debug!(target: logging::CONTROL, "{}: Limiting acceleration to {:.2} m/s²", label, cap);
+        let capped = PIDResult::new(cap)
+            .with_at_target(result.at_target)
+            .with_saturation(result.requested_acceleration * direction.sign())
+            .in_direction(direction);
+        self.last_pid_result = Some(capped.clone());
+        capped
+    }
+
+    /// Acceleration limit of the soft start ramp, None once the ramp is over
+    fn soft_start_cap(&mut self, current_time: f64) -> Option<f64> {
+        let elapsed = current_time - self.activation_time?;
+        if elapsed < 0.0 || elapsed >= self.soft_start_duration {
+            self.activation_time = None;
+            return None;
+        }
+        Some(self.gains.comfort_limit * elapsed / self.soft_start_duration)
+    }
+
+    /// Emergency and slow-down distances (m) at `current_velocity`, `travel_speed` being the same
+    /// speed along the direction of travel
+    fn braking_distances(&self, current_velocity: f64, travel_speed: f64) -> (f64, f64) {
+        let velocity_factor = (current_velocity / 10.0).max(1.0); // Scale with velocity, min factor of 1
+        // Distance covered before a brake command takes effect
+        let delay_distance = travel_speed.max(0.0) * self.actuator_delay;
+        // Braking distance grows with 1/friction at the lower achievable deceleration
+        let friction_factor = 1.0 / self.friction_coefficient;
+        (
+            self.emergency_stop_distance * velocity_factor * friction_factor + delay_distance,
+            self.slow_down_distance * velocity_factor * friction_factor + delay_distance,
+        )
+    }
+
+    /// Acceleration limit of the post-emergency cooldown, None once the cooldown is over
+    fn cooldown_cap(&mut self, current_time: f64) -> Option<f64> {
+        let elapsed = current_time - self.last_emergency_time?;
+        if elapsed < 0.0 || elapsed >= self.post_emergency_cooldown {
+            // Cooldown over (or clock restarted)
+            self.last_emergency_time = None;
+            return None;
+        }
+        let progress = elapsed / self.post_emergency_cooldown;
+        let limit = self.gains.comfort_limit;
+        let initial = self.cooldown_acceleration.min(limit);
+        Some(initial + (limit - initial) * progress)
+    }
+
+    /// Time in seconds to cover `distance` at `velocity`, None when (nearly) stopped
+    pub fn headway_time(distance: f64, velocity: f64) -> Option<f64> {
+        if velocity.abs() < MIN_HEADWAY_SPEED {
+            None
+        } else {
+            Some(distance / velocity.abs())
+        }
+    }
+
+    /// Following distance for the two second rule at `velocity`, never shorter than `min_distance`
+    pub fn safe_following_distance(velocity: f64, min_distance: f64) -> f64 {
+        (velocity.abs() * SAFE_HEADWAY_TIME).max(min_distance)
+    }
+
+    /// Add the closest in-path distance of a lidar observation to the window and return the window median
+    fn record_obstacle_distance(&mut self, distance: f64) -> f64 {
+        self.obstacle_window.push_back(distance);
+        while self.obstacle_window.len() > self.obstacle_window_size {
+            self.obstacle_window.pop_front();
+        }
+
+        let mut sorted: Vec<f64> = self.obstacle_window.iter().copied().collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        sorted[sorted.len() / 2]
+    }
+
+    /// Apply the first-order low-pass filter to a raw velocity sample
+    fn filter_velocity(&mut self, raw_velocity: f64) -> f64 {
+        let filtered = match self.filtered_velocity {
+            Some(previous) => self.velocity_filter_alpha * raw_velocity + (1.0 - self.velocity_filter_alpha) * previous,
+            None => raw_velocity,
+        };
+        self.filtered_velocity = Some(filtered);
+        filtered
+    }
+
+    /// Forward distance along the predicted path and lateral offset from its centerline for a lidar point.
+    /// A straight path uses the vehicle axes directly; a curved one follows the arc of `predicted_path`.
+    pub fn path_coordinates(&self, point: &PointCoords, steer_input: f64) -> (f64, f64) {
+        match self.predicted_path(steer_input).turn_radius {
+            Some(turn_radius) => {
+                let (to_point_x, to_point_y) = (point.x, point.y - turn_radius);
+                let (to_vehicle_x, to_vehicle_y) = (0.0, -turn_radius);
+
+                // Angle travelled around the turn center until abeam of the point
+                let cross = to_vehicle_x * to_point_y - to_vehicle_y * to_point_x;
+                let dot = to_vehicle_x * to_point_x + to_vehicle_y * to_point_y;
+                let swept_angle = cross.abs().atan2(dot);
+
+                let forward_distance = turn_radius.abs() * swept_angle;
+                let lateral_offset = to_point_x.hypot(to_point_y) - turn_radius.abs();
+                (forward_distance, lateral_offset)
+            }
+            None => (point.x, point.y),
+        }
+    }
+
+    /// Forward distance to a lidar point if it lies inside the vehicle path corridor, None otherwise
+    pub fn path_forward_distance(&self, point: &PointCoords, steer_input: f64) -> Option<f64> {
+        self.path_position(point, steer_input).map(|(forward_distance, _)| forward_distance)
+    }
+
+    /// (forward distance, lateral offset) of a lidar point inside the vehicle path corridor, None otherwise
+    fn path_position(&self, point: &PointCoords, steer_input: f64) -> Option<(f64, f64)> {
+        if !point.is_finite() {
+            return None;
+        }
+        let (forward_distance, lateral_offset) = self.path_coordinates(point, steer_input);
+        let (path_width, max_range) = self.corridor.at_speed(self.corridor_speed);
+
+        if point.x > 1.0 && forward_distance < max_range &&     // In front, with 1m minimum
+           lateral_offset.abs() < path_width / 2.0 &&            // Within lane width
+           point.z > MIN_HEIGHT && point.z < MAX_HEIGHT {        // At vehicle height
+            Some((forward_distance, lateral_offset))
+        } else {
+            None
+        }
+    }
+
+    /// Whether no in-path obstacle of the latest lidar frame is within the re-engagement clearance
+    fn path_clear_for_reengage(&self, lidar_data: Option<&LidarMeasurement>, steer_input: f64) -> bool {
+        let Some(lidar) = lidar_data else {
+            return true;
+        };
+        let clearance = self.get_reengage_clearance();
+        let closest_distance = self.path_obstacles(lidar, steer_input).iter()
+            .map(|obstacle| obstacle.forward_distance)
+            .fold(f64::MAX, f64::min);
+
+        if closest_distance <= clearance {
+            debug!(target: logging::ENGAGE, "CRUISE CONTROL RE-ENGAGEMENT BLOCKED: Obstacle at {:.2}m within clearance {:.1}m",
+                   closest_distance, clearance);
+            return false;
+        }
+        true
+    }
+
+    /// Detections that `compute` considers as in-path obstacles for the given steering input.
+    /// Empty when reversing, as the lidar path analysis only covers the area in front of the vehicle.
+    pub fn path_obstacles(&self, lidar: &LidarMeasurement, steer_input: f64) -> Vec<PathObstacle> {
+        if self.drive_direction != DriveDirection::Forward || lidar.is_empty {
+            return Vec::new();
+        }
+
+        lidar.detections.iter()
+            .filter_map(|detection| {
+                let point = &detection.point;
+                self.path_position(point, steer_input).map(|(forward_distance, lateral_offset)| PathObstacle {
+                    x: point.x,
+                    y: point.y,
+                    z: point.z,
+                    intensity: detection.intensity,
+                    forward_distance,
+                    lateral_offset,
+                })
+            })
+            .collect()
+    }
+
+    fn compute_pid(&mut self, desired_velocity: f64, current_velocity: f64, delta_time: f64) -> Result<PIDResult, PidError> {
+        let at_target = within_speed_band(desired_velocity, current_velocity, self.target_speed_tolerance);
+
+        // Work on speeds along the direction of travel so the same logic serves forward and reverse
+        let direction = self.drive_direction;
+        let desired_velocity = desired_velocity * direction.sign();
+        let current_velocity = current_velocity * direction.sign();
+
+        if let Some(acceleration) = self.bumpless_acceleration.take() {
+            self.initialize_bumpless(acceleration, desired_velocity - current_velocity, delta_time);
+        }
+
+        let predicted_velocity = self.deadtime_compensated(current_velocity, delta_time);
+
+        let hold = if self.hold_feedforward && at_target { self.hold_acceleration } else { None };
+        let step = pid_core::pid_step(&self.gains, &mut self.pid_state, desired_velocity, predicted_velocity, delta_time, hold);
+        self.last_command = Some(step.acceleration);
+
+        // Learn the steady-state acceleration while holding the target speed
+        if self.hold_feedforward && at_target && !matches!(step.mode, StepMode::OverspeedBraking { .. }) {
+            self.hold_acceleration = Some(match self.hold_acceleration {
+                Some(previous) => previous + HOLD_LEARNING_RATE * (step.acceleration - previous),
+                None => step.acceleration,
+            });
+        }
+
+        let mut result = PIDResult::new(step.acceleration)
+            .with_coast_band(self.coast_band)
+            .with_at_target(at_target)
+            .with_saturation(step.requested_acceleration);
+
+        // Log when the limits start and stop binding, not on every saturated tick
+        if step.saturated() != self.saturated {
+            self.saturated = step.saturated();
+            if self.saturated {
+                info!(target: logging::CONTROL, "SATURATION: PID requests {:.2} m/s² (integral {:.2}), limited to {:.2} m/s²",
+                      step.requested_acceleration, self.pid_state.integral_term(&self.gains), step.acceleration);
+            } else {
+                info!(target: logging::CONTROL, "SATURATION: PID output back within limits ({:.2} m/s²)", step.acceleration);
+            }
+        }
+
+        // Creep: make sure the vehicle gets moving from (near) standstill
+        let creeping = self.creep_min_throttle > 0.0
+            && desired_velocity > 0.0
+            && current_velocity < self.creep_speed_threshold
+            && result.throttle < self.creep_min_throttle
+            && !matches!(step.mode, StepMode::OverspeedBraking { .. });
+        if creeping {
+            result = result.with_throttle_floor(self.creep_min_throttle);
+            debug!(target: logging::CONTROL, "CREEP: Throttle floor {:.1}% at {}", result.throttle * 100.0, logging::speed(current_velocity));
+        }
+
+        let result = result.in_direction(direction);
+        self.last_pid_result = Some(result.clone());
+
+        match step.mode {
+            StepMode::OverspeedBraking { speed_excess } => {
+                info!(target: logging::CONTROL, "SPEED CONTROL: Applying gentle braking {:.2} m/s² (brake: {:.1}%) for speed excess {}", 
+                      step.acceleration, result.brake * 100.0, logging::speed(speed_excess));
+            }
+            StepMode::Pid if step.acceleration > 0.0 => {
+                debug!(target: logging::CONTROL, "PID CONTROL: Throttle {:.1}% ({:.2} m/s²)", result.throttle * 100.0, step.acceleration);
+            }
+            StepMode::Pid if step.acceleration < 0.0 => {
+                debug!(target: logging::CONTROL, "PID CONTROL: Brake {:.1}% ({:.2} m/s²)", result.brake * 100.0, step.acceleration);
+            }
+            StepMode::Hold { hold_acceleration } => {
+                debug!(target: logging::CONTROL, "PID HOLD: {:.2} m/s² (hold base {:.2} m/s²)", step.acceleration, hold_acceleration);
+            }
+            StepMode::Pid => {}
+        }
+        
+        Ok(result)
+    }
+
+    /// Smith predictor: `current_velocity` plus the velocity change the PID commands of the last
+    /// `deadtime` seconds will still cause. The previous command is entered with the `delta_time`
+    /// it was applied for; the oldest one only counts with the part still within the deadtime.
+    fn deadtime_compensated(&mut self, current_velocity: f64, delta_time: f64) -> f64 {
+        if self.deadtime <= 0.0 {
+            return current_velocity;
+        }
+        if let Some(acceleration) = self.last_command {
+            self.commands_in_flight.push_back((delta_time, acceleration));
+        }
+
+        let mut in_flight_time: f64 = self.commands_in_flight.iter().map(|&(duration, _)| duration).sum();
+        while let Some(&(duration, _)) = self.commands_in_flight.front() {
+            if in_flight_time - duration < self.deadtime {
+                break;
+            }
+            in_flight_time -= duration;
+            self.commands_in_flight.pop_front();
+        }
+
+        // Drag, rolling resistance and grade: the part of the command now taking effect that does not show
+        // up as acceleration, averaged slowly so the actuator lag of a transient is not mistaken for it.
+        // Without it a constant holding command would predict a speed change and leave a speed offset.
+        if let Some(&(_, acceleration)) = self.commands_in_flight.front() {
+            let observed = acceleration - self.estimated_acceleration();
+            let smoothing = delta_time / (RESISTANCE_TIME_CONSTANT + delta_time);
+            self.resistance += (observed - self.resistance) * smoothing;
+        }
+        let resistance = self.resistance;
+
+        let overlap = (in_flight_time - self.deadtime).max(0.0);
+        let expected_change: f64 = self.commands_in_flight.iter().enumerate()
+            .map(|(index, &(duration, acceleration))| {
+                let effective = if index == 0 { duration - overlap } else { duration };
+                (acceleration - resistance) * effective
+            })
+            .sum();
+
+        debug!(target: logging::CONTROL, "DEADTIME: {} measured, {} predicted ({} commands in flight)",
+               logging::speed(current_velocity), logging::speed(current_velocity + expected_change), self.commands_in_flight.len());
+        current_velocity + expected_change
+    }
+
+    /// Acceleration along the direction of travel over the recent velocity samples (m/s²)
+    fn estimated_acceleration(&self) -> f64 {
+        match (self.velocity_history.front(), self.velocity_history.back()) {
+            (Some(&(first_time, first_speed)), Some(&(last_time, last_speed))) if last_time > first_time => {
+                (last_speed - first_speed) / (last_time - first_time)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Set the PID state so the next step with `velocity_error` and `delta_time` outputs
+    /// `acceleration`: no derivative kick, the integral supplies what the P term does not
+    fn initialize_bumpless(&mut self, acceleration: f64, velocity_error: f64, delta_time: f64) {
+        self.pid_state.velocity_error = velocity_error;
+        self.pid_state.filtered_derivative = 0.0;
+
+        // pid_step decays the integral by the leak, then adds velocity_error * delta_time
+        let decay = 1.0 - self.gains.integral_leak * delta_time;
+        if self.gains.ki > 0.0 && decay > 0.0 {
+            let integral = (acceleration - self.gains.kp * velocity_error) / self.gains.ki;
+            self.pid_state.accumulated_error = (integral - velocity_error * delta_time) / decay;
+        }
+        info!(target: logging::CONTROL, "BUMPLESS TRANSFER: Continuing from {:.2} m/s² (speed error {})", acceleration, logging::speed(velocity_error));
+    }
+
+    pub fn reset(&mut self) {
+        self.pid_state = PidState::default();
+        self.previous_time = 0.0;
+        self.previous_velocity = 0.0;
+        self.cruise_suspended = false;
+        self.last_pid_result = None;
+        self.accumulated_velocity = (0.0, 0);
+        self.filtered_velocity = None;
+        self.obstacle_window.clear();
+        self.hold_acceleration = None;
+        self.emergency_phase = EmergencyBrakePhase::Inactive;
+        self.braking_onset = None;
+        self.emergency_braking_tick = false;
+        self.standstill_hold = false;
+        self.stop_held = false;
+        self.saturated = false;
+        self.in_slow_down = false;
+        self.previous_steer = None;
+        self.corridor_speed = 0.0;
+        self.emergency_frames = 0;
+        self.bumpless_acceleration = None;
+        self.velocity_history.clear();
+        self.ramped_setpoint = None;
+        self.commands_in_flight.clear();
+        self.resistance = 0.0;
+        self.last_command = None;
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::uprotocol_handler::LidarDetection;
+    use proptest::prelude::*;
+
+    /// Full pedal range of the maps: maximum deceleration to maximum acceleration (m/s²)
+    const PEDAL_RANGE: std::ops::RangeInclusive<f64> = -8.0..=3.0;
+
+    #[test]
+    fn pedal_map_is_valid() {
+        assert_eq!(PIDResult::check_pedal_map(), Ok(()));
+    }
+
+    proptest! {
+        #[test]
+        fn pedal_map_is_monotonic(a in PEDAL_RANGE, b in PEDAL_RANGE) {
+            let (low, high) = if a <= b { (a, b) } else { (b, a) };
+            let (low_throttle, low_brake) = PIDResult::acceleration_to_throttle_brake(low);
+            let (high_throttle, high_brake) = PIDResult::acceleration_to_throttle_brake(high);
+            prop_assert!(low_throttle <= high_throttle);
+            prop_assert!(low_brake >= high_brake);
+        }
+
+        #[test]
+        fn pedal_map_round_trips(acceleration in PEDAL_RANGE) {
+            prop_assert!(PIDResult::round_trip_error(acceleration) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn non_finite_input_leaves_state_untouched() {
+        let mut pid = PIDController::new(0.5, 0.1, 0.0);
+        pid.compute(20.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        pid.compute(20.0, 10.0, 1.1, None, 0.0, 0.0, 0.0).unwrap();
+        let integral = pid.integral_term();
+
+        for (input, args) in [
+            ("desired_velocity", [f64::NAN, 10.0, 1.2, 0.0, 0.0, 0.0]),
+            ("current_velocity", [20.0, f64::INFINITY, 1.2, 0.0, 0.0, 0.0]),
+            ("current_time", [20.0, 10.0, f64::NAN, 0.0, 0.0, 0.0]),
+            ("brake_input", [20.0, 10.0, 1.2, 0.0, 0.0, f64::NEG_INFINITY]),
+        ] {
+            let result = pid.compute(args[0], args[1], args[2], None, args[3], args[4], args[5]);
+            assert!(matches!(result, Err(PidError::NonFiniteInput { input: rejected, .. }) if rejected == input));
+        }
+        assert_eq!(pid.non_finite_input_count(), 4);
+        assert_eq!(pid.integral_term(), integral);
+
+        let result = pid.compute(20.0, 10.0, 1.2, None, 0.0, 0.0, 0.0).unwrap();
+        assert!(result.acceleration.is_finite() && result.acceleration > 0.0);
+    }
+
+    #[test]
+    fn cooldown_expires_at_configured_duration() {
+        // Ticks are timed by the wall clock as with `ClockSource::WallClock`
+        let clock = Arc::new(MockClock::new(Duration::from_secs(100)));
+        let mut pid = PIDController::new(2.0, 0.0, 0.0);
+        pid.set_time_source(clock.clone());
+        pid.set_post_emergency_cooldown(5.0, 0.5);
+        let tick = |pid: &mut PIDController, step: Duration, lidar: Option<&LidarMeasurement>| {
+            clock.advance(step);
+            let now = pid.wall_time().as_secs_f64();
+            pid.compute(30.0, 10.0, now, lidar, 0.0, 0.0, 0.0).unwrap()
+        };
+        let clear = LidarMeasurement { detections: Vec::new(), is_empty: true, len: 0, ..obstacle_at(0.0) };
+
+        tick(&mut pid, Duration::ZERO, None);
+        assert_eq!(tick(&mut pid, Duration::from_millis(500), Some(&clear)).acceleration, MAX_PID_ACCELERATION);
+
+        // An obstacle 2 m ahead brakes for one tick, the next frame is clear again
+        assert!(tick(&mut pid, Duration::from_millis(500), Some(&obstacle_at(2.0))).emergency_brake_engaged);
+        let first_clear = tick(&mut pid, Duration::from_millis(500), Some(&clear)).acceleration;
+        assert!((first_clear - 0.6).abs() < 1e-9, "cap rises from 0.5 to 1.5 m/s² over 5 s, got {}", first_clear);
+        let capped = tick(&mut pid, Duration::from_millis(4499), Some(&clear)).acceleration;
+        assert!(capped < MAX_PID_ACCELERATION && capped > 1.49, "still capped 1 ms before the end, got {}", capped);
+        assert_eq!(tick(&mut pid, Duration::from_millis(1), Some(&clear)).acceleration, MAX_PID_ACCELERATION,
+                   "cooldown is over exactly 5 s after the emergency");
+    }
+
+    /// Integral-only controller whose integral term reveals the time step of the second tick
+    fn integral_probe() -> PIDController {
+        let mut pid = PIDController::new(0.0, 1.0, 0.0);
+        pid.set_min_effective_dt(0.05);
+        pid.compute(20.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        pid
+    }
+
+    #[test]
+    fn duplicate_timestamp_uses_min_effective_dt() {
+        let mut pid = integral_probe();
+        pid.compute(20.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        assert!((pid.integral_term() - 10.0 * 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn small_negative_dt_uses_min_effective_dt() {
+        let mut pid = integral_probe();
+        pid.compute(20.0, 10.0, 0.9995, None, 0.0, 0.0, 0.0).unwrap();
+        assert!((pid.integral_term() - 10.0 * 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn large_negative_dt_is_an_error() {
+        let mut pid = integral_probe();
+        let error = pid.compute(20.0, 10.0, 0.9, None, 0.0, 0.0, 0.0).unwrap_err();
+        assert!(matches!(error, PidError::NegativeDeltaTime { current, previous, .. } if current == 0.9 && previous == 1.0));
+        assert_eq!(error.to_string(), "Significant negative delta_time: -0.100000 seconds. current_time=0.900000, previous_time=1.000000");
+        assert_eq!(pid.integral_term(), 0.0);
+    }
+
+    /// Lidar frame with a single detection straight ahead at `x` meters
+    pub(crate) fn obstacle_at(x: f64) -> LidarMeasurement {
+        LidarMeasurement {
+            channel_count: 1,
+            detections: vec![LidarDetection { intensity: 1.0, point: PointCoords { x, y: 0.0, z: 1.0 } }],
+            horizontal_angle: 0.0,
+            is_empty: false,
+            len: 1,
+        }
+    }
+
+    /// Emergency acceleration commanded for an obstacle at `x` meters while driving 10 m/s
+    fn emergency_acceleration(friction: f64, x: f64) -> f64 {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_friction_coefficient(friction);
+        pid.compute(10.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        let result = pid.compute(10.0, 10.0, 1.1, Some(&obstacle_at(x)), 0.0, 0.0, 0.0).unwrap();
+        assert!(result.emergency_brake_engaged);
+        result.acceleration
+    }
+
+    #[test]
+    fn low_friction_lengthens_braking_distances() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        let (dry_emergency, dry_slow_down) = pid.braking_distances(10.0, 10.0);
+        pid.set_friction_coefficient(0.5);
+        assert_eq!(pid.get_friction_coefficient(), 0.5);
+        let (wet_emergency, wet_slow_down) = pid.braking_distances(10.0, 10.0);
+        assert_eq!(wet_emergency, 2.0 * dry_emergency);
+        assert_eq!(wet_slow_down, 2.0 * dry_slow_down);
+    }
+
+    #[test]
+    fn low_friction_reduces_commanded_deceleration() {
+        let dry = emergency_acceleration(1.0, 2.0);
+        let wet = emergency_acceleration(0.5, 2.0);
+        assert!(dry < wet && wet < 0.0, "dry {} m/s², wet {} m/s²", dry, wet);
+        assert!(wet >= 0.5 * PIDController::new(0.5, 0.0, 0.0).get_emergency_config().2);
+    }
+
+    #[test]
+    fn emergency_stop_profile_ramps_then_holds_until_standstill() {
+        const DT: f64 = 0.05;
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_emergency_brake_profile(0.5);
+        let max_braking = pid.achievable_braking();
+        let (mut velocity, mut gap, mut time) = (12.0, 20.0, 1.0);
+        pid.compute(12.0, velocity, time, None, 0.0, 0.0, 0.0).unwrap();
+
+        // Approach a stopped obstacle until the profile releases the brake
+        let mut profile = Vec::new();
+        let release = loop {
+            time += DT;
+            assert!(time < 30.0, "the vehicle never stopped");
+            let result = pid.compute(12.0, velocity, time, Some(&obstacle_at(gap)), 0.0, 0.0, 0.0).unwrap();
+            if result.cruise_should_disengage {
+                break result;
+            }
+            if result.emergency_brake_engaged {
+                profile.push((pid.emergency_brake_phase(), result.acceleration));
+            }
+            velocity = (velocity + result.acceleration * DT).max(0.0);
+            gap -= velocity * DT;
+        };
+
+        assert!(matches!(profile[0].0, EmergencyBrakePhase::Ramp { .. }));
+        assert!(profile[0].1 > max_braking, "the ramp starts below max braking");
+        assert!(profile.windows(2).all(|pair| pair[1].1 <= pair[0].1 + 1e-9), "braking never eases off: {:?}", profile);
+        let holding: Vec<_> = profile.iter().filter(|(phase, _)| *phase == EmergencyBrakePhase::Hold).collect();
+        assert!(holding.len() > 1);
+        assert!(holding.iter().all(|(_, acceleration)| *acceleration == max_braking));
+        assert_eq!(profile.last().unwrap().0, EmergencyBrakePhase::Hold, "held until standstill");
+
+        assert!(velocity <= STOPPED_SPEED);
+        assert!(release.emergency_brake_engaged);
+        assert_eq!((release.acceleration, release.brake), (0.0, 0.0));
+        assert_eq!(pid.emergency_brake_phase(), EmergencyBrakePhase::Inactive);
+    }
+
+    /// Closed loop with the default tuning on the simulator's vehicle model, the regression
+    /// baseline for tuning changes
+    #[test]
+    fn closed_loop_converges_within_the_comfort_limit() {
+        const DT: f64 = 0.1;
+        const TARGET: f64 = 20.0;
+        let mut pid = crate::pid_config::PidConfig::default().build_controller();
+        let mut vehicle = crate::vehicle_model::VehicleModel::default();
+        let mut velocities = Vec::new();
+        for tick in 1..=3000 {
+            let result = pid.compute(TARGET, vehicle.velocity(), tick as f64 * DT, None, 0.0, 0.0, 0.0).unwrap();
+            assert!(result.acceleration.abs() <= MAX_PID_ACCELERATION, "{} m/s² at tick {}", result.acceleration, tick);
+            velocities.push(vehicle.step(result.acceleration, DT));
+        }
+        let settled = &velocities[velocities.len() - 100..];
+        assert!(settled.iter().all(|velocity| (velocity - TARGET).abs() < 0.5), "last 10 s: {:?}", settled);
+    }
+
+    #[test]
+    fn reverse_setpoint_reaches_steady_state() {
+        const DT: f64 = 0.1;
+        let mut pid = PIDController::new(0.5, 0.1, 0.0);
+        pid.set_drive_direction(DriveDirection::Reverse);
+        let mut velocity = 0.0;
+        let mut results = Vec::new();
+        for tick in 1..=600 {
+            let result = pid.compute(-2.0, velocity, tick as f64 * DT, None, 0.0, 0.0, 0.0).unwrap();
+            velocity += result.acceleration * DT;
+            results.push(result);
+        }
+        assert!((velocity + 2.0).abs() < 0.01, "settled at {} m/s", velocity);
+
+        // Speeding up backwards is throttle (in reverse), not brake
+        let pulling_away = &results[1];
+        assert!(pulling_away.acceleration < 0.0);
+        assert!(pulling_away.throttle > 0.0 && pulling_away.brake == 0.0);
+        assert!(results.iter().all(|result| result.acceleration.abs() <= MAX_PID_ACCELERATION));
+    }
+
+    #[test]
+    fn forward_is_the_default_direction() {
+        let mut default = PIDController::new(0.5, 0.1, 0.0);
+        let mut forward = PIDController::new(0.5, 0.1, 0.0);
+        forward.set_drive_direction(DriveDirection::Forward);
+        for (time, velocity) in [(1.0, 10.0), (1.1, 10.5), (1.2, 11.0)] {
+            assert_eq!(
+                default.compute(20.0, velocity, time, None, 0.0, 0.0, 0.0).unwrap().acceleration,
+                forward.compute(20.0, velocity, time, None, 0.0, 0.0, 0.0).unwrap().acceleration,
+            );
+        }
+    }
+
+    /// Largest PID request (before the limits) for a noisy velocity arriving every 0.5 ms
+    fn largest_request_for_bursty_ticks(min_sample_time: f64, mode: SampleTimeMode) -> f64 {
+        let mut pid = PIDController::new(0.0, 0.0, 1.0);
+        pid.set_min_sample_time(min_sample_time, mode);
+        (0..400)
+            .map(|tick| {
+                let noise = if tick % 2 == 0 { 0.01 } else { -0.01 };
+                pid.compute(10.0, 10.0 + noise, 1.0 + tick as f64 * 0.0005, None, 0.0, 0.0, 0.0).unwrap()
+            })
+            .map(|result| result.requested_acceleration.abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn min_sample_time_bounds_the_derivative_term() {
+        assert!(largest_request_for_bursty_ticks(0.0, SampleTimeMode::Skip) > 30.0, "unbounded without a minimum");
+        for mode in [SampleTimeMode::Skip, SampleTimeMode::Accumulate] {
+            let largest = largest_request_for_bursty_ticks(0.05, mode);
+            assert!(largest <= 0.02 / 0.05 + 1e-9, "{:?}: {} m/s²", mode, largest);
+
+            // The PID still runs once per min_sample_time: three updates of 0.05 s with a 10 m/s error
+            let mut pid = PIDController::new(0.0, 1.0, 0.0);
+            pid.set_min_sample_time(0.05, mode);
+            let requests: Vec<f64> = (0..400)
+                .map(|tick| pid.compute(20.0, 10.0, 1.0 + tick as f64 * 0.0005, None, 0.0, 0.0, 0.0).unwrap().requested_acceleration)
+                .collect();
+            let updates = requests.windows(2).filter(|pair| pair[1] != pair[0]).count();
+            assert_eq!(updates, 3, "{:?}: {:?}", mode, requests);
+            assert!((pid.integral_term() - 1.5).abs() < 0.01, "{:?}: {}", mode, pid.integral_term());
+        }
+    }
+
+    #[test]
+    fn skipped_ticks_return_the_previous_result() {
+        let mut pid = PIDController::new(0.1, 0.0, 0.0);
+        pid.set_min_sample_time(0.05, SampleTimeMode::Skip);
+        pid.compute(20.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        let computed = pid.compute(20.0, 10.0, 1.1, None, 0.0, 0.0, 0.0).unwrap();
+        let skipped = pid.compute(20.0, 15.0, 1.101, None, 0.0, 0.0, 0.0).unwrap();
+        assert_eq!(skipped.acceleration, computed.acceleration);
+        let next = pid.compute(20.0, 15.0, 1.2, None, 0.0, 0.0, 0.0).unwrap();
+        assert!(next.acceleration < computed.acceleration);
+    }
+
+    fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        (mean, variance)
+    }
+
+    #[test]
+    fn velocity_filter_smooths_a_noisy_step() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_velocity_filter(0.2).unwrap();
+        let noise = [0.4, -0.3, 0.1, -0.5, 0.3, 0.0, -0.2, 0.5, -0.4, 0.1];
+        let mut raw = Vec::new();
+        let mut filtered = Vec::new();
+        for tick in 0..200 {
+            let level = if tick < 20 { 5.0 } else { 10.0 };
+            let velocity = level + noise[tick % noise.len()];
+            pid.compute(12.0, velocity, 1.0 + tick as f64 * 0.1, None, 0.0, 0.0, 0.0).unwrap();
+            if tick >= 60 {
+                raw.push(velocity);
+                filtered.push(pid.filtered_velocity().unwrap());
+            }
+        }
+        let (raw_mean, raw_variance) = mean_and_variance(&raw);
+        let (filtered_mean, filtered_variance) = mean_and_variance(&filtered);
+        assert!(filtered_variance < raw_variance / 4.0, "variance {} vs raw {}", filtered_variance, raw_variance);
+        assert!((filtered_mean - raw_mean).abs() < 0.05, "mean {} vs raw {}", filtered_mean, raw_mean);
+    }
+
+    #[test]
+    fn velocity_filter_defaults_to_pass_through() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        for (time, velocity) in [(1.0, 10.0), (1.1, 13.0), (1.2, 9.5)] {
+            pid.compute(12.0, velocity, time, None, 0.0, 0.0, 0.0).unwrap();
+            assert_eq!(pid.filtered_velocity(), Some(velocity));
+        }
+        assert!(pid.set_velocity_filter(0.0).is_err());
+        assert!(pid.set_velocity_filter(1.5).is_err());
+    }
+
+    /// Lidar frame with one detection per (x, y) point at vehicle height
+    fn obstacles_at(points: &[(f64, f64)]) -> LidarMeasurement {
+        LidarMeasurement {
+            channel_count: 1,
+            detections: points.iter()
+                .map(|&(x, y)| LidarDetection { intensity: 1.0, point: PointCoords { x, y, z: 1.0 } })
+                .collect(),
+            horizontal_angle: 0.0,
+            is_empty: false,
+            len: points.len() as u32,
+        }
+    }
+
+    #[test]
+    fn curved_path_includes_obstacles_on_the_arc() {
+        let steer = 0.2;
+        let PathModel::Curved { wheelbase, max_steer_angle } = PathModel::curved() else { unreachable!() };
+        let radius = wheelbase / (steer * max_steer_angle).tan();
+        let swept = std::f64::consts::FRAC_PI_4;
+        let on_arc = (radius * swept.sin(), radius * (1.0 - swept.cos()));
+        let straight_ahead = (on_arc.0, 0.0);
+        let lidar = obstacles_at(&[on_arc, straight_ahead]);
+
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        let straight: Vec<_> = pid.path_obstacles(&lidar, steer).iter().map(|o| (o.x, o.y)).collect();
+        assert_eq!(straight, vec![straight_ahead]);
+
+        pid.set_path_model(PathModel::curved());
+        let curved = pid.path_obstacles(&lidar, steer);
+        assert_eq!(curved.len(), 1);
+        assert_eq!((curved[0].x, curved[0].y), on_arc);
+        assert!((curved[0].forward_distance - radius * swept).abs() < 1e-9);
+        assert!(curved[0].lateral_offset.abs() < 1e-9);
+    }
+
+    #[test]
+    fn curved_path_turning_left_excludes_obstacles_on_the_right() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_path_model(PathModel::curved());
+        let lidar = obstacles_at(&[(8.0, 3.0), (8.0, -3.0)]);
+        let left: Vec<_> = pid.path_obstacles(&lidar, -0.2).iter().map(|o| o.y).collect();
+        let right: Vec<_> = pid.path_obstacles(&lidar, 0.2).iter().map(|o| o.y).collect();
+        assert_eq!(left, vec![-3.0]);
+        assert_eq!(right, vec![3.0]);
+    }
+
+    /// Emergency flags of ticks at 10 m/s with the given closest in-path distances (None = clear frame)
+    fn emergency_flags(pid: &mut PIDController, distances: &[Option<f64>]) -> Vec<bool> {
+        pid.compute(10.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        distances.iter().enumerate()
+            .map(|(i, distance)| {
+                // A detection beside the path keeps the frame non-empty without an in-path obstacle
+                let lidar = obstacles_at(&[(distance.unwrap_or(5.0), if distance.is_some() { 0.0 } else { 10.0 })]);
+                let time = 1.1 + i as f64 * 0.1;
+                pid.compute(10.0, 10.0, time, Some(&lidar), 0.0, 0.0, 0.0).unwrap().emergency_brake_engaged
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_spurious_frame_does_not_emergency_brake() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_obstacle_smoothing(3, 1.8);
+        assert_eq!(pid.get_obstacle_smoothing(), (3, 1.8));
+        let flags = emergency_flags(&mut pid, &[None, None, Some(2.0), None, None]);
+        assert!(flags.iter().all(|&engaged| !engaged), "{:?}", flags);
+    }
+
+    #[test]
+    fn persistent_obstacle_emergency_brakes_once_it_dominates_the_window() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_obstacle_smoothing(3, 1.8);
+        let flags = emergency_flags(&mut pid, &[None, None, Some(2.0), Some(2.0)]);
+        assert_eq!(flags, vec![false, false, false, true]);
+    }
+
+    #[test]
+    fn detection_below_the_hard_floor_brakes_immediately() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_obstacle_smoothing(3, 1.8);
+        let flags = emergency_flags(&mut pid, &[None, None, Some(1.5)]);
+        assert_eq!(flags, vec![false, false, true]);
+    }
+
+    #[test]
+    fn window_of_one_reacts_to_every_frame() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        let flags = emergency_flags(&mut pid, &[None, Some(2.0)]);
+        assert_eq!(flags, vec![false, true]);
+    }
+
+    #[test]
+    fn config_snapshot_round_trips_every_tunable() {
+        let mut pid = PIDController::new(0.3, 0.02, 0.01);
+        pid.set_friction_coefficient(0.7);
+        pid.set_deadtime(0.2);
+        pid.set_stop_hold(0.4, 0.3);
+        pid.set_obstacle_smoothing(5, 1.5);
+        pid.set_path_model(PathModel::curved());
+        let snapshot = pid.config_snapshot();
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        let fields = json.as_object().unwrap();
+        assert_eq!(fields.len(), 44, "a tunable is missing from the snapshot: {:?}", fields.keys());
+        for (field, value) in [("kp", 0.3), ("ki", 0.02), ("kd", 0.01), ("friction_coefficient", 0.7),
+                               ("deadtime", 0.2), ("stop_hold_brake", 0.4), ("stop_hold_speed", 0.3),
+                               ("emergency_hard_floor", 1.5)] {
+            assert_eq!(fields[field].as_f64(), Some(value), "{}", field);
+        }
+        assert_eq!(fields["obstacle_window_size"], 5);
+
+        let restored: ControllerSnapshot = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, snapshot);
+    }
+
+    #[test]
+    fn acceleration_ramps_up_after_an_emergency_clears() {
+        let mut pid = PIDController::new(2.0, 0.0, 0.0);
+        pid.set_post_emergency_cooldown(3.0, 0.5);
+        pid.compute(30.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        assert!(pid.compute(30.0, 10.0, 1.1, Some(&obstacle_at(2.0)), 0.0, 0.0, 0.0).unwrap().emergency_brake_engaged);
+
+        let clear = obstacles_at(&[]);
+        let accelerations: Vec<f64> = (1..=8)
+            .map(|i| pid.compute(30.0, 10.0, 1.1 + i as f64 * 0.5, Some(&clear), 0.0, 0.0, 0.0).unwrap().acceleration)
+            .collect();
+        assert!(accelerations[0] < 1.0, "right after the emergency the cap is gentle, got {}", accelerations[0]);
+        assert!(accelerations.windows(2).all(|pair| pair[1] >= pair[0]), "{:?}", accelerations);
+        assert!(accelerations.windows(2).all(|pair| pair[1] - pair[0] < 0.5), "no jump: {:?}", accelerations);
+        assert_eq!(*accelerations.last().unwrap(), MAX_PID_ACCELERATION, "the cap is lifted after the cooldown");
+    }
+
+    #[test]
+    fn headway_time_for_a_known_distance_and_speed() {
+        assert_eq!(PIDController::headway_time(30.0, 15.0), Some(2.0));
+        assert_eq!(PIDController::headway_time(30.0, -15.0), Some(2.0), "reversing uses the speed magnitude");
+        assert_eq!(PIDController::headway_time(30.0, 0.05), None, "undefined when nearly stopped");
+    }
+
+    #[test]
+    fn safe_following_distance_uses_the_two_second_rule() {
+        assert_eq!(PIDController::safe_following_distance(15.0, 5.0), 30.0);
+        assert_eq!(PIDController::safe_following_distance(1.0, 5.0), 5.0, "never shorter than the minimum");
+    }
+
+    #[test]
+    fn soft_start_caps_acceleration_after_engage_and_ramps_it_up() {
+        let mut pid = PIDController::new(2.0, 0.0, 0.0);
+        pid.set_soft_start(2.0);
+        assert_eq!(pid.get_soft_start(), 2.0);
+        let accelerate = |pid: &mut PIDController, time: f64| pid.compute(30.0, 10.0, time, None, 0.0, 0.0, 0.0).unwrap().acceleration;
+
+        assert_eq!(accelerate(&mut pid, 1.0), 0.0, "capped to zero at the moment of engaging");
+        let ramp: Vec<f64> = [1.5, 2.0, 2.5].iter().map(|&time| accelerate(&mut pid, time)).collect();
+        for (acceleration, elapsed) in ramp.iter().zip([0.5, 1.0, 1.5]) {
+            assert!((acceleration - MAX_PID_ACCELERATION * elapsed / 2.0).abs() < 1e-9, "{:?}", ramp);
+        }
+        assert_eq!(accelerate(&mut pid, 3.0), MAX_PID_ACCELERATION, "the full limit applies after the ramp");
+
+        // Engaging again starts a new ramp
+        pid.reset();
+        accelerate(&mut pid, 10.0);
+        assert!(accelerate(&mut pid, 10.5) < MAX_PID_ACCELERATION);
+    }
+
+    #[test]
+    fn soft_start_is_disabled_by_default() {
+        let mut pid = PIDController::new(2.0, 0.0, 0.0);
+        pid.compute(30.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        assert_eq!(pid.compute(30.0, 10.0, 1.1, None, 0.0, 0.0, 0.0).unwrap().acceleration, MAX_PID_ACCELERATION);
+    }
+
+    #[test]
+    fn small_decelerations_coast_within_the_coast_band() {
+        // 0.4 m/s over the target with kp 0.5 requests -0.2 m/s²
+        let decelerate = |coast_band: f64| {
+            let mut pid = PIDController::new(0.5, 0.0, 0.0);
+            pid.set_coast_band(coast_band);
+            pid.compute(20.0, 20.4, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+            pid.compute(20.0, 20.4, 1.1, None, 0.0, 0.0, 0.0).unwrap()
+        };
+
+        let braking = decelerate(0.0);
+        assert!(braking.acceleration < 0.0 && braking.brake > 0.0, "{:?}", braking);
+        let coasting = decelerate(0.3);
+        assert_eq!(coasting.acceleration, braking.acceleration, "coasting only changes the pedals");
+        assert_eq!((coasting.throttle, coasting.brake), (0.0, 0.0));
+    }
+
+    #[test]
+    fn actuator_delay_triggers_emergency_braking_earlier() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        let (emergency_distance, _) = pid.braking_distances(10.0, 10.0);
+        let obstacle = emergency_distance + 2.0;
+        assert_eq!(emergency_flags(&mut pid, &[Some(obstacle)]), vec![false]);
+
+        // 0.5 s at 10 m/s adds 5 m before the brakes act
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_actuator_delay(0.5);
+        assert_eq!(pid.get_actuator_delay(), 0.5);
+        assert_eq!(pid.braking_distances(10.0, 10.0).0, emergency_distance + 5.0);
+        assert_eq!(emergency_flags(&mut pid, &[Some(obstacle)]), vec![true]);
+    }
+
+    /// Largest deviation from 20 m/s over the last 30 s of a flat drive against drag
+    fn flat_profile_ripple(hold_feedforward: bool) -> f64 {
+        const DT: f64 = 0.1;
+        const TARGET: f64 = 20.0;
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_hold_feedforward(hold_feedforward);
+        let mut vehicle = crate::vehicle_model::VehicleModel::default();
+        let mut deviations = Vec::new();
+        for tick in 1..=1500 {
+            let result = pid.compute(TARGET, vehicle.velocity(), tick as f64 * DT, None, 0.0, 0.0, 0.0).unwrap();
+            deviations.push((vehicle.step(result.acceleration, DT) - TARGET).abs());
+        }
+        deviations[deviations.len() - 300..].iter().copied().fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn hold_feedforward_reduces_speed_ripple_on_a_flat_profile() {
+        let without = flat_profile_ripple(false);
+        let with = flat_profile_ripple(true);
+        assert!(with < without / 2.0, "ripple {:.3} m/s with hold feedforward, {:.3} m/s without", with, without);
+    }
+
+    /// Integral term of an integral-only controller after 1 s of 2 m/s error and `settled` seconds without error
+    fn integral_after_error_removed(integral_leak: f64, settled: f64) -> f64 {
+        let mut pid = PIDController::new(0.0, 1.0, 0.0);
+        pid.set_integral_leak(integral_leak);
+        let mut time = 1.0;
+        pid.compute(20.0, 18.0, time, None, 0.0, 0.0, 0.0).unwrap();
+        for _ in 0..10 {
+            time += 0.1;
+            pid.compute(20.0, 18.0, time, None, 0.0, 0.0, 0.0).unwrap();
+        }
+        for _ in 0..(settled * 10.0).round() as usize {
+            time += 0.1;
+            pid.compute(20.0, 20.0, time, None, 0.0, 0.0, 0.0).unwrap();
+        }
+        pid.integral_term()
+    }
+
+    #[test]
+    fn leaky_integral_decays_once_the_error_is_removed() {
+        let held = integral_after_error_removed(0.0, 2.0);
+        assert!((held - integral_after_error_removed(0.0, 0.0)).abs() < 1e-9, "without leak the integral is held");
+
+        let leaky_start = integral_after_error_removed(0.5, 0.0);
+        let leaky_end = integral_after_error_removed(0.5, 2.0);
+        assert!(leaky_start < held, "the leak already acts while the error builds up");
+        // Decays by (1 - 0.5 * 0.1) per 0.1 s tick
+        assert!((leaky_end - leaky_start * 0.95f64.powi(20)).abs() < 1e-9, "{} -> {}", leaky_start, leaky_end);
+    }
+
+    #[test]
+    fn creep_applies_a_throttle_floor_below_the_speed_threshold() {
+        // kp 0.01: 1 m/s below a 1 m/s target requests far too little throttle to pull away
+        let pull_away = |creep: bool, velocity: f64| {
+            let mut pid = PIDController::new(0.01, 0.0, 0.0);
+            if creep {
+                pid.set_creep_config(0.15, 0.5);
+            }
+            pid.compute(1.0 + velocity, velocity, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+            pid.compute(1.0 + velocity, velocity, 1.1, None, 0.0, 0.0, 0.0).unwrap()
+        };
+
+        let plain = pull_away(false, 0.0);
+        assert!(plain.throttle > 0.0 && plain.throttle < 0.15, "{:?}", plain);
+        let creeping = pull_away(true, 0.0);
+        assert_eq!((creeping.throttle, creeping.brake), (0.15, 0.0));
+        assert!(creeping.acceleration > plain.acceleration);
+
+        // Above the threshold the PID output is left alone
+        let moving = pull_away(true, 2.0);
+        assert!(moving.throttle < 0.15);
+        assert_eq!(moving.throttle, pull_away(false, 2.0).throttle);
+    }
+
+    /// Outputs of a derivative-only controller on a measurement with alternating ±0.1 m/s noise
+    fn derivative_outputs_on_noise(tau_d: f64) -> Vec<f64> {
+        let mut pid = PIDController::new(0.0, 0.0, 0.1);
+        pid.set_derivative_filter(tau_d);
+        (0..100)
+            .map(|tick| {
+                let noise = if tick % 2 == 0 { 0.1 } else { -0.1 };
+                pid.compute(15.0, 15.0 + noise, 1.0 + tick as f64 * 0.1, None, 0.0, 0.0, 0.0).unwrap().acceleration
+            })
+            .skip(10)
+            .collect()
+    }
+
+    #[test]
+    fn derivative_filter_reduces_noise_amplification() {
+        assert_eq!(PIDController::new(0.0, 0.0, 0.1).get_derivative_filter(), 0.0, "unfiltered by default");
+        let (_, raw_variance) = mean_and_variance(&derivative_outputs_on_noise(0.0));
+        let (_, filtered_variance) = mean_and_variance(&derivative_outputs_on_noise(0.5));
+        assert!(raw_variance > 0.0);
+        assert!(filtered_variance < raw_variance / 10.0, "filtered {} vs raw {}", filtered_variance, raw_variance);
+    }
+
+    #[test]
+    fn reset_clears_the_filtered_derivative() {
+        let run = |pid: &mut PIDController| -> Vec<f64> {
+            [(2.0, 15.0), (2.1, 14.0), (2.2, 14.5)].iter()
+                .map(|&(time, velocity)| pid.compute(20.0, velocity, time, None, 0.0, 0.0, 0.0).unwrap().acceleration)
+                .collect()
+        };
+        let mut fresh = PIDController::new(0.0, 0.0, 0.1);
+        fresh.set_derivative_filter(0.5);
+        let expected = run(&mut fresh);
+
+        let mut pid = PIDController::new(0.0, 0.0, 0.1);
+        pid.set_derivative_filter(0.5);
+        pid.compute(20.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        pid.compute(20.0, 18.0, 1.1, None, 0.0, 0.0, 0.0).unwrap();
+        pid.reset();
+        assert_eq!(run(&mut pid), expected, "no derivative left over from before the reset");
+    }
+
+    #[test]
+    fn persistent_obstacle_at_standstill_ends_in_the_standstill_hold() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_max_braking_time(2.0);
+        assert_eq!(pid.get_max_braking_time(), 2.0);
+        let obstacle = obstacle_at(1.5);
+        pid.compute(10.0, 0.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+
+        let mut regimes = Vec::new();
+        for tick in 1..=6 {
+            let result = pid.compute(10.0, 0.0, 1.0 + tick as f64 * 0.5, Some(&obstacle), 0.0, 0.0, 0.0).unwrap();
+            regimes.push((result.emergency_brake_engaged, result.regime));
+        }
+        assert!(regimes[..4].iter().all(|&(emergency, _)| emergency), "{:?}", regimes);
+        assert!(pid.is_standstill_hold(), "2 s of emergency braking at standstill trigger the cutout");
+        assert_eq!(regimes[5], (false, ControlRegime::StandstillHold), "emergency braking is no longer recomputed");
+
+        // Moving again leaves the hold
+        pid.compute(10.0, 1.0, 4.5, None, 0.0, 0.0, 0.0).unwrap();
+        assert!(!pid.is_standstill_hold());
+    }
+
+    #[test]
+    fn braking_cutout_needs_the_vehicle_stopped() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_max_braking_time(2.0);
+        pid.compute(10.0, 5.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        for tick in 1..=6 {
+            pid.compute(10.0, 5.0, 1.0 + tick as f64 * 0.5, Some(&obstacle_at(1.5)), 0.0, 0.0, 0.0).unwrap();
+        }
+        assert!(!pid.is_standstill_hold());
+    }
+
+    #[test]
+    fn steer_step_ramps_within_the_rate_limit() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_max_steer_rate(0.5);
+        let steer = |pid: &mut PIDController, time: f64, input: f64| pid.compute(10.0, 10.0, time, None, 0.0, input, 0.0).unwrap().steer;
+
+        assert_eq!(steer(&mut pid, 1.0, 0.0), 0.0);
+        let ramp: Vec<f64> = (1..=6).map(|tick| steer(&mut pid, 1.0 + tick as f64 * 0.2, 0.4)).collect();
+        let expected = [0.1, 0.2, 0.3, 0.4, 0.4, 0.4];
+        assert!(ramp.iter().zip(expected).all(|(steer, expected)| (steer - expected).abs() < 1e-9), "{:?}", ramp);
+
+        // Back to straight ahead at the same rate
+        assert!((steer(&mut pid, 2.4, 0.0) - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn steer_passes_through_without_rate_limit() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.compute(10.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        assert_eq!(pid.compute(10.0, 10.0, 1.1, None, 0.0, 0.8, 0.0).unwrap().steer, 0.8);
+    }
+
+    /// The preset tunables as currently set on the controller
+    fn applied_values(pid: &PIDController) -> PresetValues {
+        let snapshot = pid.config_snapshot();
+        PresetValues {
+            kp: snapshot.kp,
+            ki: snapshot.ki,
+            kd: snapshot.kd,
+            derivative_tau: snapshot.derivative_tau,
+            soft_start_duration: snapshot.soft_start_duration,
+            coast_band: snapshot.coast_band,
+            max_steer_rate: snapshot.max_steer_rate,
+        }
+    }
+
+    #[test]
+    fn each_preset_applies_its_documented_values() {
+        let mut pid = PIDController::new(1.0, 1.0, 1.0);
+        for preset in [Preset::Sport, Preset::Comfort, Preset::Normal] {
+            pid.apply_preset(preset);
+            assert_eq!(applied_values(&pid), preset.values(), "{:?}", preset);
+        }
+        assert_eq!(Preset::Comfort.values().soft_start_duration, 3.0);
+        assert!(Preset::Sport.values().kp > Preset::Normal.values().kp && Preset::Normal.values().kp > Preset::Comfort.values().kp);
+    }
+
+    #[test]
+    fn normal_preset_matches_the_built_in_defaults() {
+        let defaults = crate::pid_config::PidConfig::default().build_controller();
+        assert_eq!(applied_values(&defaults), Preset::Normal.values());
+    }
+
+    #[test]
+    fn reengagement_waits_until_the_path_is_clear() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_reengage_clearance(Some(15.0));
+        assert_eq!(pid.get_reengage_clearance(), 15.0);
+        pid.compute(10.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        pid.compute(10.0, 10.0, 1.1, None, 0.0, 0.0, 0.5).unwrap();
+        assert!(pid.is_cruise_suspended(), "the driver braked");
+
+        let blocked = pid.compute(10.0, 10.0, 1.2, Some(&obstacle_at(12.0)), 0.0, 0.0, 0.0).unwrap();
+        assert!(pid.is_cruise_suspended() && !blocked.cruise_can_reengage, "obstacle within the clearance");
+
+        let clear = pid.compute(10.0, 10.0, 1.3, Some(&obstacle_at(20.0)), 0.0, 0.0, 0.0).unwrap();
+        assert!(!pid.is_cruise_suspended() && clear.regime != ControlRegime::DriverOverride, "{:?}", clear);
+    }
+
+    #[test]
+    fn reengage_clearance_defaults_to_the_slow_down_distance() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        let (_, slow_down_distance, _) = pid.get_emergency_config();
+        assert_eq!(pid.get_reengage_clearance(), slow_down_distance);
+        pid.set_reengage_clearance(Some(-1.0));
+        assert_eq!(pid.get_reengage_clearance(), 0.0);
+    }
+
+    #[test]
+    fn corridor_widens_with_speed() {
+        assert_eq!(Corridor::default().at_speed(0.0), Corridor::default().at_speed(30.0));
+
+        let corridor = Corridor {
+            min_width: 2.0,
+            max_width: 4.0,
+            width_per_speed: 0.1,
+            min_range: 20.0,
+            max_range: 60.0,
+            range_per_speed: 2.0,
+        };
+        assert_eq!(corridor.at_speed(0.0), (2.0, 20.0));
+        assert_eq!(corridor.at_speed(10.0), (3.0, 40.0));
+        assert_eq!(corridor.at_speed(30.0), (4.0, 60.0));
+
+        // 1.5 m to the side is outside the 2 m standstill corridor but inside the 4 m one at 20 m/s
+        let lidar = obstacles_at(&[(15.0, 1.5), (40.0, 0.0)]);
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_corridor(corridor);
+        pid.compute(0.0, 0.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        assert!(pid.path_obstacles(&lidar, 0.0).is_empty());
+
+        pid.compute(20.0, 20.0, 1.1, None, 0.0, 0.0, 0.0).unwrap();
+        let included: Vec<_> = pid.path_obstacles(&lidar, 0.0).iter().map(|o| (o.x, o.y)).collect();
+        assert_eq!(included, vec![(15.0, 1.5), (40.0, 0.0)]);
+    }
+
+    #[test]
+    fn single_frame_artifact_brakes_gently_without_disengaging() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_emergency_confirmation(3);
+        assert_eq!(pid.get_emergency_confirmation(), 3);
+        pid.compute(10.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+
+        let artifact = pid.compute(10.0, 10.0, 1.1, Some(&obstacle_at(2.0)), 0.0, 0.0, 0.0).unwrap();
+        assert!(!artifact.emergency_brake_engaged);
+        assert!(!artifact.cruise_should_disengage);
+        assert!(artifact.acceleration < 0.0 && artifact.acceleration >= -1.0, "{}", artifact.acceleration);
+
+        // Clear frames restart the count, only an uninterrupted run confirms the obstacle
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_emergency_confirmation(3);
+        let flags = emergency_flags(&mut pid, &[None, Some(2.0), Some(2.0), None, Some(2.0), Some(2.0), Some(2.0)]);
+        assert_eq!(flags, vec![false, false, false, false, false, false, true]);
+    }
+
+    #[test]
+    fn stop_hold_brakes_at_standstill_until_a_positive_setpoint() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.compute(0.0, 0.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        let idle = pid.compute(0.0, 0.0, 1.1, None, 0.0, 0.0, 0.0).unwrap();
+        assert_eq!(idle.brake, 0.0);
+
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_stop_hold(0.4, 0.3);
+        assert_eq!(pid.get_stop_hold(), (0.4, 0.3));
+        pid.compute(0.0, 0.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        for (i, velocity) in [0.0, 0.2, 0.1].into_iter().enumerate() {
+            let result = pid.compute(0.0, velocity, 1.1 + i as f64 * 0.1, None, 0.0, 0.0, 0.0).unwrap();
+            assert!((result.brake - 0.4).abs() < 1e-6, "{}", result.brake);
+            assert_eq!(result.throttle, 0.0);
+        }
+
+        let released = pid.compute(10.0, 0.0, 1.4, None, 0.0, 0.0, 0.0).unwrap();
+        assert_eq!(released.brake, 0.0);
+        assert!(released.acceleration > 0.0, "{}", released.acceleration);
+    }
+
+    #[test]
+    fn acceleration_limits_require_safety_at_least_comfort() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        for (comfort, safety) in [(2.0, 1.0), (0.0, 1.0), (1.0, f64::INFINITY)] {
+            assert_eq!(pid.set_acceleration_limits(comfort, safety),
+                       Err(PidError::InvalidAccelerationLimits { comfort, safety }));
+        }
+        assert_eq!(pid.set_acceleration_limits(1.0, 1.0), Ok(()));
+        assert_eq!(pid.get_acceleration_limits(), (1.0, 1.0));
+    }
+
+    #[test]
+    fn normal_output_respects_comfort_while_emergency_reaches_safety() {
+        let limited = || {
+            let mut pid = PIDController::new(0.5, 0.0, 0.0);
+            pid.set_acceleration_limits(1.0, 3.0).unwrap();
+            pid.compute(10.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+            pid
+        };
+
+        let result = limited().compute(30.0, 10.0, 1.1, None, 0.0, 0.0, 0.0).unwrap();
+        assert_eq!(result.acceleration, 1.0);
+
+        let result = limited().compute(10.0, 10.0, 1.1, Some(&obstacle_at(2.0)), 0.0, 0.0, 0.0).unwrap();
+        assert!(result.emergency_brake_engaged);
+        assert_eq!(result.acceleration, -3.0);
+    }
+
+    /// First output after re-engaging from a manual brake while coasting down at 0.3 m/s²
+    fn first_reengaged_output(bumpless: bool) -> f64 {
+        let mut pid = PIDController::new(0.5, 0.1, 0.0);
+        pid.set_bumpless_transfer(bumpless);
+        assert_eq!(pid.get_bumpless_transfer(), bumpless);
+        let speed_at = |i: usize| 20.0 - 0.03 * i as f64;
+        pid.compute(20.0, speed_at(0), 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        for i in 1..=5 {
+            let result = pid.compute(20.0, speed_at(i), 1.0 + i as f64 * 0.1, None, 0.0, 0.0, 0.5).unwrap();
+            assert!(result.manual_brake_detected);
+        }
+        pid.compute(20.0, speed_at(6), 1.6, None, 0.0, 0.0, 0.0).unwrap().acceleration
+    }
+
+    #[test]
+    fn bumpless_transfer_removes_the_reengagement_jump() {
+        let coasting = -0.3;
+        let without = first_reengaged_output(false);
+        let with = first_reengaged_output(true);
+        assert!((without - coasting).abs() > 0.3, "{}", without);
+        assert!((with - coasting).abs() < 1e-3, "{}", with);
+    }
+
+    #[test]
+    fn normal_braking_is_capped_while_emergencies_use_full_brake() {
+        let capped = || {
+            let mut pid = PIDController::new(0.5, 0.0, 0.0);
+            pid.set_max_normal_brake(0.1);
+            assert_eq!(pid.get_max_normal_brake(), 0.1);
+            pid.compute(20.0, 20.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+            pid
+        };
+
+        let uncapped = PIDResult::new(-MAX_PID_ACCELERATION);
+        assert!(uncapped.brake > 0.1, "{}", uncapped.brake);
+        let normal = capped().compute(0.0, 20.0, 1.1, None, 0.0, 0.0, 0.0).unwrap();
+        assert_eq!(normal.brake, 0.1);
+        assert!(normal.acceleration > uncapped.acceleration, "{}", normal.acceleration);
+
+        // Strong enough braking to saturate the pedal map
+        let mut pid = capped();
+        pid.set_emergency_config(3.0, 15.0, -20.0);
+        pid.set_acceleration_limits(MAX_PID_ACCELERATION, 20.0).unwrap();
+        let emergency = pid.compute(20.0, 20.0, 1.1, Some(&obstacle_at(2.0)), 0.0, 0.0, 0.0).unwrap();
+        assert!(emergency.emergency_brake_engaged);
+        assert_eq!(emergency.brake, 1.0);
+    }
+
+    #[test]
+    fn setpoint_ramp_bounds_the_initial_acceleration() {
+        let mut step = PIDController::new(0.5, 0.0, 0.0);
+        step.compute(30.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        let stepped = step.compute(30.0, 10.0, 1.1, None, 0.0, 0.0, 0.0).unwrap();
+        assert_eq!(stepped.acceleration, MAX_PID_ACCELERATION);
+
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_setpoint_ramp(1.0);
+        assert_eq!(pid.get_setpoint_ramp(), 1.0);
+        pid.compute(30.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        let mut setpoints = Vec::new();
+        for i in 1..=3 {
+            let result = pid.compute(30.0, 10.0, 1.0 + i as f64 * 0.1, None, 0.0, 0.0, 0.0).unwrap();
+            assert!(result.acceleration <= 0.5 * 0.1 * i as f64 + 1e-9, "{}", result.acceleration);
+            setpoints.push(pid.ramped_setpoint().unwrap());
+        }
+        // 1 m/s² over 0.1 s ticks, starting from the current speed
+        assert!(setpoints.iter().zip([10.1, 10.2, 10.3]).all(|(setpoint, expected)| (setpoint - expected).abs() < 1e-9),
+                "{:?}", setpoints);
+
+        pid.reset();
+        assert_eq!(pid.ramped_setpoint(), None);
+    }
+
+    /// Accelerations at 10 m/s for obstacles from 40 m closer in 5 cm steps, until emergency braking
+    fn slow_down_sweep(brake_blend: BrakeBlend) -> Vec<f64> {
+        (0..800)
+            .map(|step| 40.0 - step as f64 * 0.05)
+            .map_while(|distance| {
+                let mut pid = PIDController::new(0.5, 0.0, 0.0);
+                pid.set_brake_blend(brake_blend);
+                pid.compute(10.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+                let result = pid.compute(10.0, 10.0, 1.1, Some(&obstacle_at(distance)), 0.0, 0.0, 0.0).unwrap();
+                (!result.emergency_brake_engaged).then_some(result.acceleration)
+            })
+            .collect()
+    }
+
+    fn largest_step(accelerations: &[f64]) -> f64 {
+        accelerations.windows(2).map(|pair| (pair[1] - pair[0]).abs()).fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn weighted_brake_blend_is_continuous_across_the_slow_down_zone() {
+        let override_sweep = slow_down_sweep(BrakeBlend::Override);
+        assert!(largest_step(&override_sweep) > 0.3, "Override jumps to gentle braking mid-zone");
+
+        let weighted = slow_down_sweep(BrakeBlend::Weighted);
+        assert_eq!(weighted[0], 0.0, "outside the zone");
+        assert!(*weighted.last().unwrap() < -0.5, "{:?}", weighted.last());
+        assert!(largest_step(&weighted) < 0.05, "{}", largest_step(&weighted));
+    }
+
+    #[test]
+    fn steering_prediction_slows_and_bends_the_corridor_together() {
+        // Above the 30% steering from which the cornering speed reduction starts
+        let steer = 0.5;
+        let PathModel::Curved { wheelbase, max_steer_angle } = PathModel::curved() else { unreachable!() };
+        let radius = wheelbase / (steer * max_steer_angle).tan();
+        let on_arc = (radius * 1.0_f64.sin(), radius * (1.0 - 1.0_f64.cos()));
+        let lidar = obstacles_at(&[on_arc, (10.0, 0.0)]);
+
+        let mut straight = PIDController::new(0.5, 0.0, 0.0);
+        let mut predicting = PIDController::new(0.5, 0.0, 0.0);
+        predicting.set_predict_with_steering(true);
+        assert!(predicting.get_predict_with_steering());
+
+        // Both slow down for the turn by the same factor, only the predicting one bends the corridor
+        let (straight_path, predicted_path) = (straight.predicted_path(steer), predicting.predicted_path(steer));
+        assert!(predicted_path.speed_factor < 1.0);
+        assert_eq!(predicted_path.speed_factor, straight_path.speed_factor);
+        assert_eq!(straight_path.turn_radius, None);
+        assert!((predicted_path.turn_radius.unwrap() - radius).abs() < 1e-9);
+
+        let in_path = |pid: &PIDController| pid.path_obstacles(&lidar, steer).iter().map(|o| (o.x, o.y)).collect::<Vec<_>>();
+        assert_eq!(in_path(&straight), vec![(10.0, 0.0)]);
+        assert_eq!(in_path(&predicting), vec![on_arc]);
+
+        // The reduced target makes the controller brake at the previous target speed
+        for pid in [&mut straight, &mut predicting] {
+            pid.compute(20.0, 20.0, 1.0, None, 0.0, steer, 0.0).unwrap();
+            let result = pid.compute(20.0, 20.0, 1.1, None, 0.0, steer, 0.0).unwrap();
+            assert!(result.acceleration < 0.0, "{}", result.acceleration);
+        }
+    }
+
+    /// Peak and final speed of an 18 -> 20 m/s setpoint step on a plant with 1 s actuator lag
+    fn lagged_plant_run(deadtime: f64) -> (f64, f64) {
+        const DT: f64 = 0.1;
+        let mut pid = PIDController::new(2.0, 0.5, 0.0);
+        pid.set_deadtime(deadtime);
+        let mut vehicle = crate::vehicle_model::VehicleModel::new(1500.0, 0.4, 0.012, 1.0);
+        let mut peak = 0.0_f64;
+        for tick in 1..=1800 {
+            // Ramp up to 18 m/s within the comfort limit and settle before the step
+            let target = if tick <= 600 { (tick as f64 * DT).min(18.0) } else { 20.0 };
+            let result = pid.compute(target, vehicle.velocity(), tick as f64 * DT, None, 0.0, 0.0, 0.0).unwrap();
+            let velocity = vehicle.step(result.acceleration, DT);
+            if tick > 600 {
+                peak = peak.max(velocity);
+            }
+        }
+        (peak, vehicle.velocity())
+    }
+
+    #[test]
+    fn deadtime_compensation_reduces_overshoot_on_a_lagged_plant() {
+        let (uncompensated_peak, _) = lagged_plant_run(0.0);
+        let (compensated_peak, compensated_final) = lagged_plant_run(1.0);
+        // Deadtime matching the actuator lag: less than half the overshoot, no steady-state offset
+        assert!(compensated_peak - 20.0 < (uncompensated_peak - 20.0) / 2.0, "peak {} vs {}", compensated_peak, uncompensated_peak);
+        assert!((compensated_final - 20.0).abs() < 0.05, "{}", compensated_final);
+    }
+
+    #[test]
+    fn large_errors_report_the_unclamped_request() {
+        let mut pid = PIDController::new(1.0, 0.0, 0.0);
+        pid.compute(30.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        let result = pid.compute(30.0, 10.0, 1.1, None, 0.0, 0.0, 0.0).unwrap();
+        assert!(result.saturated, "{:?}", result);
+        assert_eq!(result.acceleration, MAX_PID_ACCELERATION);
+        assert!((result.requested_acceleration - 20.0).abs() < 1e-9, "{}", result.requested_acceleration);
+
+        // Within the limits the request is passed through unchanged
+        let result = pid.compute(30.0, 29.5, 1.2, None, 0.0, 0.0, 0.0).unwrap();
+        assert!(!result.saturated);
+        assert_eq!(result.requested_acceleration, result.acceleration);
+        assert!((result.acceleration - 0.5).abs() < 1e-9, "{}", result.acceleration);
+    }
+
+    /// Regimes of ticks at 10 m/s with the obstacle alternating 0.3 m inside and outside the slow-down distance
+    fn boundary_regimes(hysteresis: f64) -> Vec<ControlRegime> {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_slow_down_hysteresis(hysteresis);
+        let (_, slow_down_distance) = pid.braking_distances(10.0, 10.0);
+        pid.compute(10.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        (1..=10)
+            .map(|tick| {
+                let x = if tick % 2 == 1 { slow_down_distance - 0.3 } else { slow_down_distance + 0.3 };
+                pid.compute(10.0, 10.0, 1.0 + tick as f64 * 0.1, Some(&obstacle_at(x)), 0.0, 0.0, 0.0).unwrap().regime
+            })
+            .collect()
+    }
+
+    #[test]
+    fn slow_down_hysteresis_stops_the_regime_flicker_at_the_boundary() {
+        let flickering = boundary_regimes(0.0);
+        assert!(flickering.iter().step_by(2).all(|regime| *regime == ControlRegime::ObstacleSlowdown), "{:?}", flickering);
+        assert!(flickering.iter().skip(1).step_by(2).all(|regime| *regime == ControlRegime::Cruise), "{:?}", flickering);
+
+        let steady = boundary_regimes(1.0);
+        assert!(steady.iter().all(|regime| *regime == ControlRegime::ObstacleSlowdown), "{:?}", steady);
+
+        // The obstacle leaves the zone once it is beyond the margin
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_slow_down_hysteresis(1.0);
+        let (_, slow_down_distance) = pid.braking_distances(10.0, 10.0);
+        pid.compute(10.0, 10.0, 1.0, None, 0.0, 0.0, 0.0).unwrap();
+        let inside = pid.compute(10.0, 10.0, 1.1, Some(&obstacle_at(slow_down_distance - 0.3)), 0.0, 0.0, 0.0).unwrap();
+        let beyond = pid.compute(10.0, 10.0, 1.2, Some(&obstacle_at(slow_down_distance + 1.3)), 0.0, 0.0, 0.0).unwrap();
+        assert_eq!((inside.regime, beyond.regime), (ControlRegime::ObstacleSlowdown, ControlRegime::Cruise));
+    }
+}
@@ -0,0 +1,348 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Pure cruise control math: PID step, acceleration limits, steering compensation and pedal mapping.
+//!
+//! Everything here is a pure function of its arguments (no logging, no clock, no shared state,
+//! no allocation), so it is tested in isolation. `PIDController` wraps it with logging, lidar
+//! handling and state bookkeeping. Built as its own library, which is `#![no_std]` without the
+//! default `std` feature: `cargo check --lib --no-default-features` keeps it that way.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+/// Default symmetric limit of normal PID output, m/s²
+pub const MAX_PID_ACCELERATION: f64 = 1.5;
+
+/// Overspeed ratio above the setpoint at which the PID is bypassed for gentle braking
+pub const OVERSPEED_RATIO: f64 = 0.15;
+
+/// Throttle map breakpoints as (acceleration m/s², throttle 0.0-1.0), linear in between:
+/// gentle (0-0.5 m/s²) uses 0-20%, moderate (0.5-1.5 m/s²) 20-60%, higher (1.5-3.0 m/s²) 60-100%
+pub const THROTTLE_CURVE: [(f64, f64); 4] = [(0.0, 0.0), (0.5, 0.2), (1.5, 0.6), (3.0, 1.0)];
+
+/// Brake map breakpoints as (deceleration m/s², brake 0.0-1.0), linear in between:
+/// gentle (0-0.5 m/s²) uses 0-15%, moderate (0.5-2.0 m/s²) 15-50%, hard (2.0-8.0 m/s²) 50-100%
+pub const BRAKE_CURVE: [(f64, f64); 4] = [(0.0, 0.0), (0.5, 0.15), (2.0, 0.5), (8.0, 1.0)];
+
+/// Proportional, integral and derivative gains
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidGains {
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    pub integral_leak: f64, // 1/s, fraction of the accumulated error forgotten per second (0.0 = none)
+    pub derivative_tau: f64, // s, time constant of the derivative low-pass filter (0.0 = raw derivative)
+    pub comfort_limit: f64,  // m/s², symmetric limit of the regular PID output (MAX_PID_ACCELERATION by default)
+}
+
+/// Error terms carried from one PID step to the next
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PidState {
+    pub velocity_error: f64,
+    pub previous_error: f64,
+    pub accumulated_error: f64,
+    pub filtered_derivative: f64,
+}
+
+impl PidState {
+    /// Integral contribution Ki * accumulated error
+    pub fn integral_term(&self, gains: &PidGains) -> f64 {
+        gains.ki * self.accumulated_error
+    }
+}
+
+/// How a `pid_step` output was produced
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepMode {
+    /// Regular PID regulation, limited to [-comfort_limit, comfort_limit]
+    Pid,
+    /// Gentle braking because the vehicle is more than OVERSPEED_RATIO above the setpoint
+    OverspeedBraking { speed_excess: f64 },
+    /// Held feedforward acceleration plus P and D terms, integral frozen
+    Hold { hold_acceleration: f64 },
+}
+
+/// Result of one PID step, all values in the direction of travel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepOutput {
+    pub acceleration: f64, // m/s²
+    pub requested_acceleration: f64, // m/s², before the comfort limit
+    pub throttle: f64,     // 0.0 to 1.0
+    pub brake: f64,        // 0.0 to 1.0
+    pub mode: StepMode,
+}
+
+/// One control step from speeds along the direction of travel (m/s) and the elapsed time (s).
+/// With `hold_acceleration` the integral is frozen and the held acceleration is used as the base
+/// instead, so the output does not ripple around the setpoint
+pub fn pid_step(
+    gains: &PidGains,
+    state: &mut PidState,
+    desired_velocity: f64,
+    current_velocity: f64,
+    delta_time: f64,
+    hold_acceleration: Option<f64>,
+) -> StepOutput {
+    // Check if we're significantly over the desired speed (more than 15% overspeed)
+    if current_velocity > desired_velocity + (desired_velocity * OVERSPEED_RATIO) {
+        // Apply gentle negative acceleration (braking) when we need to slow down
+        let speed_excess = current_velocity - desired_velocity;
+
+        // Use a much gentler braking approach
+        let gentle_braking = if speed_excess > 2.0 {
+            -1.0  // Maximum gentle braking for significant overspeed
+        } else {
+            -speed_excess * 0.8  // Proportional gentle braking
+        };
+        return output(gentle_braking.max(-gains.comfort_limit), gentle_braking, StepMode::OverspeedBraking { speed_excess });
+    }
+
+    // Normal PID control for acceleration and gentle deceleration
+    state.previous_error = state.velocity_error;
+    state.velocity_error = desired_velocity - current_velocity;
+    let derivative_error = filter_derivative(gains, state, (state.velocity_error - state.previous_error) / delta_time, delta_time);
+
+    if let Some(hold_acceleration) = hold_acceleration {
+        let acceleration = hold_acceleration
+            + (gains.kp * state.velocity_error)
+            + (gains.kd * derivative_error);
+        return output(limit_acceleration_to(acceleration, gains.comfort_limit), acceleration, StepMode::Hold { hold_acceleration });
+    }
+
+    // Leaky integrator: decay old error before adding the new contribution
+    state.accumulated_error *= (1.0 - gains.integral_leak * delta_time).max(0.0);
+    state.accumulated_error += state.velocity_error * delta_time;
+    let acceleration = (gains.kp * state.velocity_error)
+        + (gains.ki * state.accumulated_error)
+        + (gains.kd * derivative_error);
+
+    output(limit_acceleration_to(acceleration, gains.comfort_limit), acceleration, StepMode::Pid)
+}
+
+/// First-order low-pass filter on the derivative (time constant `derivative_tau`), so measurement
+/// noise is not amplified by the D term
+fn filter_derivative(gains: &PidGains, state: &mut PidState, raw_derivative: f64, delta_time: f64) -> f64 {
+    if gains.derivative_tau <= 0.0 {
+        return raw_derivative;
+    }
+    state.filtered_derivative += (raw_derivative - state.filtered_derivative) * delta_time / (gains.derivative_tau + delta_time);
+    state.filtered_derivative
+}
+
+fn output(acceleration: f64, requested_acceleration: f64, mode: StepMode) -> StepOutput {
+    let (throttle, brake) = acceleration_to_pedals(acceleration);
+    StepOutput { acceleration, requested_acceleration, throttle, brake, mode }
+}
+
+impl StepOutput {
+    /// Whether the comfort limit clipped the requested acceleration
+    pub fn saturated(&self) -> bool {
+        self.acceleration != self.requested_acceleration
+    }
+}
+
+/// Clamp an acceleration to [-limit, limit]
+pub fn limit_acceleration_to(acceleration: f64, limit: f64) -> f64 {
+    acceleration.max(-limit).min(limit)
+}
+
+/// Speed reduction factor for a steering input from -1.0 (full left) to 1.0 (full right).
+/// More steering = more speed reduction for safer cornering.
+pub fn steering_compensation(steer_input: f64) -> f64 {
+    // Use absolute value since turning left or right both require speed reduction
+    let abs_steering = steer_input.abs();
+
+    const MAX_SPEED_REDUCTION: f64 = 0.8; // Maximum 20% speed reduction at full steering
+    const STEERING_SENSITIVITY: f64 = 0.3; // Start reducing at 30% steering (0.3 abs value)
+
+    if abs_steering <= STEERING_SENSITIVITY {
+        1.0 // No speed reduction for gentle steering
+    } else {
+        // Progressive speed reduction: 30% steering = 100% speed, 100% steering = 80% speed
+        let reduction_factor = (abs_steering - STEERING_SENSITIVITY) / (1.0 - STEERING_SENSITIVITY);
+        1.0 - (reduction_factor * (1.0 - MAX_SPEED_REDUCTION))
+    }
+}
+
+/// Convert acceleration (m/s²) to (throttle, brake) values (0.0-1.0)
+pub fn acceleration_to_pedals(acceleration: f64) -> (f64, f64) {
+    if acceleration > 0.0 {
+        // Positive acceleration -> throttle
+        // Use progressive scaling: small accelerations get small throttle
+        (interpolate(&THROTTLE_CURVE, acceleration), 0.0)
+    } else {
+        // Negative acceleration -> brake
+        (0.0, interpolate(&BRAKE_CURVE, -acceleration))
+    }
+}
+
+/// Like `acceleration_to_pedals`, but decelerations down to `coast_band` m/s² are left to engine
+/// braking (no throttle, no brake); the brake curve starts at the edge of the band
+pub fn acceleration_to_pedals_with_coast(acceleration: f64, coast_band: f64) -> (f64, f64) {
+    if acceleration < 0.0 && coast_band > 0.0 {
+        let braking = -acceleration - coast_band;
+        if braking <= 0.0 {
+            (0.0, 0.0)
+        } else {
+            (0.0, interpolate(&BRAKE_CURVE, braking))
+        }
+    } else {
+        acceleration_to_pedals(acceleration)
+    }
+}
+
+/// Inverse of `acceleration_to_pedals`: recover the acceleration (m/s²) from pedal values.
+/// Brake takes precedence when both pedals are applied. Saturated pedals map to the last breakpoint.
+pub fn pedals_to_acceleration(throttle: f64, brake: f64) -> f64 {
+    if brake > 0.0 {
+        -interpolate_inverse(&BRAKE_CURVE, brake)
+    } else {
+        interpolate_inverse(&THROTTLE_CURVE, throttle)
+    }
+}
+
+/// Piecewise linear lookup of a pedal value, saturating after the last breakpoint
+pub fn interpolate(curve: &[(f64, f64)], magnitude: f64) -> f64 {
+    for pair in curve.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        if magnitude <= x1 {
+            return (y0 + (magnitude - x0) * (y1 - y0) / (x1 - x0)).clamp(0.0, 1.0);
+        }
+    }
+    curve[curve.len() - 1].1
+}
+
+/// Piecewise linear inverse lookup of an acceleration magnitude from a pedal value
+pub fn interpolate_inverse(curve: &[(f64, f64)], pedal: f64) -> f64 {
+    for pair in curve.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        if pedal <= y1 {
+            return (x0 + (pedal.max(0.0) - y0) * (x1 - x0) / (y1 - y0)).max(0.0);
+        }
+    }
+    curve[curve.len() - 1].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAINS: PidGains = PidGains {
+        kp: 0.5,
+        ki: 0.1,
+        kd: 0.0,
+        integral_leak: 0.0,
+        derivative_tau: 0.0,
+        comfort_limit: MAX_PID_ACCELERATION,
+    };
+
+    #[test]
+    fn pid_step_sums_the_terms() {
+        let mut state = PidState::default();
+        let step = pid_step(&GAINS, &mut state, 20.0, 18.0, 0.5, None);
+        assert_eq!(step.mode, StepMode::Pid);
+        assert_eq!(state.accumulated_error, 1.0);
+        assert_eq!(state.integral_term(&GAINS), 0.1);
+        assert_eq!(step.acceleration, 0.5 * 2.0 + 0.1 * 1.0);
+        assert!(!step.saturated());
+        assert_eq!((step.throttle, step.brake), acceleration_to_pedals(step.acceleration));
+    }
+
+    #[test]
+    fn pid_step_is_limited_to_the_comfort_limit() {
+        let mut state = PidState::default();
+        let step = pid_step(&GAINS, &mut state, 20.0, 10.0, 0.1, None);
+        assert_eq!(step.acceleration, MAX_PID_ACCELERATION);
+        assert!(step.saturated());
+
+        let gains = PidGains { comfort_limit: 0.8, ..GAINS };
+        let step = pid_step(&gains, &mut PidState::default(), 10.0, 20.0, 0.1, None);
+        assert_eq!(step.acceleration, -0.8, "overspeed braking respects the limit too");
+    }
+
+    #[test]
+    fn overspeed_brakes_gently_without_touching_the_state() {
+        let mut state = PidState { accumulated_error: 3.0, ..PidState::default() };
+        let step = pid_step(&GAINS, &mut state, 10.0, 12.0, 0.1, None);
+        assert_eq!(step.mode, StepMode::OverspeedBraking { speed_excess: 2.0 });
+        assert!((step.requested_acceleration + 1.6).abs() < 1e-9);
+        assert_eq!(step.acceleration, -MAX_PID_ACCELERATION);
+        assert_eq!(state.accumulated_error, 3.0);
+
+        let step = pid_step(&GAINS, &mut state, 10.0, 15.0, 0.1, None);
+        assert_eq!(step.acceleration, -1.0);
+    }
+
+    #[test]
+    fn hold_freezes_the_integral() {
+        let mut state = PidState { accumulated_error: 3.0, ..PidState::default() };
+        let step = pid_step(&GAINS, &mut state, 20.0, 19.8, 0.1, Some(0.4));
+        assert_eq!(step.mode, StepMode::Hold { hold_acceleration: 0.4 });
+        assert_eq!(state.accumulated_error, 3.0);
+        assert!((step.acceleration - (0.4 + 0.5 * 0.2)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn integral_leak_decays_the_accumulated_error() {
+        let gains = PidGains { integral_leak: 0.5, ..GAINS };
+        let mut state = PidState { accumulated_error: 4.0, ..PidState::default() };
+        pid_step(&gains, &mut state, 20.0, 20.0, 1.0, None);
+        assert_eq!(state.accumulated_error, 2.0);
+
+        let gains = PidGains { integral_leak: 5.0, ..GAINS };
+        pid_step(&gains, &mut state, 20.0, 20.0, 1.0, None);
+        assert_eq!(state.accumulated_error, 0.0, "the decay factor never goes negative");
+    }
+
+    #[test]
+    fn derivative_filter_smooths_a_step() {
+        let gains = PidGains { kp: 0.0, ki: 0.0, kd: 1.0, derivative_tau: 0.9, ..GAINS };
+        let mut state = PidState::default();
+        let step = pid_step(&gains, &mut state, 20.0, 19.0, 0.1, None);
+        assert!((step.acceleration - 1.0).abs() < 1e-9, "raw derivative 10, filtered to a tenth");
+    }
+
+    #[test]
+    fn steering_compensation_reduces_speed_past_the_sensitivity() {
+        assert_eq!(steering_compensation(0.0), 1.0);
+        assert_eq!(steering_compensation(-0.3), 1.0);
+        assert!((steering_compensation(0.65) - 0.9).abs() < 1e-9);
+        assert_eq!(steering_compensation(1.0), 0.8);
+        assert_eq!(steering_compensation(-1.0), 0.8);
+    }
+
+    #[test]
+    fn pedal_curves_hit_their_breakpoints() {
+        for (acceleration, throttle) in THROTTLE_CURVE {
+            assert!((acceleration_to_pedals(acceleration).0 - throttle).abs() < 1e-9);
+        }
+        for (deceleration, brake) in BRAKE_CURVE {
+            assert!((acceleration_to_pedals(-deceleration).1 - brake).abs() < 1e-9);
+        }
+        assert_eq!(acceleration_to_pedals(5.0), (1.0, 0.0));
+        assert_eq!(acceleration_to_pedals(-12.0), (0.0, 1.0));
+        assert_eq!(pedals_to_acceleration(1.0, 0.0), 3.0);
+        assert_eq!(pedals_to_acceleration(1.0, 1.0), -8.0, "brake takes precedence");
+    }
+
+    #[test]
+    fn coast_band_shifts_the_brake_curve() {
+        assert_eq!(acceleration_to_pedals_with_coast(-0.3, 0.5), (0.0, 0.0));
+        assert_eq!(acceleration_to_pedals_with_coast(-2.5, 0.5), acceleration_to_pedals(-2.0));
+        assert_eq!(acceleration_to_pedals_with_coast(1.0, 0.5), acceleration_to_pedals(1.0));
+        assert_eq!(acceleration_to_pedals_with_coast(-0.3, 0.0), acceleration_to_pedals(-0.3));
+    }
+}
@@ -0,0 +1,150 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Raw capture of the inbound messages for offline replay.
+//!
+//! Every stream is appended to `<dir>/<stream>.ndjson`, one JSON object per message:
+//! `{"ts": <unix µs>, "resource": "<source uri>", "format": "<media type>", "base64_payload": "..."}`
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use base64ct::{Base64, Encoding};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use up_rust::{UMessage, UMessageBuilder, UPayloadFormat, UUri};
+
+use crate::logging;
+
+const RECORDING_EXTENSION: &str = "ndjson";
+
+/// One captured message, as stored in the recording files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    pub ts: u64,                // Unix time of receipt in microseconds
+    pub resource: String,       // Source URI of the message
+    pub format: Option<String>, // Media type of the payload format, None = unspecified
+    pub base64_payload: String,
+}
+
+impl RecordedMessage {
+    fn from_message(message: &UMessage) -> Self {
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64;
+        let payload = message.payload.as_deref().unwrap_or_default();
+        let mut encoded = vec![0u8; Base64::encoded_len(payload)];
+        let base64_payload = Base64::encode(payload, &mut encoded)
+            .expect("Base64 buffer sized by encoded_len")
+            .to_string();
+        Self {
+            ts,
+            resource: message.source().map(|uri| uri.to_uri(false)).unwrap_or_default(),
+            format: message.payload_format().and_then(UPayloadFormat::to_media_type),
+            base64_payload,
+        }
+    }
+
+    /// Rebuild the message as published on `topic`
+    pub fn to_message(&self, topic: &UUri) -> Result<UMessage, Box<dyn std::error::Error>> {
+        let mut decoded = vec![0u8; self.base64_payload.len()];
+        let payload = Base64::decode(&self.base64_payload, &mut decoded)
+            .map_err(|e| format!("Invalid base64 payload: {}", e))?
+            .to_vec();
+        let format = self.format.as_deref()
+            .and_then(|media_type| UPayloadFormat::from_media_type(media_type).ok())
+            .unwrap_or(UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED);
+        Ok(UMessageBuilder::publish(topic.clone()).build_with_payload(payload, format)?)
+    }
+}
+
+/// Appends the received messages of every stream to its recording file while enabled
+#[derive(Debug, Default)]
+pub struct MessageRecorder {
+    sink: Mutex<Option<RecordingSink>>,
+}
+
+#[derive(Debug)]
+struct RecordingSink {
+    dir: PathBuf,
+    files: HashMap<String, File>,
+}
+
+impl MessageRecorder {
+    /// Record into `dir` from now on, appending to the files of an earlier recording there
+    pub fn start(&self, dir: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        *self.sink.lock().unwrap() = Some(RecordingSink { dir: dir.to_path_buf(), files: HashMap::new() });
+        Ok(())
+    }
+
+    pub fn record(&self, stream: &str, message: &UMessage) {
+        let mut sink = self.sink.lock().unwrap();
+        let Some(sink) = sink.as_mut() else {
+            return;
+        };
+        let line = match serde_json::to_string(&RecordedMessage::from_message(message)) {
+            Ok(line) => line,
+            Err(e) => {
+                error!(target: logging::RECORDING, "Failed to serialize recorded {} message: {}", stream, e);
+                return;
+            }
+        };
+        if !sink.files.contains_key(stream) {
+            let path = sink.dir.join(format!("{}.{}", stream, RECORDING_EXTENSION));
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => {
+                    sink.files.insert(stream.to_string(), file);
+                }
+                Err(e) => {
+                    error!(target: logging::RECORDING, "Failed to open recording file {}: {}", path.display(), e);
+                    return;
+                }
+            }
+        }
+        if let Err(e) = writeln!(sink.files.get_mut(stream).unwrap(), "{}", line) {
+            error!(target: logging::RECORDING, "Failed to record {} message: {}", stream, e);
+        }
+    }
+}
+
+/// Read all recording files in `dir` as (stream, message) pairs in timestamp order
+pub fn read_recording(dir: &Path) -> io::Result<Vec<(String, RecordedMessage)>> {
+    let mut messages = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some(RECORDING_EXTENSION) {
+            continue;
+        }
+        let Some(stream) = path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string) else {
+            continue;
+        };
+        for (number, line) in BufReader::new(File::open(&path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<RecordedMessage>(&line) {
+                Ok(message) => messages.push((stream.clone(), message)),
+                Err(e) => warn!(target: logging::RECORDING, "Skipping line {} of {}: {}", number + 1, path.display(), e),
+            }
+        }
+    }
+    // Stable sort keeps the file order of messages with the same timestamp
+    messages.sort_by_key(|(_, message)| message.ts);
+    Ok(messages)
+}
@@ -0,0 +1,132 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! uProtocol authorities and resource IDs of every stream, see the mapping table in the README.
+//!
+//! Resource IDs are only unique per authority (e.g. 0x8001 is the velocity on `EGOVehicle` but the
+//! target speed on `AAOS`); the const assertions at the bottom reject duplicates at compile time.
+
+// Shared with the test binaries, which only use part of the streams
+#![allow(dead_code)]
+
+use up_rust::{UUri, UUriError};
+
+/// Entity ID of every stream
+pub const UE_ID: u32 = 0;
+/// Major version of every stream
+pub const UE_VERSION: u8 = 2;
+
+/// Vehicle (or simulator) publishing the measured state
+pub const VEHICLE: &str = "EGOVehicle";
+/// Android Automotive HMI publishing the driver inputs
+pub const HMI: &str = "AAOS";
+/// This PID node
+pub const CRUISE_CONTROL: &str = "CruiseControl";
+
+/// `EGOVehicle`: current velocity (m/s)
+pub const VELOCITY: u16 = 0x8001;
+/// `EGOVehicle`: simulation/system time in seconds
+pub const CLOCK: u16 = 0x8002;
+/// `EGOVehicle`: lidar point cloud
+pub const LIDAR: u16 = 0x8003;
+/// `EGOVehicle`: road friction estimate
+pub const FRICTION: u16 = 0x8004;
+
+/// `AAOS`: driver target speed
+pub const TARGET_SPEED: u16 = 0x8001;
+/// `AAOS`: engage/disengage cruise control
+pub const ENGAGE: u16 = 0x8002;
+/// `AAOS`: ADAS speed limit
+pub const SPEED_LIMIT: u16 = 0x8003;
+
+/// `CruiseControl`: acceleration command to the actuators
+pub const ACTUATION: u16 = 0x8001;
+/// `CruiseControl`: pedal values and steer of the driver/control system
+pub const CONTROL_VALUES: u16 = 0x8004;
+/// `CruiseControl`: "at target speed" status
+pub const AT_TARGET_STATUS: u16 = 0x8005;
+/// `CruiseControl`: throttle/brake/steer pedal command output
+pub const PEDAL_COMMAND: u16 = 0x8006;
+/// `CruiseControl`: remote operator commands
+pub const COMMAND: u16 = 0x8007;
+/// `CruiseControl`: replies to operator commands
+pub const COMMAND_STATUS: u16 = 0x8008;
+/// `CruiseControl`: debounced "at speed" status for the operator display
+pub const AT_SPEED_STATUS: u16 = 0x8009;
+/// `CruiseControl`: control regime for HMI display
+pub const REGIME: u16 = 0x800A;
+
+/// URI of a stream, e.g. `uri(VEHICLE, LIDAR)`
+pub fn uri(authority: &str, resource_id: u16) -> Result<UUri, UUriError> {
+    UUri::try_from_parts(authority, UE_ID, UE_VERSION, resource_id)
+}
+
+const fn all_distinct(resource_ids: &[u16]) -> bool {
+    let mut i = 0;
+    while i < resource_ids.len() {
+        let mut j = i + 1;
+        while j < resource_ids.len() {
+            if resource_ids[i] == resource_ids[j] {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+const _: () = assert!(all_distinct(&[VELOCITY, CLOCK, LIDAR, FRICTION]), "duplicate EGOVehicle resource ID");
+const _: () = assert!(all_distinct(&[TARGET_SPEED, ENGAGE, SPEED_LIMIT]), "duplicate AAOS resource ID");
+const _: () = assert!(
+    all_distinct(&[ACTUATION, CONTROL_VALUES, AT_TARGET_STATUS, PEDAL_COMMAND, COMMAND, COMMAND_STATUS, AT_SPEED_STATUS, REGIME]),
+    "duplicate CruiseControl resource ID"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constants_map_to_the_expected_uris() {
+        for (authority, resource_id, expected) in [
+            (VEHICLE, VELOCITY, 0x8001),
+            (VEHICLE, CLOCK, 0x8002),
+            (VEHICLE, LIDAR, 0x8003),
+            (VEHICLE, FRICTION, 0x8004),
+            (HMI, TARGET_SPEED, 0x8001),
+            (HMI, ENGAGE, 0x8002),
+            (HMI, SPEED_LIMIT, 0x8003),
+            (CRUISE_CONTROL, ACTUATION, 0x8001),
+            (CRUISE_CONTROL, COMMAND, 0x8007),
+            (CRUISE_CONTROL, REGIME, 0x800A),
+        ] {
+            let uri = uri(authority, resource_id).unwrap();
+            assert_eq!(uri.authority_name, authority);
+            assert_eq!((uri.ue_id, uri.ue_version_major), (UE_ID, UE_VERSION as u32));
+            assert_eq!(uri.resource_id, expected, "{}", uri.to_uri(false));
+        }
+        assert_eq!(uri(VEHICLE, LIDAR).unwrap().to_uri(false), "//EGOVehicle/0/2/8003");
+    }
+
+    #[test]
+    fn streams_of_one_authority_do_not_collide() {
+        assert!(all_distinct(&[VELOCITY, CLOCK, LIDAR, FRICTION]));
+        assert!(all_distinct(&[TARGET_SPEED, ENGAGE, SPEED_LIMIT]));
+        assert!(all_distinct(&[ACTUATION, CONTROL_VALUES, AT_TARGET_STATUS, PEDAL_COMMAND, COMMAND, COMMAND_STATUS, AT_SPEED_STATUS, REGIME]));
+        assert!(!all_distinct(&[VELOCITY, CLOCK, VELOCITY]));
+    }
+}
@@ -14,6 +14,7 @@
 // limitations under the License.
 //
 
+use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 use tokio::time::{sleep, Duration};
 use rand::Rng;
@@ -21,9 +22,14 @@ use rand::Rng;
 use clap::Parser;
 use log::{info, error};
 use up_transport_zenoh::{UPTransportZenoh, zenoh_config};
-use up_rust::{LocalUriProvider, StaticUriProvider, UUri, UMessageBuilder, UTransport, UPayloadFormat};
+use up_rust::{LocalUriProvider, StaticUriProvider, UListener, UMessage, UMessageBuilder, UTransport, UPayloadFormat};
 use zenoh::{Config};
 
+#[path = "../resources.rs"]
+mod resources;
+mod vehicle_model;
+use vehicle_model::VehicleModel;
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
@@ -37,12 +43,56 @@ struct Args {
     delta: f64,
     #[clap(long, default_value = None)]
     router: Option<String>,
+    /// Close the loop: integrate the published acceleration with a vehicle model every `delta` seconds
+    #[clap(long)]
+    plant: bool,
+    /// Constant target speed (m/s) used with --plant
+    #[clap(long, default_value_t = 15.0)]
+    target: f64,
 }
 
-// Helper function to create a Zenoh configuration
-pub(crate) fn get_zenoh_config() -> zenoh_config::Config {
-    let args = Args::parse();
+// Keeps the latest acceleration command published by the PID controller
+struct AccelerationListener {
+    command: Arc<Mutex<f64>>,
+}
+
+#[async_trait::async_trait]
+impl UListener for AccelerationListener {
+    async fn on_receive(&self, message: UMessage) {
+        if let Some(payload) = message.payload {
+            match decode_acceleration(&payload) {
+                Ok(acceleration) => *self.command.lock().unwrap() = acceleration,
+                Err(e) => error!("Failed to decode acceleration command, keeping the last one: {}", e),
+            }
+        }
+    }
+}
+
+/// Acceleration (m/s²) from an actuation payload in any of the controller's acceleration encodings:
+/// signed text, `{"acceleration": a}`, `{"acceleration": a, "deceleration": d}` and the Ackermann
+/// `{"drive": {"acceleration": a}}`. Pedal values are rejected, the vehicle model needs an acceleration
+fn decode_acceleration(payload: &[u8]) -> Result<f64, String> {
+    let text = std::str::from_utf8(payload).map_err(|e| e.to_string())?.trim();
+    if let Ok(acceleration) = text.parse::<f64>() {
+        return Ok(acceleration);
+    }
+    let json: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("neither a number nor JSON ({})", e))?;
+    let fields = json.get("drive").unwrap_or(&json);
+    if fields.get("throttle").is_some() || fields.get("brake").is_some() {
+        return Err("pedal commands are not supported with --plant, publish an acceleration".to_string());
+    }
+    let number = |name: &str| match fields.get(name) {
+        Some(value) => value.as_f64().map(Some).ok_or_else(|| format!("`{}` is not a number", name)),
+        None => Ok(None),
+    };
+    match (number("acceleration")?, number("deceleration")?) {
+        (Some(acceleration), deceleration) => Ok(acceleration - deceleration.unwrap_or(0.0)),
+        (None, _) => Err("no `acceleration` field".to_string()),
+    }
+}
 
+// Helper function to create a Zenoh configuration
+pub(crate) fn get_zenoh_config(args: &Args) -> zenoh_config::Config {
     let zenoh_string = if let Some(router) = &args.router {
         format!("{{ mode: 'peer', connect: {{ endpoints: [ 'tcp/{}:7447' ] }} }}", router)
     } else {
@@ -58,6 +108,7 @@ pub(crate) fn get_zenoh_config() -> zenoh_config::Config {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
     env_logger::init();
+    let args = Args::parse();
     
     info!("*** Started uProtocol Publisher");
 
@@ -68,16 +119,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize uProtocol transport with Zenoh
     let transport = UPTransportZenoh::builder(uri_provider.get_authority())
         .expect("invalid authority name")
-        .with_config(get_zenoh_config())
+        .with_config(get_zenoh_config(&args))
         .build()
         .await?;
 
 
     // Create URIs for publishing according to the mapping table
-    let clock_uri = UUri::try_from_parts("EGOVehicle", 0, 2, 0x8002)?;      // vehicle/status/clock_status
-    let velocity_uri = UUri::try_from_parts("EGOVehicle", 0, 2, 0x8001)?;   // vehicle/status/velocity_status
-    let target_uri = UUri::try_from_parts("AAOS", 0, 2, 0x8001)?;           // adas/cruise_control/target_speed
-    let engage_uri = UUri::try_from_parts("AAOS", 0, 2, 0x8002)?;           // adas/cruise_control/engage
+    let clock_uri = resources::uri(resources::VEHICLE, resources::CLOCK)?;         // vehicle/status/clock_status
+    let velocity_uri = resources::uri(resources::VEHICLE, resources::VELOCITY)?;   // vehicle/status/velocity_status
+    let target_uri = resources::uri(resources::HMI, resources::TARGET_SPEED)?;     // adas/cruise_control/target_speed
+    let engage_uri = resources::uri(resources::HMI, resources::ENGAGE)?;           // adas/cruise_control/engage
 
     info!("uProtocol Publisher initialized with URIs:");
     info!("  Clock: {}", String::from(&clock_uri));
@@ -85,12 +136,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("  Target Speed: {}", String::from(&target_uri));
     info!("  Engage: {}", String::from(&engage_uri));
 
+    let acceleration_command = Arc::new(Mutex::new(0.0));
+    let mut vehicle = VehicleModel::default();
+
+    if args.plant {
+        let actuation_uri = resources::uri(resources::CRUISE_CONTROL, resources::ACTUATION)?;
+        let listener = AccelerationListener { command: Arc::clone(&acceleration_command) };
+        transport.register_listener(&actuation_uri, None, Arc::new(listener)).await?;
+        info!("Closed loop: vehicle model driven by {} every {}s", String::from(&actuation_uri), args.delta);
+    }
+
     #[allow(unused_mut)]
     let mut engaged = 1;
 
     loop {
-        let velocity = rand::rng().random_range(5.0..15.0);
-        let target = rand::rng().random_range(10.0..20.0);
+        let (velocity, target) = if args.plant {
+            let command = *acceleration_command.lock().unwrap();
+            vehicle.step(command, args.delta);
+            info!("Vehicle model: command={:.2} m/s², applied={:.2} m/s², velocity={:.2} m/s",
+                  command, vehicle.applied_acceleration(), vehicle.velocity());
+            (vehicle.velocity(), args.target)
+        } else {
+            (rand::rng().random_range(5.0..15.0), rand::rng().random_range(10.0..20.0))
+        };
 
         // Getting system time as a timestamp in seconds
         let current_time: f64 = SystemTime::now()
@@ -152,6 +220,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Uncomment to toggle engagement for testing
         // engaged = if engaged == 1 { 0 } else { 1 };
 
-        sleep(Duration::from_secs(2)).await;
+        if args.plant {
+            sleep(Duration::from_secs_f64(args.delta)).await;
+        } else {
+            sleep(Duration::from_secs(2)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_every_acceleration_encoding() {
+        assert_eq!(decode_acceleration(b" -2.5\n"), Ok(-2.5));
+        assert_eq!(decode_acceleration(br#"{"acceleration": 1.2}"#), Ok(1.2));
+        assert_eq!(decode_acceleration(br#"{"acceleration": 0.0, "deceleration": 2.5}"#), Ok(-2.5));
+        assert_eq!(decode_acceleration(br#"{"acceleration": 0.8, "deceleration": 0.0}"#), Ok(0.8));
+        let ackermann = br#"{"header": {"frame_id": "base_link"}, "drive": {"speed": 20.0, "acceleration": -1.5}}"#;
+        assert_eq!(decode_acceleration(ackermann), Ok(-1.5));
+    }
+
+    #[test]
+    fn rejects_what_is_not_an_acceleration() {
+        assert!(decode_acceleration(br#"{"throttle": 0.0, "brake": 0.42}"#).unwrap_err().contains("pedal"));
+        assert!(decode_acceleration(br#"{"speed": 20.0}"#).is_err());
+        assert!(decode_acceleration(br#"{"acceleration": "fast"}"#).is_err());
+        assert!(decode_acceleration(b"fast").is_err());
     }
 }
@@ -0,0 +1,126 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+const GRAVITY: f64 = 9.81; // m/s²
+
+/// Point-mass longitudinal vehicle model used to close the loop around the PID.
+///
+/// The commanded acceleration passes through a first-order actuator lag, then
+/// aerodynamic drag and rolling resistance are subtracted before integrating velocity.
+#[derive(Debug, Clone)]
+pub struct VehicleModel {
+    pub mass: f64,               // kg
+    pub drag_coefficient: f64,   // N/(m/s)², i.e. 0.5 * air density * Cd * frontal area
+    pub rolling_resistance: f64, // dimensionless coefficient
+    pub actuator_lag: f64,       // seconds, time constant of the powertrain/brake response
+    velocity: f64,               // m/s
+    applied_acceleration: f64,   // m/s², actuator output after the lag
+}
+
+impl Default for VehicleModel {
+    /// Mid-size passenger car
+    fn default() -> Self {
+        Self::new(1500.0, 0.4, 0.012, 0.3)
+    }
+}
+
+impl VehicleModel {
+    pub fn new(mass: f64, drag_coefficient: f64, rolling_resistance: f64, actuator_lag: f64) -> Self {
+        Self {
+            mass,
+            drag_coefficient,
+            rolling_resistance,
+            actuator_lag,
+            velocity: 0.0,
+            applied_acceleration: 0.0,
+        }
+    }
+
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// Acceleration currently produced by the actuator (m/s²), lagging the command
+    pub fn applied_acceleration(&self) -> f64 {
+        self.applied_acceleration
+    }
+
+    /// Advance the model by `dt` seconds under the commanded acceleration (m/s²) and return the new velocity
+    pub fn step(&mut self, commanded_acceleration: f64, dt: f64) -> f64 {
+        if dt <= 0.0 {
+            return self.velocity;
+        }
+
+        // First-order lag, discretized so it stays stable for any dt
+        let lag_factor = if self.actuator_lag > 0.0 { dt / (self.actuator_lag + dt) } else { 1.0 };
+        self.applied_acceleration += (commanded_acceleration - self.applied_acceleration) * lag_factor;
+
+        // Resistive forces always oppose the direction of motion
+        let drag = self.drag_coefficient * self.velocity * self.velocity.abs() / self.mass;
+        let rolling = if self.velocity != 0.0 {
+            self.rolling_resistance * GRAVITY * self.velocity.signum()
+        } else {
+            0.0
+        };
+
+        let previous_velocity = self.velocity;
+        self.velocity += (self.applied_acceleration - drag - rolling) * dt;
+
+        // Braking and resistance stop the vehicle, they do not reverse it
+        if self.applied_acceleration <= 0.0 && previous_velocity >= 0.0 && self.velocity < 0.0 {
+            self.velocity = 0.0;
+        }
+
+        self.velocity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actuator_lag_delays_the_command() {
+        let mut vehicle = VehicleModel::new(1500.0, 0.0, 0.0, 0.3);
+        vehicle.step(2.0, 0.1);
+        assert!((vehicle.applied_acceleration() - 0.5).abs() < 1e-9);
+        for _ in 0..50 {
+            vehicle.step(2.0, 0.1);
+        }
+        assert!((vehicle.applied_acceleration() - 2.0).abs() < 1e-3);
+
+        let mut direct = VehicleModel::new(1500.0, 0.0, 0.0, 0.0);
+        assert_eq!(direct.step(2.0, 0.5), 1.0);
+    }
+
+    #[test]
+    fn resistance_slows_a_coasting_vehicle() {
+        let mut vehicle = VehicleModel::new(1500.0, 0.4, 0.012, 0.0);
+        vehicle.step(3.0, 10.0);
+        let cruising = vehicle.velocity();
+        let coasted = vehicle.step(0.0, 0.1);
+        assert!(coasted < cruising);
+        assert_eq!(vehicle.step(1.0, 0.0), coasted, "no time, no change");
+    }
+
+    #[test]
+    fn braking_stops_without_reversing() {
+        let mut vehicle = VehicleModel::new(1500.0, 0.4, 0.012, 0.0);
+        vehicle.step(1.0, 1.0);
+        assert_eq!(vehicle.step(-8.0, 1.0), 0.0);
+        assert_eq!(vehicle.step(-8.0, 1.0), 0.0);
+    }
+}
@@ -14,19 +14,16 @@
 // limitations under the License.
 //
 
-use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use log::{info, debug, error, warn};
-use up_rust::{UUri, UListener, UMessage, UMessageBuilder, UTransport, UPayloadFormat};
-use up_transport_zenoh::UPTransportZenoh;
+use up_rust::{UUri, UUID, UListener, UMessage, UMessageBuilder, UTransport, UPayloadFormat};
 
 
-// New resource ID for control values
-pub const RESOURCE_CONTROL_VALUES: u16 = 0x8004;
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ControlValues {
     pub throttle: f64,
@@ -34,7 +31,34 @@ pub struct ControlValues {
     pub brake: f64,
 }
 
-use crate::pid_controller::PIDController;
+impl ControlValues {
+    /// Check that throttle/brake are in [0, 1] and steer is in [-1, 1]
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value, min) in [("throttle", self.throttle, 0.0), ("steer", self.steer, -1.0), ("brake", self.brake, 0.0)] {
+            if !value.is_finite() || value < min || value > 1.0 {
+                return Err(format!("{} out of range [{}, 1]: {}", name, min, value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy with every value clamped into its valid range (NaN is not repaired)
+    pub fn clamped(&self) -> Self {
+        Self {
+            throttle: self.throttle.clamp(0.0, 1.0),
+            steer: self.steer.clamp(-1.0, 1.0),
+            brake: self.brake.clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "test-fault-injection"))]
+use crate::fault_injection::{FaultInjector, VelocityFault};
+use crate::logging;
+use crate::pid_config::PidConfig;
+use crate::recording::{read_recording, MessageRecorder};
+use crate::resources;
+use crate::pid_controller::{within_speed_band, ControlRegime, ControllerSnapshot, PIDController, PIDResult, PidError, Preset};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct VelocityStatus {
@@ -51,9 +75,52 @@ struct TargetSpeed {
     speed: f64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct FrictionEstimate {
+    friction: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct EngageStatus {
     engaged: u8,
+    // Optional setpoint applied together with the engage state
+    #[serde(default)]
+    target: Option<f64>,
+}
+
+/// Operator command received on the command resource, e.g. `{"cmd": "reset"}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum ControlCommand {
+    /// Clear the PID state (errors, filters, suspension)
+    Reset,
+    /// Discard the recorded result series
+    ClearResults,
+    /// Write the recorded result series to `logs/`
+    StoreResults,
+    /// Suspend cruise control as if the driver had braked
+    Suspend,
+    /// Lift a suspension immediately
+    Resume,
+    /// Switch the tuning preset, e.g. `{"cmd": "apply_preset", "preset": "comfort"}`
+    ApplyPreset { preset: Preset },
+}
+
+/// Reply published on the command status resource
+#[derive(Debug, Serialize)]
+struct CommandStatus {
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    command: Option<ControlCommand>,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+/// Control regime published on the regime resource
+#[derive(Debug, Serialize)]
+struct RegimeStatus {
+    regime: ControlRegime,
+    timestamp: f64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -65,6 +132,55 @@ pub struct LidarMeasurement {
     pub len: u32,
 }
 
+/// CARLA-style lidar measurement carrying the points as a flat
+/// `raw_data: [x, y, z, intensity, x, y, z, intensity, ...]` array
+#[derive(Debug, Deserialize)]
+struct RawLidarMeasurement {
+    #[serde(default)]
+    channel_count: u32,
+    #[serde(default)]
+    horizontal_angle: f64,
+    raw_data: Vec<f64>,
+}
+
+impl LidarMeasurement {
+    /// Parse a lidar payload with a `detections` array, or else with a flat CARLA `raw_data` array
+    pub fn from_json(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        let detections_error = match serde_json::from_slice::<LidarMeasurement>(bytes) {
+            Ok(measurement) => return Ok(measurement),
+            Err(e) => e,
+        };
+
+        match serde_json::from_slice::<RawLidarMeasurement>(bytes) {
+            Ok(raw) if raw.raw_data.len() % 4 == 0 => Ok(raw.into()),
+            Ok(raw) => Err(serde::de::Error::custom(format!(
+                "raw_data length {} is not a multiple of 4 (x, y, z, intensity)", raw.raw_data.len()))),
+            // Report the error of the primary format
+            Err(_) => Err(detections_error),
+        }
+    }
+}
+
+impl From<RawLidarMeasurement> for LidarMeasurement {
+    fn from(raw: RawLidarMeasurement) -> Self {
+        let detections: Vec<LidarDetection> = raw.raw_data
+            .chunks_exact(4)
+            .map(|chunk| LidarDetection {
+                intensity: chunk[3],
+                point: PointCoords { x: chunk[0], y: chunk[1], z: chunk[2] },
+            })
+            .collect();
+
+        LidarMeasurement {
+            channel_count: raw.channel_count,
+            horizontal_angle: raw.horizontal_angle,
+            is_empty: detections.is_empty(),
+            len: detections.len() as u32,
+            detections,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct LidarDetection {
     pub intensity: f64,
@@ -78,102 +194,1300 @@ pub struct PointCoords {
     pub z: f64,
 }
 
+impl PointCoords {
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+}
+
+/// Which actuation messages `publish_acc` sends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ActuationOutput {
+    /// Scalar acceleration (m/s²) as text on the actuation resource (default)
+    Acceleration,
+    /// ControlValues-shaped JSON `{throttle, steer, brake}` on the pedal command resource
+    ControlValues,
+    /// Both of the above
+    Both,
+}
+
+impl ActuationOutput {
+    fn publishes_acceleration(&self) -> bool {
+        matches!(self, ActuationOutput::Acceleration | ActuationOutput::Both)
+    }
+
+    fn publishes_control_values(&self) -> bool {
+        matches!(self, ActuationOutput::ControlValues | ActuationOutput::Both)
+    }
+}
+
+/// How the command is encoded on the actuation resource, for actuators that do not take
+/// braking as a negative acceleration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum OutputConvention {
+    /// Signed acceleration (m/s²) as text, negative = braking (default), e.g. `-2.5`
+    SignedAcceleration,
+    /// Pedal values (0.0-1.0) as JSON on separate channels, e.g. `{"throttle": 0.0, "brake": 0.42}`
+    SplitThrottleBrake,
+    /// Acceleration and deceleration magnitudes (m/s², both non-negative) as JSON,
+    /// e.g. `{"acceleration": 0.0, "deceleration": 2.5}`
+    PositiveBrake,
+}
+
+/// Message shape on the actuation resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum WireSchema {
+    /// uProtocol payload selected by the `OutputConvention` (default)
+    UProtocolText,
+    /// ROS2 `ackermann_msgs/AckermannDriveStamped` as JSON, for a ROS2 bridge
+    Ackermann,
+}
+
+/// Front wheel angle at full steer input, as in the curved path model
+const ACKERMANN_MAX_STEER_ANGLE_DEG: f64 = 70.0;
+/// Frame of the Ackermann message header
+const ACKERMANN_FRAME_ID: &str = "base_link";
+/// Directory of the result files written by `store_results`
+const RESULTS_DIR: &str = "logs";
+
+/// One actuation command, the single place the actuation wire format is produced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActuationCommand {
+    pub speed: f64,        // m/s, target speed the command regulates to
+    pub acceleration: f64, // m/s², negative = braking
+    pub throttle: f64,     // 0.0 to 1.0
+    pub brake: f64,        // 0.0 to 1.0
+    pub steer: f64,        // Rate limited steer passthrough
+    pub regime: ControlRegime,
+    pub timestamp: f64,    // s, time of the velocity sample the command was computed for
+}
+
+impl ActuationCommand {
+    pub fn from_result(result: &PIDResult, timestamp: f64) -> Self {
+        Self {
+            speed: 0.0,
+            acceleration: result.acceleration,
+            throttle: result.throttle,
+            brake: result.brake,
+            steer: result.steer,
+            regime: result.regime,
+            timestamp,
+        }
+    }
+
+    /// Set the target speed reported by the Ackermann schema
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Actuation payload and its format. `convention` and `encoding` apply to the
+    /// `UProtocolText` schema only; `encoding` to `SignedAcceleration`, the other conventions
+    /// and the Ackermann schema are always JSON.
+    pub fn to_payload(&self, schema: WireSchema, convention: OutputConvention, encoding: PayloadEncoding) -> (String, UPayloadFormat) {
+        if schema == WireSchema::Ackermann {
+            return (self.to_ackermann().to_string(), UPayloadFormat::UPAYLOAD_FORMAT_JSON);
+        }
+        match convention {
+            OutputConvention::SignedAcceleration => match encoding {
+                PayloadEncoding::Text => (format!("{}", self.acceleration), UPayloadFormat::UPAYLOAD_FORMAT_TEXT),
+                PayloadEncoding::Json => {
+                    let payload = serde_json::json!({ "acceleration": self.acceleration });
+                    (payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+                }
+            },
+            OutputConvention::SplitThrottleBrake => {
+                let payload = serde_json::json!({ "throttle": self.throttle, "brake": self.brake });
+                (payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+            }
+            OutputConvention::PositiveBrake => {
+                let payload = serde_json::json!({
+                    "acceleration": self.acceleration.max(0.0),
+                    "deceleration": (-self.acceleration).max(0.0),
+                });
+                (payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+            }
+        }
+    }
+
+    /// `AckermannDriveStamped` fields: the target speed, the commanded acceleration and the
+    /// steer input as front wheel angle (rad, positive = right like the steer input), stamped
+    /// with the time of the velocity sample
+    fn to_ackermann(&self) -> serde_json::Value {
+        let timestamp = self.timestamp.max(0.0);
+        serde_json::json!({
+            "header": {
+                "stamp": {
+                    "sec": timestamp.trunc() as i64,
+                    "nanosec": (timestamp.fract() * 1e9) as u32,
+                },
+                "frame_id": ACKERMANN_FRAME_ID,
+            },
+            "drive": {
+                "steering_angle": self.steer * ACKERMANN_MAX_STEER_ANGLE_DEG.to_radians(),
+                "steering_angle_velocity": 0.0,
+                "speed": self.speed,
+                "acceleration": self.acceleration,
+                "jerk": 0.0,
+            },
+        })
+    }
+}
+
+/// How the driver setpoint and the ADAS speed limit are combined into the effective target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SpeedArbitration {
+    /// Lower of the two targets (default)
+    Min,
+    /// Higher of the two targets
+    Max,
+    /// Driver setpoint whenever one is set (non-zero), speed limit otherwise
+    DriverPriority,
+}
+
+impl SpeedArbitration {
+    /// Effective target; the driver setpoint alone is used until a speed limit has been received
+    pub fn resolve(&self, driver_target: f64, speed_limit: Option<f64>) -> f64 {
+        let Some(limit) = speed_limit else {
+            return driver_target;
+        };
+        match self {
+            SpeedArbitration::Min => driver_target.min(limit),
+            SpeedArbitration::Max => driver_target.max(limit),
+            SpeedArbitration::DriverPriority => if driver_target != 0.0 { driver_target } else { limit },
+        }
+    }
+}
+
+/// Where `current_time` of the control tick comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ClockSource {
+    /// Clock stream of the simulator/vehicle (default)
+    External,
+    /// Unix time in seconds taken at each velocity message, for producers without a clock stream;
+    /// clock messages are ignored
+    WallClock,
+}
+
+/// Where the control tick triggered by a velocity message runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ControlLoop {
+    /// In the velocity listener, which returns after the actuation was sent (default)
+    Inline,
+    /// On a dedicated control-loop task signalled by the velocity listener, which returns right
+    /// after updating the velocity; messages arriving during a tick are coalesced into one tick
+    /// with the newest values
+    Async,
+}
+
+/// What a target speed of 0 means while cruise control is engaged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ZeroTargetPolicy {
+    /// Regulate to 0 like any other target, braking the vehicle to a stop (default)
+    BrakeToStop,
+    /// Disengage cruise control, as if the driver had switched it off
+    Disengage,
+    /// Treat 0 as "no target" and keep the previous target
+    Ignore,
+}
+
+/// Runtime options of the uProtocol handler
+#[derive(Debug, Clone, Serialize)]
+pub struct HandlerConfig {
+    pub actuation_output: ActuationOutput,
+    /// Encoding of the actuation resource payload
+    pub output_convention: OutputConvention,
+    pub acceleration_encoding: PayloadEncoding,
+    /// Message shape on the actuation resource; `Ackermann` replaces the output convention
+    pub wire_schema: WireSchema,
+    /// Minimum time between actuation messages; PID state still updates every tick (zero = unlimited)
+    pub min_publish_interval: Duration,
+    /// Clamp out-of-range incoming ControlValues instead of rejecting them
+    pub clamp_control_values: bool,
+    /// Give up on a publish after this long so a congested transport cannot stall the control loop (zero = wait forever)
+    pub send_timeout: Duration,
+    pub speed_arbitration: SpeedArbitration,
+    pub zero_target_policy: ZeroTargetPolicy,
+    /// Move the stored target speed towards a new driver target by at most this many m/s per second (0.0 = apply immediately)
+    pub target_slew_rate: f64,
+    pub clock_source: ClockSource,
+    pub control_loop: ControlLoop,
+    /// Keep results of the last N runs as `logs/<series>-<unix_ms>.log` (0 = overwrite `logs/<series>.log`)
+    pub keep_log_runs: usize,
+    /// Also write the results every interval while running, so a crash loses at most one interval (zero = only on shutdown)
+    pub results_interval: Duration,
+    /// Run the control tick on every Kth velocity message only; the others just update the state (1 = every message)
+    pub decimation_factor: u32,
+    /// Accept engage values above 1 (any nonzero value engages); otherwise they are rejected
+    pub multi_state_engage: bool,
+    /// Warn when a tick takes longer than this from velocity receipt to actuation (zero = no warning)
+    pub tick_budget: Duration,
+    /// Publish zero acceleration for the first N ticks after activation while the PID settles
+    pub warmup_ticks: u32,
+    /// Republish the last acceleration when no actuation message was sent for this long (zero = off)
+    pub heartbeat_interval: Duration,
+    /// Band around the desired velocity (m/s) reported as "at speed" to the operator (0 = off)
+    pub at_speed_band: f64,
+    /// Time the velocity must stay inside/outside `at_speed_band` before the status flips
+    pub at_speed_dwell: Duration,
+    /// Enter the fail-safe after this many consecutive ticks with a failed actuation send (0 = off)
+    pub fail_safe_after: u32,
+    /// Deceleration (m/s², negative) commanded on a best-effort basis when the fail-safe engages
+    pub fail_safe_acceleration: f64,
+    /// Publish the control regime on change for HMI display
+    pub publish_regime: bool,
+    /// Time a new regime must persist before it is published; emergency braking is published at once
+    pub regime_debounce: Duration,
+    /// Refuse to engage until `self_test` passes
+    pub require_self_test: bool,
+    /// Age after which the last valid clock/velocity message no longer counts for the self-test
+    pub self_test_timeout: Duration,
+}
+
+impl Default for HandlerConfig {
+    fn default() -> Self {
+        Self {
+            actuation_output: ActuationOutput::Acceleration,
+            output_convention: OutputConvention::SignedAcceleration,
+            acceleration_encoding: PayloadEncoding::Text,
+            wire_schema: WireSchema::UProtocolText,
+            min_publish_interval: Duration::ZERO,
+            clamp_control_values: false,
+            send_timeout: Duration::from_millis(100),
+            speed_arbitration: SpeedArbitration::Min,
+            zero_target_policy: ZeroTargetPolicy::BrakeToStop,
+            target_slew_rate: 0.0,
+            clock_source: ClockSource::External,
+            control_loop: ControlLoop::Inline,
+            keep_log_runs: 0,
+            results_interval: Duration::ZERO,
+            decimation_factor: 1,
+            multi_state_engage: false,
+            tick_budget: Duration::ZERO,
+            warmup_ticks: 0,
+            heartbeat_interval: Duration::ZERO,
+            at_speed_band: 0.0,
+            at_speed_dwell: Duration::from_millis(500),
+            fail_safe_after: 0,
+            fail_safe_acceleration: -2.0,
+            publish_regime: false,
+            regime_debounce: Duration::from_millis(300),
+            require_self_test: false,
+            self_test_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Context of a run, stored with its result series so runs can be compared later
+#[derive(Debug, Clone, Serialize)]
+pub struct RunMetadata {
+    pub started_at_ms: u64,         // Unix time of the controller start, also the suffix of rotated result files
+    pub version: String,            // Crate version
+    pub git_commit: Option<String>, // GIT_COMMIT at build time, when set
+    pub controller: ControllerSnapshot,
+    pub handler: HandlerConfig,
+}
+
+/// Contents of `logs/pid_results.json`: the run metadata and every result series
+#[derive(Debug, Serialize)]
+pub struct RunResults<'a> {
+    pub metadata: RunMetadata,
+    pub series: &'a HashMap<String, Vec<f64>>,
+}
+
+/// Summary of how well the controller tracked the desired velocity
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TrackingMetrics {
+    pub data_points: usize,
+    pub min_error: f64,
+    pub max_error: f64,
+    pub avg_error: f64,
+    pub rms_error: f64,
+    pub min_acceleration: f64,
+    pub max_acceleration: f64,
+    pub avg_acceleration: f64,
+    pub overshoot: f64,      // m/s, largest speed above the setpoint (0.0 = none)
+    pub settling_time: f64,  // s, until the error stays within SETTLING_BAND (0.0 without time series)
+}
+
+/// Speed error band (m/s) the velocity has to stay within to count as settled
+pub const SETTLING_BAND: f64 = 0.5;
+
+/// Weights of the metric changes in the overall verdict of `TrackingMetrics::compare`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ComparisonWeights {
+    pub rms_error: f64,     // per m/s
+    pub overshoot: f64,     // per m/s
+    pub settling_time: f64, // per second
+}
+
+impl Default for ComparisonWeights {
+    fn default() -> Self {
+        Self {
+            rms_error: 1.0,
+            overshoot: 1.0,
+            settling_time: 0.1,
+        }
+    }
+}
+
+/// Change from a baseline run to a candidate run; negative deltas are improvements
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TuningComparison {
+    pub delta_rms_error: f64,
+    pub delta_overshoot: f64,
+    pub delta_settling_time: f64,
+    pub score: f64,     // Weighted sum of the deltas
+    pub improved: bool, // Weighted score below zero
+}
+
+impl TrackingMetrics {
+    /// Build metrics from the results series (desired_velocity, current_velocity, acceleration)
+    pub fn from_results(results: &HashMap<String, Vec<f64>>) -> Self {
+        let (Some(desired), Some(current), Some(acceleration)) = (
+            results.get("desired_velocity"),
+            results.get("current_velocity"),
+            results.get("acceleration"),
+        ) else {
+            return Self::default();
+        };
+
+        let data_points = desired.len().min(current.len()).min(acceleration.len());
+        if data_points == 0 {
+            return Self::default();
+        }
+
+        let mut min_error = f64::MAX;
+        let mut max_error = f64::MIN;
+        let mut sum_error = 0.0;
+        let mut sum_squared_error = 0.0;
+
+        for i in 0..data_points {
+            let error = desired[i] - current[i];
+            min_error = min_error.min(error);
+            max_error = max_error.max(error);
+            sum_error += error;
+            sum_squared_error += error * error;
+        }
+
+        let acc_values = &acceleration[..data_points];
+
+        // Settled after the last sample outside the band
+        let settling_time = match results.get("current_time") {
+            Some(time) if time.len() >= data_points => (0..data_points)
+                .rev()
+                .find(|&i| (desired[i] - current[i]).abs() > SETTLING_BAND)
+                .map_or(0.0, |i| time[(i + 1).min(data_points - 1)] - time[0]),
+            _ => 0.0,
+        };
+
+        Self {
+            data_points,
+            min_error,
+            max_error,
+            avg_error: sum_error / data_points as f64,
+            rms_error: (sum_squared_error / data_points as f64).sqrt(),
+            min_acceleration: acc_values.iter().fold(f64::INFINITY, |a, &b| a.min(b)),
+            max_acceleration: acc_values.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b)),
+            avg_acceleration: acc_values.iter().sum::<f64>() / data_points as f64,
+            overshoot: (-min_error).max(0.0),
+            settling_time,
+        }
+    }
+
+    /// Compare a candidate run against this baseline run, e.g. after changing the gains
+    #[allow(dead_code)]
+    pub fn compare(&self, candidate: &TrackingMetrics, weights: &ComparisonWeights) -> TuningComparison {
+        let delta_rms_error = candidate.rms_error - self.rms_error;
+        let delta_overshoot = candidate.overshoot - self.overshoot;
+        let delta_settling_time = candidate.settling_time - self.settling_time;
+        let score = weights.rms_error * delta_rms_error
+            + weights.overshoot * delta_overshoot
+            + weights.settling_time * delta_settling_time;
+
+        TuningComparison {
+            delta_rms_error,
+            delta_overshoot,
+            delta_settling_time,
+            score,
+            improved: score < 0.0,
+        }
+    }
+}
+
+/// Number of payloads per stream that could not be decoded (or were rejected as invalid)
+#[derive(Debug, Default)]
+pub struct DecodeErrorCounters {
+    clock: AtomicU64,
+    velocity: AtomicU64,
+    target_speed: AtomicU64,
+    speed_limit: AtomicU64,
+    engage: AtomicU64,
+    lidar: AtomicU64,
+    friction: AtomicU64,
+    control_values: AtomicU64,
+    command: AtomicU64,
+}
+
+impl DecodeErrorCounters {
+    fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Snapshot of the counters keyed by stream name
+    pub fn snapshot(&self) -> HashMap<String, u64> {
+        let mut counts = HashMap::new();
+        counts.insert("clock".to_string(), self.clock.load(Ordering::Relaxed));
+        counts.insert("velocity".to_string(), self.velocity.load(Ordering::Relaxed));
+        counts.insert("target_speed".to_string(), self.target_speed.load(Ordering::Relaxed));
+        counts.insert("speed_limit".to_string(), self.speed_limit.load(Ordering::Relaxed));
+        counts.insert("engage".to_string(), self.engage.load(Ordering::Relaxed));
+        counts.insert("lidar".to_string(), self.lidar.load(Ordering::Relaxed));
+        counts.insert("friction".to_string(), self.friction.load(Ordering::Relaxed));
+        counts.insert("control_values".to_string(), self.control_values.load(Ordering::Relaxed));
+        counts.insert("command".to_string(), self.command.load(Ordering::Relaxed));
+        counts
+    }
+}
+
+/// Consistent view of the handler and controller outputs, as returned by `snapshot`
+#[derive(Debug, Clone, Serialize)]
+pub struct Telemetry {
+    pub current_velocity: f64,
+    pub desired_velocity: f64,
+    pub speed_limit: Option<f64>,
+    pub current_time: f64,
+    pub engaged: bool,
+    pub pid_active: bool,
+    pub acceleration: Option<f64>,              // Last computed command, None before the first tick
+    pub closest_obstacle_distance: Option<f64>, // In-path obstacle of the last tick, None = path clear or no lidar
+    pub throttle: f64,
+    pub steer: f64,
+    pub brake: f64,
+    pub regime: Option<ControlRegime>,          // Regime of the last tick, None before the first tick
+    pub degraded: bool,                         // Fail-safe engaged after actuation send failures
+    pub saturated: bool,                        // An acceleration limit clipped the PID output of the last tick
+    pub requested_acceleration: Option<f64>,    // PID output of the last tick before the limits
+}
+
+/// Failed checks of `self_test`, empty lists/None = check passed
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SelfTestReport {
+    pub stale_streams: Vec<String>,        // Required streams without a valid message within the timeout
+    pub unsubscribed_streams: Vec<String>, // Streams whose listener is not registered on the transport
+    pub config_error: Option<String>,      // PID config validation error, or missing config
+}
+
+impl SelfTestReport {
+    pub fn passed(&self) -> bool {
+        self.stale_streams.is_empty() && self.unsubscribed_streams.is_empty() && self.config_error.is_none()
+    }
+}
+
+impl std::fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut failures = Vec::new();
+        if !self.stale_streams.is_empty() {
+            failures.push(format!("no valid message on {}", self.stale_streams.join(", ")));
+        }
+        if !self.unsubscribed_streams.is_empty() {
+            failures.push(format!("not subscribed to {}", self.unsubscribed_streams.join(", ")));
+        }
+        if let Some(config_error) = &self.config_error {
+            failures.push(format!("invalid config: {}", config_error));
+        }
+        write!(f, "{}", failures.join("; "))
+    }
+}
+
+/// Receive activity of one subscribed stream, as returned by `subscription_status`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SubscriptionInfo {
+    pub stream: String,
+    pub uri: String,
+    pub subscribed: bool,              // Listener registration succeeded
+    pub received: u64,                 // Messages received since startup
+    pub last_received_ms: Option<u64>, // Unix time of the last message, None = silent so far
+}
+
+/// Receive statistics of every input stream, updated by `TrackedListener`
+#[derive(Debug)]
+struct SubscriptionTracker {
+    streams: Mutex<Vec<SubscriptionInfo>>,
+}
+
+impl SubscriptionTracker {
+    fn new(streams: &[(&str, &UUri)]) -> Self {
+        let streams = streams.iter()
+            .map(|(stream, uri)| SubscriptionInfo {
+                stream: stream.to_string(),
+                uri: uri.to_uri(false),
+                subscribed: false,
+                received: 0,
+                last_received_ms: None,
+            })
+            .collect();
+        Self { streams: Mutex::new(streams) }
+    }
+
+    fn update(&self, stream: &str, update: impl FnOnce(&mut SubscriptionInfo)) {
+        if let Some(info) = self.streams.lock().unwrap().iter_mut().find(|info| info.stream == stream) {
+            update(info);
+        }
+    }
+
+    fn mark_subscribed(&self, stream: &str) {
+        self.update(stream, |info| info.subscribed = true);
+    }
+
+    fn record(&self, stream: &str) {
+        let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        self.update(stream, |info| {
+            info.received += 1;
+            info.last_received_ms = Some(now_ms);
+        });
+    }
+
+    fn snapshot(&self) -> Vec<SubscriptionInfo> {
+        self.streams.lock().unwrap().clone()
+    }
+}
+
+/// Counts (and records, see `record_to`) every message of a stream before handing it to the wrapped listener
+struct TrackedListener<L> {
+    stream: &'static str,
+    tracker: Arc<SubscriptionTracker>,
+    recorder: Arc<MessageRecorder>,
+    inner: L,
+}
+
+#[async_trait::async_trait]
+impl<L: UListener> UListener for TrackedListener<L> {
+    async fn on_receive(&self, message: UMessage) {
+        self.tracker.record(self.stream);
+        self.recorder.record(self.stream, &message);
+        self.inner.on_receive(message).await;
+    }
+}
+
+/// Monotonic guard on the creation time carried in the uProtocol message ID.
+/// Messages created earlier than the newest one already processed are rejected and counted;
+/// messages without a uProtocol UUID timestamp are always accepted.
+#[derive(Debug, Default)]
+pub struct FreshnessGuard {
+    newest_ms: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl FreshnessGuard {
+    /// Record the message creation time, returning false when the message is out of order
+    fn accept(&self, message: &UMessage) -> bool {
+        let Some(created_ms) = message.id().and_then(UUID::get_time) else {
+            return true;
+        };
+        let newest_ms = self.newest_ms.fetch_max(created_ms, Ordering::Relaxed);
+        if created_ms < newest_ms {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            warn!(target: logging::MESSAGES, "Ignoring out-of-order message created {} ms before the newest processed one",
+                  newest_ms - created_ms);
+            return false;
+        }
+        true
+    }
+
+    /// Number of messages rejected as out of order
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
+
+/// Number of recent ticks the latency percentile is computed over
+const LATENCY_WINDOW: usize = 1000;
+
+/// Value of the `closest_obstacle_distance` series for ticks without lidar data or in-path obstacle
+pub const NO_OBSTACLE_DISTANCE: f64 = -1.0;
+
+/// Wakes the control-loop task of `ControlLoop::Async` for the next tick
+#[derive(Debug, Default)]
+struct ControlTrigger {
+    notify: tokio::sync::Notify,
+    pending: Mutex<Option<Instant>>, // Receipt of the oldest velocity not yet processed by a tick
+    running: AtomicBool,             // Whether the control-loop task has been started
+}
+
+impl ControlTrigger {
+    fn trigger(&self, received: Instant) {
+        self.pending.lock().unwrap().get_or_insert(received);
+        self.notify.notify_one();
+    }
+
+    async fn next(&self) -> Instant {
+        loop {
+            self.notify.notified().await;
+            if let Some(received) = self.pending.lock().unwrap().take() {
+                return received;
+            }
+        }
+    }
+}
+
+/// Latency of the velocity -> actuation path, from velocity receipt to the end of `publish_acc`
+#[derive(Debug, Default)]
+pub struct TickLatency {
+    inner: Mutex<TickLatencyInner>,
+}
+
+#[derive(Debug, Default)]
+struct TickLatencyInner {
+    count: u64,
+    min: Duration,
+    max: Duration,
+    total: Duration,
+    recent: VecDeque<Duration>, // Last LATENCY_WINDOW ticks, for the percentile
+}
+
+/// Latency statistics in milliseconds; min/max/avg over all ticks, p99 over the last 1000
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencySummary {
+    pub ticks: u64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub avg_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl TickLatency {
+    fn record(&self, latency: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.min = if inner.count == 0 { latency } else { inner.min.min(latency) };
+        inner.max = inner.max.max(latency);
+        inner.total += latency;
+        inner.count += 1;
+        if inner.recent.len() == LATENCY_WINDOW {
+            inner.recent.pop_front();
+        }
+        inner.recent.push_back(latency);
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        let inner = self.inner.lock().unwrap();
+        if inner.count == 0 {
+            return LatencySummary::default();
+        }
+
+        let mut recent: Vec<Duration> = inner.recent.iter().copied().collect();
+        recent.sort_unstable();
+        let p99_index = (recent.len() * 99).div_ceil(100).saturating_sub(1);
+        let to_ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+        LatencySummary {
+            ticks: inner.count,
+            min_ms: to_ms(inner.min),
+            max_ms: to_ms(inner.max),
+            avg_ms: to_ms(inner.total) / inner.count as f64,
+            p99_ms: to_ms(recent[p99_index]),
+        }
+    }
+}
+
+/// Cruise control engaged state (0 = disengaged) with change notifications.
+/// Every transition between engaged and disengaged is sent to all subscribed channels.
+#[derive(Debug, Default)]
+pub struct EngagedState {
+    value: Mutex<u8>,
+    subscribers: Mutex<Vec<mpsc::Sender<bool>>>,
+}
+
+impl EngagedState {
+    pub fn get(&self) -> u8 {
+        *self.value.lock().unwrap()
+    }
+
+    /// Store a new engaged value, notify subscribers on a transition and return the previous value
+    fn set(&self, engaged: u8) -> u8 {
+        let previous = std::mem::replace(&mut *self.value.lock().unwrap(), engaged);
+        if (previous != 0) != (engaged != 0) {
+            // Receivers that were dropped are unsubscribed
+            self.subscribers.lock().unwrap().retain(|sender| sender.send(engaged != 0).is_ok());
+        }
+        previous
+    }
+
+    fn subscribe(&self) -> mpsc::Receiver<bool> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+}
+
+/// Inputs and outputs of the control tick that are read together, kept behind a single lock
+/// so a tick works on one consistent snapshot. Never held while the controller is locked.
+#[derive(Debug, Clone, Default)]
+pub struct ControlState {
+    pub current_velocity: f64,
+    pub desired_velocity: f64,
+    pub speed_limit: Option<f64>,
+    pub current_time: f64,
+    pub previous_time: f64,
+    pub pid_active: bool,
+    pub latest_lidar_data: Option<Arc<LidarMeasurement>>,
+    pub throttle: f64,
+    pub steer: f64,
+    pub brake: f64,
+    pub at_target: bool,
+    pub last_acceleration: Option<f64>, // Command of the last tick
+    pub last_obstacle_distance: Option<f64>, // Closest in-path obstacle of the last tick
+    pub last_regime: Option<ControlRegime>, // Regime of the last tick (published or not)
+    pub last_saturated: bool, // An acceleration limit clipped the PID output of the last tick
+    pub last_requested_acceleration: Option<f64>, // PID output of the last tick before the limits
+    pub at_speed: bool, // Debounced "at speed" status last published
+    pub at_speed_pending: Option<f64>, // Time the velocity first disagreed with `at_speed`
+    pub regime: Option<ControlRegime>, // Control regime last published
+    pub regime_pending: Option<(ControlRegime, f64)>, // Differing regime and the time it was first seen
+    pub actuation_send_failures: u32, // Consecutive ticks whose actuation send failed
+    pub degraded: bool, // Fail-safe engaged, cleared by the next successful actuation send
+    pub last_actuation_publish: Option<Instant>,
+    pub last_actuation: Option<(String, UPayloadFormat)>, // Last acceleration payload, repeated by the heartbeat
+    pub velocity_samples: u64,
+    pub last_valid_clock: Option<Instant>,    // Receipt of the last accepted clock message
+    pub last_valid_velocity: Option<Instant>, // Receipt of the last accepted velocity message
+    pub active_ticks: u64, // Control ticks since the last activation
+    pub zero_target_disengage: bool, // A zero target arrived under ZeroTargetPolicy::Disengage
+    pub requested_velocity: Option<f64>, // Driver target `desired_velocity` is still slewing towards
+}
+
+/// What a control tick changed in the `ControlState`, decided under one lock and published after it
+struct TickUpdate {
+    result: PIDResult,
+    reengage: bool,
+    at_target_changed: bool,
+    at_speed_changed: Option<bool>,
+    regime_changed: Option<ControlRegime>,
+    publish_due: bool,
+    actuation_payload: Option<(String, UPayloadFormat)>,
+    delta_time: f64,
+}
+
+/// Wire formats accepted on the text-or-JSON input streams, also selectable for the published acceleration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PayloadEncoding {
+    /// Bare value, e.g. `-2.5`
+    Text,
+    /// JSON object, e.g. `{"acceleration": -2.5}`
+    Json,
+}
+
+/// Format a producer actually sends on one input stream, learned from the first decodable message.
+/// The learned format is tried first; the other one still works as a fallback and is learned instead
+/// when the producer switches.
+#[derive(Debug)]
+struct LearnedFormat {
+    stream: &'static str,
+    learned: Mutex<Option<PayloadEncoding>>,
+}
+
+impl LearnedFormat {
+    fn new(stream: &'static str) -> Self {
+        Self { stream, learned: Mutex::new(None) }
+    }
+
+    /// Decode a payload with the text or JSON decoder, preferring the learned format
+    fn decode<T>(
+        &self,
+        bytes: &[u8],
+        text: impl Fn(&str) -> Option<T>,
+        json: impl Fn(&[u8]) -> Option<T>,
+    ) -> Option<T> {
+        let preferred = *self.learned.lock().unwrap();
+        let order = match preferred {
+            Some(PayloadEncoding::Json) => [PayloadEncoding::Json, PayloadEncoding::Text],
+            _ => [PayloadEncoding::Text, PayloadEncoding::Json],
+        };
+
+        for encoding in order {
+            let value = match encoding {
+                PayloadEncoding::Text => std::str::from_utf8(bytes).ok().and_then(|payload_str| text(payload_str.trim())),
+                PayloadEncoding::Json => json(bytes),
+            };
+            if let Some(value) = value {
+                if preferred != Some(encoding) {
+                    match preferred {
+                        None => info!(target: logging::MESSAGES, "FORMAT: {} stream uses {:?} payloads", self.stream, encoding),
+                        Some(previous) => warn!(target: logging::MESSAGES, "FORMAT: {} stream switched from {:?} to {:?} payloads",
+                                                self.stream, previous, encoding),
+                    }
+                    *self.learned.lock().unwrap() = Some(encoding);
+                }
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
 pub struct UProtocolHandler {
     controller: Arc<Mutex<PIDController>>,
-    transport: Arc<UPTransportZenoh>,
+    // Any uProtocol transport (Zenoh in production, in-memory for tests)
+    transport: Arc<dyn UTransport>,
     
     // uProtocol URIs
     velocity_uri: UUri,
     clock_uri: UUri,
     engage_uri: UUri,
     target_speed_uri: UUri,
+    speed_limit_uri: UUri,
     actuation_uri: UUri,
     lidar_uri: UUri,
+    friction_uri: UUri,
     control_values_uri: UUri,
+    at_target_uri: UUri,
+    at_speed_uri: UUri,
+    regime_uri: UUri,
+    pedal_command_uri: UUri,
+    command_uri: UUri,
+    command_status_uri: UUri,
+    
+    // Handler options
+    config: Arc<Mutex<HandlerConfig>>,
     
     // State variables
-    current_velocity: Arc<Mutex<f64>>,
-    desired_velocity: Arc<Mutex<f64>>,
-    current_time: Arc<Mutex<f64>>,
-    previous_time: Arc<Mutex<f64>>,
-    is_engaged: Arc<Mutex<u8>>,
-    pid_active: Arc<Mutex<bool>>,
-    latest_lidar_data: Arc<Mutex<Option<LidarMeasurement>>>,
-    throttle: Arc<Mutex<f64>>,
-    steer: Arc<Mutex<f64>>,
-    brake: Arc<Mutex<f64>>,
+    state: Arc<Mutex<ControlState>>,
+    is_engaged: Arc<EngagedState>,
+    decode_errors: Arc<DecodeErrorCounters>,
+    dropped_sends: Arc<AtomicU64>,
+    clock_freshness: Arc<FreshnessGuard>,
+    velocity_freshness: Arc<FreshnessGuard>,
+    tick_latency: Arc<TickLatency>,
+    control_trigger: Arc<ControlTrigger>,
+    #[cfg(all(test, feature = "test-fault-injection"))]
+    velocity_faults: Arc<FaultInjector>,
+    subscriptions: Arc<SubscriptionTracker>,
+    recorder: Arc<MessageRecorder>,
+    pid_config: Arc<Mutex<Option<PidConfig>>>, // Config the controller was built from, checked by the self-test
     
     // Results storage
     results: Arc<Mutex<HashMap<String, Vec<f64>>>>,
+    run_id: u128, // Suffix of the result files when log rotation is enabled
 }
 
 impl UProtocolHandler {
-    pub fn new(
+    pub fn new<T: UTransport + 'static>(
         controller: PIDController,
-        transport: UPTransportZenoh,
+        transport: T,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_transport(controller, Arc::new(transport))
+    }
+
+    /// Create a handler on top of an already shared transport, e.g. a mock
+    /// implementation of `UTransport` that records sent messages.
+    pub fn with_transport(
+        controller: PIDController,
+        transport: Arc<dyn UTransport>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut results = HashMap::new();
         results.insert("desired_velocity".to_string(), Vec::new());
         results.insert("current_velocity".to_string(), Vec::new());
+        results.insert("filtered_velocity".to_string(), Vec::new());
         results.insert("current_time".to_string(), Vec::new());
         results.insert("acceleration".to_string(), Vec::new());
+        results.insert("closest_obstacle_distance".to_string(), Vec::new());
+        results.insert("requested_acceleration".to_string(), Vec::new());
 
         // Create URIs for different services
-        let velocity_uri = UUri::try_from_parts("EGOVehicle", 0, 2, 0x8001)?;
-        let clock_uri = UUri::try_from_parts("EGOVehicle", 0, 2, 0x8002)?;
-        let engage_uri = UUri::try_from_parts("AAOS", 0, 2, 0x8002)?;
-        let target_speed_uri = UUri::try_from_parts("AAOS", 0, 2, 0x8001)?;
-        let actuation_uri = UUri::try_from_parts("CruiseControl", 0, 2, 0x8001)?;
-        let lidar_uri = UUri::try_from_parts("EGOVehicle", 0, 2, 0x8003)?; // Use 0x8003 instead of 8003
-        let control_values_uri = UUri::try_from_parts("CruiseControl", 0, 2, RESOURCE_CONTROL_VALUES)?;
+        let velocity_uri = resources::uri(resources::VEHICLE, resources::VELOCITY)?;
+        let clock_uri = resources::uri(resources::VEHICLE, resources::CLOCK)?;
+        let engage_uri = resources::uri(resources::HMI, resources::ENGAGE)?;
+        let target_speed_uri = resources::uri(resources::HMI, resources::TARGET_SPEED)?;
+        let speed_limit_uri = resources::uri(resources::HMI, resources::SPEED_LIMIT)?;
+        let actuation_uri = resources::uri(resources::CRUISE_CONTROL, resources::ACTUATION)?;
+        let lidar_uri = resources::uri(resources::VEHICLE, resources::LIDAR)?;
+        let friction_uri = resources::uri(resources::VEHICLE, resources::FRICTION)?;
+        let control_values_uri = resources::uri(resources::CRUISE_CONTROL, resources::CONTROL_VALUES)?;
+        let at_target_uri = resources::uri(resources::CRUISE_CONTROL, resources::AT_TARGET_STATUS)?;
+        let at_speed_uri = resources::uri(resources::CRUISE_CONTROL, resources::AT_SPEED_STATUS)?;
+        let regime_uri = resources::uri(resources::CRUISE_CONTROL, resources::REGIME)?;
+        let pedal_command_uri = resources::uri(resources::CRUISE_CONTROL, resources::PEDAL_COMMAND)?;
+        let command_uri = resources::uri(resources::CRUISE_CONTROL, resources::COMMAND)?;
+        let command_status_uri = resources::uri(resources::CRUISE_CONTROL, resources::COMMAND_STATUS)?;
+        let subscriptions = SubscriptionTracker::new(&[
+            ("clock", &clock_uri),
+            ("velocity", &velocity_uri),
+            ("target_speed", &target_speed_uri),
+            ("speed_limit", &speed_limit_uri),
+            ("engage", &engage_uri),
+            ("lidar", &lidar_uri),
+            ("friction", &friction_uri),
+            ("control_values", &control_values_uri),
+            ("command", &command_uri),
+        ]);
+
+        let run_id = controller.wall_time().as_millis();
 
         Ok(UProtocolHandler {
             controller: Arc::new(Mutex::new(controller)),
-            transport: Arc::new(transport),
+            transport,
             velocity_uri,
             clock_uri,
             engage_uri,
             target_speed_uri,
+            speed_limit_uri,
             actuation_uri,
             lidar_uri,
+            friction_uri,
             control_values_uri,
-            current_velocity: Arc::new(Mutex::new(0.0)),
-            desired_velocity: Arc::new(Mutex::new(0.0)),
-            current_time: Arc::new(Mutex::new(0.0)),
-            previous_time: Arc::new(Mutex::new(0.0)),
-            is_engaged: Arc::new(Mutex::new(0)),
-            pid_active: Arc::new(Mutex::new(false)),
-            latest_lidar_data: Arc::new(Mutex::new(None)),
-            throttle: Arc::new(Mutex::new(0.0)),
-            steer: Arc::new(Mutex::new(0.0)),
-            brake: Arc::new(Mutex::new(0.0)),
+            at_target_uri,
+            at_speed_uri,
+            regime_uri,
+            pedal_command_uri,
+            command_uri,
+            command_status_uri,
+            config: Arc::new(Mutex::new(HandlerConfig::default())),
+            state: Arc::new(Mutex::new(ControlState::default())),
+            is_engaged: Arc::new(EngagedState::default()),
+            decode_errors: Arc::new(DecodeErrorCounters::default()),
+            dropped_sends: Arc::new(AtomicU64::new(0)),
+            clock_freshness: Arc::new(FreshnessGuard::default()),
+            velocity_freshness: Arc::new(FreshnessGuard::default()),
+            tick_latency: Arc::new(TickLatency::default()),
+            control_trigger: Arc::new(ControlTrigger::default()),
+            #[cfg(all(test, feature = "test-fault-injection"))]
+            velocity_faults: Arc::new(FaultInjector::default()),
+            subscriptions: Arc::new(subscriptions),
+            recorder: Arc::new(MessageRecorder::default()),
+            pid_config: Arc::new(Mutex::new(None)),
             results: Arc::new(Mutex::new(results)),
+            run_id,
         })
     }
 
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Starting UProtocolHandler subscribers...");
+        info!(target: logging::MESSAGES, "Starting UProtocolHandler subscribers...");
 
         // Register listeners for each subscription
         self.setup_clock_subscriber().await?;
         self.setup_velocity_subscriber().await?;
         self.setup_target_subscriber().await?;
+        self.setup_speed_limit_subscriber().await?;
         self.setup_engage_subscriber().await?;
         self.setup_lidar_subscriber().await?;
+        self.setup_friction_subscriber().await?;
         self.setup_control_values_subscriber().await?;
+        self.setup_command_subscriber().await?;
+
+        self.store_effective_config();
+        self.start_periodic_results(RESULTS_DIR);
+        self.start_actuation_heartbeat();
+        self.start_control_loop();
 
         Ok(())
     }
 
-    // Getter method to access the latest lidar data
-    pub fn get_latest_lidar_data(&self) -> Option<LidarMeasurement> {
-        let lidar_data = self.latest_lidar_data.lock().unwrap();
-        lidar_data.clone()
+    /// Run the control ticks on a dedicated task when `ControlLoop::Async` is configured (no-op otherwise)
+    fn start_control_loop(&self) {
+        if self.config.lock().unwrap().control_loop != ControlLoop::Async {
+            return;
+        }
+
+        let listener = self.velocity_listener();
+        let trigger = Arc::clone(&self.control_trigger);
+        trigger.running.store(true, Ordering::Relaxed);
+        tokio::spawn(async move {
+            loop {
+                let received = trigger.next().await;
+                listener.run_tick(received).await;
+            }
+        });
+        info!(target: logging::CONTROL, "Control loop runs on a dedicated task");
     }
-    
-    // Helper method to get obstacle information from lidar data
-    pub fn get_closest_obstacle(&self) -> Option<f64> {
-        if let Some(ref measurement) = *self.latest_lidar_data.lock().unwrap() {
+
+    /// Persist the results to `dir` every `results_interval` on a background task (no-op when the interval is zero)
+    fn start_periodic_results(&self, dir: &str) {
+        let interval = self.config.lock().unwrap().results_interval;
+        if interval.is_zero() {
+            return;
+        }
+
+        let results = Arc::clone(&self.results);
+        let controller = Arc::clone(&self.controller);
+        let config = Arc::clone(&self.config);
+        let run_id = self.run_id;
+        let dir = dir.to_string();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // The first tick completes immediately
+            loop {
+                ticker.tick().await;
+                let _ = Self::write_results(&results, &controller, &config, run_id, &dir);
+            }
+        });
+        info!(target: logging::RESULTS, "Results are saved every {:?}", interval);
+    }
+
+    /// Republish the last acceleration during gaps in the velocity stream (no-op when the interval is zero).
+    /// Publishes driven by velocity messages move the deadline, so nothing is sent twice while they flow.
+    fn start_actuation_heartbeat(&self) {
+        let interval = self.config.lock().unwrap().heartbeat_interval;
+        if interval.is_zero() {
+            return;
+        }
+
+        let state = Arc::clone(&self.state);
+        let config = Arc::clone(&self.config);
+        let transport = Arc::clone(&self.transport);
+        let actuation_uri = self.actuation_uri.clone();
+        let dropped_sends = Arc::clone(&self.dropped_sends);
+        tokio::spawn(async move {
+            loop {
+                let (due_at, last_actuation) = {
+                    let state = state.lock().unwrap();
+                    let last_actuation = if state.pid_active { state.last_actuation.clone() } else { None };
+                    (state.last_actuation_publish.map(|last| last + interval), last_actuation)
+                };
+
+                if let Some(due_at) = due_at.filter(|due_at| *due_at > Instant::now()) {
+                    tokio::time::sleep_until(tokio::time::Instant::from_std(due_at)).await;
+                    continue;
+                }
+                let Some((payload, payload_format)) = last_actuation else {
+                    tokio::time::sleep(interval).await;
+                    continue;
+                };
+
+                state.lock().unwrap().last_actuation_publish = Some(Instant::now());
+                let message = UMessageBuilder::publish(actuation_uri.clone())
+                    .build_with_payload(payload.clone(), payload_format)
+                    .unwrap();
+                let send_timeout = config.lock().unwrap().send_timeout;
+                match Self::send_with_timeout(&transport, message, send_timeout, &dropped_sends).await {
+                    Ok(()) => debug!(target: logging::ACTUATION, "HEARTBEAT: Republished acceleration {}", payload),
+                    Err(e) => error!(target: logging::ACTUATION, "Failed to republish acceleration: {}", e),
+                }
+            }
+        });
+        info!(target: logging::ACTUATION, "Actuation heartbeat every {:?}", interval);
+    }
+
+    /// Write the controller tunables and handler options to `logs/effective_config.json`
+    pub fn store_effective_config(&self) {
+        if let Err(e) = std::fs::create_dir_all("logs") {
+            error!(target: logging::CONFIG, "Failed to create logs directory: {}", e);
+            return;
+        }
+
+        let snapshot = serde_json::json!({
+            "controller": self.controller.lock().unwrap().config_snapshot(),
+            "handler": self.get_config(),
+        });
+        match serde_json::to_string_pretty(&snapshot) {
+            Ok(json) => match std::fs::write("logs/effective_config.json", json) {
+                Ok(()) => info!(target: logging::CONFIG, "Effective configuration saved to logs/effective_config.json"),
+                Err(e) => error!(target: logging::CONFIG, "Failed to write effective configuration: {}", e),
+            },
+            Err(e) => error!(target: logging::CONFIG, "Failed to serialize effective configuration: {}", e),
+        }
+    }
+
+    /// Remember the PID config the controller was built from, validated again by `self_test`
+    pub fn set_pid_config(&self, pid_config: PidConfig) {
+        *self.pid_config.lock().unwrap() = Some(pid_config);
+    }
+
+    /// Apply a changed PID config to the running controller, keeping its state
+    pub fn apply_pid_config(&self, old: &PidConfig, new: &PidConfig) {
+        new.apply_to(&mut self.controller.lock().unwrap());
+        *self.pid_config.lock().unwrap() = Some(new.clone());
+        for change in old.diff(new) {
+            info!(target: logging::CONFIG, "CONFIG RELOAD: {}", change);
+        }
+        self.store_effective_config();
+    }
+
+    /// Replace all handler options at once
+    pub fn set_config(&self, config: HandlerConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Get a copy of the current handler options
+    pub fn get_config(&self) -> HandlerConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// Select which actuation messages are published on every PID tick
+    pub fn set_actuation_output(&self, output: ActuationOutput) {
+        self.config.lock().unwrap().actuation_output = output;
+    }
+
+    /// Select how the command on the actuation resource is encoded
+    pub fn set_output_convention(&self, convention: OutputConvention) {
+        self.config.lock().unwrap().output_convention = convention;
+    }
+
+    /// Publish the signed acceleration as text (default) or as JSON `{"acceleration": ...}`
+    pub fn set_acceleration_encoding(&self, encoding: PayloadEncoding) {
+        self.config.lock().unwrap().acceleration_encoding = encoding;
+    }
+
+    /// Publish the actuation as uProtocol text/JSON (default) or as ROS2 `AckermannDriveStamped` JSON
+    pub fn set_wire_schema(&self, schema: WireSchema) {
+        self.config.lock().unwrap().wire_schema = schema;
+    }
+
+    /// Limit how often actuation messages are published (Duration::ZERO = every tick)
+    pub fn set_min_publish_interval(&self, interval: Duration) {
+        self.config.lock().unwrap().min_publish_interval = interval;
+    }
+
+    /// Clamp out-of-range incoming ControlValues instead of rejecting them
+    pub fn set_clamp_control_values(&self, clamp: bool) {
+        self.config.lock().unwrap().clamp_control_values = clamp;
+    }
+
+    /// Keep the result files of the last `runs` runs with timestamped names (0 = overwrite on every run)
+    pub fn set_keep_log_runs(&self, runs: usize) {
+        self.config.lock().unwrap().keep_log_runs = runs;
+    }
+
+    /// Save the results every `interval` while running (Duration::ZERO = only on shutdown).
+    /// Takes effect when `start` is called.
+    pub fn set_results_interval(&self, interval: Duration) {
+        self.config.lock().unwrap().results_interval = interval;
+    }
+
+    /// Run the control tick on every `factor`th velocity message to save CPU (0 and 1 = every message).
+    /// The PID measures the elapsed time itself, so skipped messages only lengthen its time step.
+    pub fn set_decimation_factor(&self, factor: u32) {
+        self.config.lock().unwrap().decimation_factor = factor.max(1);
+    }
+
+    /// Accept engage values above 1 from producers with more than two engage states;
+    /// by default only 0 (disengaged) and 1 (engaged) are valid
+    pub fn set_multi_state_engage(&self, enabled: bool) {
+        self.config.lock().unwrap().multi_state_engage = enabled;
+    }
+
+    /// Publish zero acceleration for the first `ticks` control ticks after activation; the PID
+    /// still runs so its state has settled when normal publishing starts (0 = disabled).
+    /// Emergency and manual braking are never suppressed.
+    pub fn set_warmup_ticks(&self, ticks: u32) {
+        self.config.lock().unwrap().warmup_ticks = ticks;
+    }
+
+    /// Keep actuators that need a continuous command stream fed: while engaged, republish the last
+    /// acceleration whenever no actuation message was sent for `interval` (Duration::ZERO = off).
+    /// Takes effect when `start` is called.
+    pub fn set_heartbeat_interval(&self, interval: Duration) {
+        self.config.lock().unwrap().heartbeat_interval = interval;
+    }
+
+    /// Publish "at speed" to the operator once the velocity has stayed within `band` (m/s) of the
+    /// desired velocity for `dwell`, and "not at speed" once it has stayed outside for `dwell`.
+    /// Independent of the controller's target speed tolerance (band 0 = off).
+    pub fn set_at_speed_band(&self, band: f64, dwell: Duration) {
+        let mut config = self.config.lock().unwrap();
+        config.at_speed_band = band.max(0.0);
+        config.at_speed_dwell = dwell;
+    }
+
+    /// After `after_failures` consecutive ticks whose actuation message could not be sent, publish
+    /// `acceleration` (m/s², negative) on a best-effort basis, disengage cruise control and report
+    /// the handler as degraded until an actuation message is sent again (0 failures = off)
+    pub fn set_fail_safe(&self, after_failures: u32, acceleration: f64) {
+        let mut config = self.config.lock().unwrap();
+        config.fail_safe_after = after_failures;
+        config.fail_safe_acceleration = acceleration.min(0.0);
+    }
+
+    /// Publish the control regime (cruise, obstacle slowdown, ...) whenever it changes, once the
+    /// new regime has persisted for `debounce`; emergency braking is published without delay
+    pub fn set_regime_publishing(&self, enabled: bool, debounce: Duration) {
+        let mut config = self.config.lock().unwrap();
+        config.publish_regime = enabled;
+        config.regime_debounce = debounce;
+    }
+
+    /// Refuse to engage until `self_test` passes, counting clock/velocity messages younger than `timeout`
+    pub fn set_self_test(&self, required: bool, timeout: Duration) {
+        let mut config = self.config.lock().unwrap();
+        config.require_self_test = required;
+        config.self_test_timeout = timeout;
+    }
+
+    /// Warn about ticks slower than `budget` from velocity receipt to actuation (Duration::ZERO = never)
+    pub fn set_tick_budget(&self, budget: Duration) {
+        self.config.lock().unwrap().tick_budget = budget;
+    }
+
+    /// Select how the driver setpoint and the ADAS speed limit are combined
+    pub fn set_speed_arbitration(&self, arbitration: SpeedArbitration) {
+        self.config.lock().unwrap().speed_arbitration = arbitration;
+    }
+
+    /// Select how a target speed of 0 received while engaged is handled
+    pub fn set_zero_target_policy(&self, policy: ZeroTargetPolicy) {
+        self.config.lock().unwrap().zero_target_policy = policy;
+    }
+
+    /// Slew limit of driver target changes while engaged in m/s per second, so rapid "set speed +/-"
+    /// taps ramp the stored target instead of stepping it (0.0 = apply immediately, the default)
+    pub fn set_target_slew_rate(&self, rate: f64) {
+        self.config.lock().unwrap().target_slew_rate = rate.max(0.0);
+    }
+
+    /// Test utility: apply `fault` to the next `count` velocity messages (0 = clear), to exercise
+    /// the freshness, validation and time step handling without a broken sensor
+    #[cfg(all(test, feature = "test-fault-injection"))]
+    pub fn inject_velocity_faults(&self, fault: VelocityFault, count: u32) {
+        self.velocity_faults.inject(fault, count);
+    }
+
+    /// Run the control tick in the velocity listener (default) or on a dedicated task.
+    /// Takes effect at `start`; until the task runs, ticks stay inline.
+    pub fn set_control_loop(&self, control_loop: ControlLoop) {
+        self.config.lock().unwrap().control_loop = control_loop;
+    }
+
+    /// Select whether the control tick time comes from the clock stream or from the wall clock
+    pub fn set_clock_source(&self, clock_source: ClockSource) {
+        self.config.lock().unwrap().clock_source = clock_source;
+    }
+
+    /// Target velocity currently used by the PID after arbitration
+    pub fn effective_target(&self) -> f64 {
+        let arbitration = self.config.lock().unwrap().speed_arbitration;
+        let state = self.state.lock().unwrap();
+        arbitration.resolve(state.desired_velocity, state.speed_limit)
+    }
+
+    /// Maximum time a single publish may take before it is dropped (Duration::ZERO = no limit)
+    pub fn set_send_timeout(&self, timeout: Duration) {
+        self.config.lock().unwrap().send_timeout = timeout;
+    }
+
+    // Getter method to access the latest lidar data
+    pub fn get_latest_lidar_data(&self) -> Option<LidarMeasurement> {
+        let state = self.state.lock().unwrap();
+        state.latest_lidar_data.as_deref().cloned()
+    }
+    
+    /// In-path detections of the latest lidar frame, filtered exactly as the controller does,
+    /// as a JSON array of `{x, y, z, intensity, forward_distance, lateral_offset}` for visualization
+    #[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+    pub fn path_obstacles_json(&self) -> String {
+        let (lidar, steer_input) = {
+            let state = self.state.lock().unwrap();
+            (state.latest_lidar_data.clone(), state.steer)
+        };
+        let obstacles = match lidar {
+            Some(lidar) => self.controller.lock().unwrap().path_obstacles(&lidar, steer_input),
+            None => Vec::new(),
+        };
+        serde_json::to_string(&obstacles).unwrap()
+    }
+
+    // Helper method to get obstacle information from lidar data
+    pub fn get_closest_obstacle(&self) -> Option<f64> {
+        let state = self.state.lock().unwrap();
+        if let Some(measurement) = state.latest_lidar_data.as_deref() {
             if measurement.is_empty || measurement.detections.is_empty() {
                 return None;
             }
@@ -201,193 +1515,521 @@ impl UProtocolHandler {
         }
     }
     
+    fn clock_listener(&self) -> ClockListener {
+        ClockListener::new(
+            Arc::clone(&self.state),
+            Arc::clone(&self.config),
+            Arc::clone(&self.decode_errors),
+            Arc::clone(&self.clock_freshness),
+        )
+    }
+
+    /// Process a velocity sample at simulation time `time` exactly as if the clock and velocity
+    /// messages had been received: updates the state and runs the PID/publish step
+    #[cfg(test)]
+    pub async fn inject_velocity(&self, velocity: f64, time: f64) {
+        self.subscriptions.record("clock");
+        self.subscriptions.record("velocity");
+        self.clock_listener().apply(time);
+        self.velocity_listener().apply(velocity, Instant::now()).await;
+    }
+
+    /// Set the target speed as if received on the target speed topic
+    #[cfg(test)]
+    pub fn inject_target(&self, speed: f64) {
+        self.subscriptions.record("target_speed");
+        self.target_speed_listener().apply(speed);
+    }
+
+    /// Engage or disengage cruise control as if received on the engage topic
+    #[cfg(test)]
+    pub fn inject_engage(&self, engaged: bool) {
+        self.subscriptions.record("engage");
+        self.engage_listener().apply(engaged as u8, None);
+    }
+
+    /// Store a lidar measurement as if received on the lidar topic
+    #[cfg(test)]
+    pub fn inject_lidar(&self, measurement: LidarMeasurement) {
+        self.subscriptions.record("lidar");
+        self.lidar_listener().apply(measurement);
+    }
+
+    /// Append the raw payload and receive time of every message on every input stream to
+    /// `<dir>/<stream>.ndjson` from now on, for exact reproduction with `replay_from`
+    pub fn record_to(&self, dir: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        self.recorder.start(dir.as_ref())?;
+        info!(target: logging::RECORDING, "RECORDING: Inbound messages are recorded to {}", dir.as_ref().display());
+        Ok(())
+    }
+
+    /// Feed a recording made by `record_to` through the stream listeners in timestamp order,
+    /// as fast as they process it; the controller timing follows the recorded clock messages.
+    /// Returns the number of replayed messages.
+    pub async fn replay_from(&self, dir: impl AsRef<std::path::Path>) -> Result<usize, Box<dyn std::error::Error>> {
+        let recording = read_recording(dir.as_ref())?;
+        let mut replayed = 0;
+        for (stream, recorded) in recording {
+            let (uri, listener): (&UUri, Box<dyn UListener>) = match stream.as_str() {
+                "clock" => (&self.clock_uri, Box::new(self.clock_listener())),
+                "velocity" => (&self.velocity_uri, Box::new(self.velocity_listener())),
+                "target_speed" => (&self.target_speed_uri, Box::new(self.target_speed_listener())),
+                "speed_limit" => (&self.speed_limit_uri, Box::new(self.speed_limit_listener())),
+                "engage" => (&self.engage_uri, Box::new(self.engage_listener())),
+                "lidar" => (&self.lidar_uri, Box::new(self.lidar_listener())),
+                "friction" => (&self.friction_uri, Box::new(self.friction_listener())),
+                "control_values" => (&self.control_values_uri, Box::new(self.control_values_listener())),
+                "command" => (&self.command_uri, Box::new(self.command_listener())),
+                _ => {
+                    warn!(target: logging::RECORDING, "REPLAY: Skipping message of unknown stream '{}'", stream);
+                    continue;
+                }
+            };
+            let message = match recorded.to_message(uri) {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!(target: logging::RECORDING, "REPLAY: Skipping {} message recorded at {}: {}", stream, recorded.ts, e);
+                    continue;
+                }
+            };
+            self.subscriptions.record(&stream);
+            listener.on_receive(message).await;
+            replayed += 1;
+        }
+        info!(target: logging::RECORDING, "REPLAY: Replayed {} messages from {}", replayed, dir.as_ref().display());
+        Ok(replayed)
+    }
+
+    /// Register `listener` for `uri`, counting its messages in the subscription status
+    async fn register_tracked<L: UListener + 'static>(
+        &self,
+        stream: &'static str,
+        uri: &UUri,
+        listener: L,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let listener = TrackedListener {
+            stream,
+            tracker: Arc::clone(&self.subscriptions),
+            recorder: Arc::clone(&self.recorder),
+            inner: listener,
+        };
+        self.transport.register_listener(uri, None, Arc::new(listener)).await?;
+        self.subscriptions.mark_subscribed(stream);
+        Ok(())
+    }
+
     async fn setup_clock_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let current_time_arc = Arc::clone(&self.current_time);
-        let transport = Arc::clone(&self.transport);
         let clock_uri = self.clock_uri.clone();
         
-        let listener = ClockListener::new(current_time_arc);
-        transport.register_listener(&clock_uri, None, Arc::new(listener)).await?;
+        let listener = self.clock_listener();
+        self.register_tracked("clock", &clock_uri, listener).await?;
         
-        info!("Timestamp subscriber registered");
+        info!(target: logging::MESSAGES, "Timestamp subscriber registered");
         Ok(())
     }
     
-    async fn setup_velocity_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let current_velocity = Arc::clone(&self.current_velocity);
-        let transport = Arc::clone(&self.transport);
-        let velocity_uri = self.velocity_uri.clone();
-        
+    fn velocity_listener(&self) -> VelocityListener {
         // Clone all necessary data for publish_acc
-        let desired_velocity = Arc::clone(&self.desired_velocity);
-        let current_time = Arc::clone(&self.current_time);
-        let previous_time = Arc::clone(&self.previous_time);
-        let pid_active = Arc::clone(&self.pid_active);
+        let state = Arc::clone(&self.state);
         let controller = Arc::clone(&self.controller);
         let results = Arc::clone(&self.results);
         let actuation_uri = self.actuation_uri.clone();
         let transport_for_publish = Arc::clone(&self.transport);
         
-        let listener = VelocityListener::new(
-            current_velocity,
-            desired_velocity,
-            current_time,
-            previous_time,
-            pid_active,
+        VelocityListener::new(
+            state,
             controller,
             results,
             actuation_uri,
             transport_for_publish,
-            Arc::clone(&self.latest_lidar_data),
             Arc::clone(&self.is_engaged),
             self.engage_uri.clone(),
-            Arc::clone(&self.throttle),
-            Arc::clone(&self.steer),
-            Arc::clone(&self.brake),
-        );
+            self.at_target_uri.clone(),
+            self.at_speed_uri.clone(),
+            self.regime_uri.clone(),
+            Arc::clone(&self.decode_errors),
+            Arc::clone(&self.config),
+            self.pedal_command_uri.clone(),
+            Arc::clone(&self.dropped_sends),
+            Arc::clone(&self.velocity_freshness),
+            Arc::clone(&self.tick_latency),
+            Arc::clone(&self.control_trigger),
+            #[cfg(all(test, feature = "test-fault-injection"))]
+            Arc::clone(&self.velocity_faults),
+        )
+    }
+
+    async fn setup_velocity_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let velocity_uri = self.velocity_uri.clone();
         
-        transport.register_listener(&velocity_uri, None, Arc::new(listener)).await?;
+        let listener = self.velocity_listener();
+        self.register_tracked("velocity", &velocity_uri, listener).await?;
         
-        info!("Velocity subscriber registered");
+        info!(target: logging::MESSAGES, "Velocity subscriber registered");
         Ok(())
     }
 
+    fn target_speed_listener(&self) -> TargetSpeedListener {
+        TargetSpeedListener::new(Arc::clone(&self.state), Arc::clone(&self.config), Arc::clone(&self.decode_errors))
+    }
+
     async fn setup_target_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let desired_velocity = Arc::clone(&self.desired_velocity);
-        let transport = Arc::clone(&self.transport);
         let target_speed_uri = self.target_speed_uri.clone();
         
-        let listener = TargetSpeedListener::new(desired_velocity);
-        transport.register_listener(&target_speed_uri, None, Arc::new(listener)).await?;
+        let listener = self.target_speed_listener();
+        self.register_tracked("target_speed", &target_speed_uri, listener).await?;
         
-        info!("Target Speed subscriber registered");
+        info!(target: logging::MESSAGES, "Target Speed subscriber registered");
         Ok(())
     }
     
+    fn speed_limit_listener(&self) -> SpeedLimitListener {
+        SpeedLimitListener::new(Arc::clone(&self.state), Arc::clone(&self.decode_errors))
+    }
+
+    async fn setup_speed_limit_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let speed_limit_uri = self.speed_limit_uri.clone();
+
+        let listener = self.speed_limit_listener();
+        self.register_tracked("speed_limit", &speed_limit_uri, listener).await?;
+
+        info!(target: logging::MESSAGES, "Speed Limit subscriber registered for URI: {}", speed_limit_uri.to_uri(false));
+        Ok(())
+    }
+
+    fn engage_listener(&self) -> EngageListener {
+        EngageListener::new(
+            Arc::clone(&self.is_engaged),
+            Arc::clone(&self.state),
+            Arc::clone(&self.controller),
+            Arc::clone(&self.config),
+            Arc::clone(&self.decode_errors),
+            Arc::clone(&self.subscriptions),
+            Arc::clone(&self.pid_config),
+        )
+    }
+
     async fn setup_engage_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let is_engaged = Arc::clone(&self.is_engaged);
-        let pid_active = Arc::clone(&self.pid_active);
-        let controller = Arc::clone(&self.controller);
-        let transport = Arc::clone(&self.transport);
         let engage_uri = self.engage_uri.clone();
         
-        let listener = EngageListener::new(is_engaged, pid_active, controller);
-        transport.register_listener(&engage_uri, None, Arc::new(listener)).await?;
+        let listener = self.engage_listener();
+        self.register_tracked("engage", &engage_uri, listener).await?;
         
-        info!("Engage subscriber registered");
+        info!(target: logging::MESSAGES, "Engage subscriber registered");
+        Ok(())
+    }
+
+    fn friction_listener(&self) -> FrictionListener {
+        FrictionListener::new(Arc::clone(&self.controller), Arc::clone(&self.decode_errors))
+    }
+
+    async fn setup_friction_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let friction_uri = self.friction_uri.clone();
+
+        let listener = self.friction_listener();
+        self.register_tracked("friction", &friction_uri, listener).await?;
+
+        info!(target: logging::MESSAGES, "Friction subscriber registered for URI: {}", friction_uri.to_uri(false));
         Ok(())
     }
 
+    fn lidar_listener(&self) -> LidarListener {
+        LidarListener::new(Arc::clone(&self.state), Arc::clone(&self.decode_errors))
+    }
+
     async fn setup_lidar_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let latest_lidar_data = Arc::clone(&self.latest_lidar_data);
-        let transport = Arc::clone(&self.transport);
         let lidar_uri = self.lidar_uri.clone();
         
-        let listener = LidarListener::new(latest_lidar_data);
-        transport.register_listener(&lidar_uri, None, Arc::new(listener)).await?;
+        let listener = self.lidar_listener();
+        self.register_tracked("lidar", &lidar_uri, listener).await?;
         
-        info!("Lidar subscriber registered for URI: {}", lidar_uri.to_uri(false));
+        info!(target: logging::MESSAGES, "Lidar subscriber registered for URI: {}", lidar_uri.to_uri(false));
         Ok(())
     }
 
+    fn control_values_listener(&self) -> ControlValuesListener {
+        ControlValuesListener::new(Arc::clone(&self.state), Arc::clone(&self.config), Arc::clone(&self.decode_errors))
+    }
+
     async fn setup_control_values_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let throttle = Arc::clone(&self.throttle);
-        let steer = Arc::clone(&self.steer);
-        let brake = Arc::clone(&self.brake);
-        let transport = Arc::clone(&self.transport);
         let control_values_uri = self.control_values_uri.clone();
-        let listener = ControlValuesListener::new(throttle, steer, brake);
-        transport.register_listener(&control_values_uri, None, Arc::new(listener)).await?;
-        info!("Control Values subscriber registered for URI: {}", control_values_uri.to_uri(false));
+        let listener = self.control_values_listener();
+        self.register_tracked("control_values", &control_values_uri, listener).await?;
+        info!(target: logging::MESSAGES, "Control Values subscriber registered for URI: {}", control_values_uri.to_uri(false));
+        Ok(())
+    }
+
+    fn command_listener(&self) -> CommandListener {
+        CommandListener {
+            controller: Arc::clone(&self.controller),
+            results: Arc::clone(&self.results),
+            config: Arc::clone(&self.config),
+            run_id: self.run_id,
+            transport: Arc::clone(&self.transport),
+            status_uri: self.command_status_uri.clone(),
+            dropped_sends: Arc::clone(&self.dropped_sends),
+            decode_errors: Arc::clone(&self.decode_errors),
+        }
+    }
+
+    async fn setup_command_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let listener = self.command_listener();
+        self.register_tracked("command", &self.command_uri, listener).await?;
+        info!(target: logging::MESSAGES, "Command subscriber registered for URI: {}", self.command_uri.to_uri(false));
         Ok(())
     }
 
     // Static method for PID computation and publishing
     async fn publish_acc(
-        desired_velocity: &Arc<Mutex<f64>>,
-        current_velocity: &Arc<Mutex<f64>>,
-        current_time: &Arc<Mutex<f64>>,
-        previous_time: &Arc<Mutex<f64>>,
-        pid_active: &Arc<Mutex<bool>>,
+        state: &Arc<Mutex<ControlState>>,
         controller: &Arc<Mutex<PIDController>>,
-        transport: &Arc<UPTransportZenoh>,
+        transport: &Arc<dyn UTransport>,
         actuation_uri: UUri,
         results: &Arc<Mutex<HashMap<String, Vec<f64>>>>,
-        latest_lidar_data: &Arc<Mutex<Option<LidarMeasurement>>>,
-        is_engaged: &Arc<Mutex<u8>>,
+        is_engaged: &Arc<EngagedState>,
         engage_uri: &UUri,
-        throttle: &Arc<Mutex<f64>>,
-        steer: &Arc<Mutex<f64>>,
-        brake: &Arc<Mutex<f64>>,
+        at_target_uri: &UUri,
+        at_speed_uri: &UUri,
+        regime_uri: &UUri,
+        config: &Arc<Mutex<HandlerConfig>>,
+        pedal_command_uri: &UUri,
+        dropped_sends: &Arc<AtomicU64>,
     ) {
-        // Check if PID is active
-        let is_active = {
-            let active = pid_active.lock().unwrap();
-            *active
+        // Take one consistent snapshot of the inputs, if PID is active
+        let target_slew_rate = config.lock().unwrap().target_slew_rate;
+        let snapshot = {
+            let mut state = state.lock().unwrap();
+            if !state.pid_active {
+                return;
+            }
+            Self::slew_target(&mut state, target_slew_rate);
+            state.clone()
         };
-        
-        if !is_active {
+
+        let (
+            (send_timeout, speed_arbitration, warmup_ticks, at_speed_band, at_speed_dwell, publish_regime, regime_debounce),
+            (actuation_output, wire_schema, output_convention, acceleration_encoding, min_publish_interval, fail_safe_after),
+        ) = {
+            let config = config.lock().unwrap();
+            ((config.send_timeout, config.speed_arbitration, config.warmup_ticks, config.at_speed_band, config.at_speed_dwell,
+              config.publish_regime, config.regime_debounce),
+             (config.actuation_output, config.wire_schema, config.output_convention, config.acceleration_encoding,
+              config.min_publish_interval, config.fail_safe_after))
+        };
+
+        // A zero target under ZeroTargetPolicy::Disengage ends the cruise control session
+        if snapshot.zero_target_disengage {
+            info!(target: logging::ENGAGE, "CRUISE CONTROL DISENGAGEMENT: Zero target speed received - disengaging cruise control");
+            state.lock().unwrap().zero_target_disengage = false;
+            is_engaged.set(0);
+            Self::deactivate_pid(state, controller);
+
+            let disengage_message = UMessageBuilder::publish(engage_uri.clone())
+                .build_with_payload("0".to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+                .expect("Failed to build disengage message");
+            if let Err(e) = Self::send_with_timeout(transport, disengage_message, send_timeout, dropped_sends).await {
+                error!(target: logging::ENGAGE, "Failed to send cruise control disengage message: {}", e);
+            }
             return;
         }
 
-        let (desired_vel, current_vel, curr_time) = {
-            let desired = desired_velocity.lock().unwrap();
-            let current = current_velocity.lock().unwrap();
-            let time = current_time.lock().unwrap();
-            (*desired, *current, *time)
-        };
+        let desired_vel = speed_arbitration.resolve(snapshot.desired_velocity, snapshot.speed_limit);
+        let current_vel = snapshot.current_velocity;
+        let curr_time = snapshot.current_time;
 
         // Compute acceleration using PID controller
-        let (acceleration, emergency_brake_engaged, manual_brake_detected, cruise_should_disengage, cruise_can_reengage) = {
+        let (result, filtered_vel, closest_obstacle_distance) = {
             let mut pid = controller.lock().unwrap();
-            let lidar_data = latest_lidar_data.lock().unwrap();
             
             // Get current control values
-            let throttle_input = *throttle.lock().unwrap();
-            let steer_input = *steer.lock().unwrap();
-            let brake_input = *brake.lock().unwrap();
+            let throttle_input = snapshot.throttle;
+            let steer_input = snapshot.steer;
+            let brake_input = snapshot.brake;
             
             // Pass lidar data and control values to PID controller
-            let lidar_ref = lidar_data.as_ref();
+            let lidar_ref = snapshot.latest_lidar_data.as_deref();
             
-            match pid.compute(desired_vel, current_vel, curr_time, lidar_ref, throttle_input, steer_input, brake_input) {
+            let compute_result = pid.compute(desired_vel, current_vel, curr_time, lidar_ref, throttle_input, steer_input, brake_input);
+            let filtered_vel = pid.filtered_velocity().unwrap_or(current_vel);
+            let closest_obstacle_distance = lidar_ref
+                .and_then(|lidar| pid.path_obstacles(lidar, steer_input).iter()
+                    .map(|obstacle| obstacle.forward_distance)
+                    .min_by(f64::total_cmp))
+                .unwrap_or(NO_OBSTACLE_DISTANCE);
+
+            #[cfg(feature = "metrics")]
+            {
+                let m = crate::metrics::metrics();
+                m.messages_processed.inc();
+                m.current_velocity.set(current_vel);
+                m.desired_velocity.set(desired_vel);
+                m.integral_term.set(pid.integral_term());
+            }
+
+            match compute_result {
                 Ok(result) => {
                     if result.emergency_brake_engaged {
-                        warn!("EMERGENCY BRAKE ENGAGED: {}", 
+                        warn!(target: logging::EMERGENCY, "EMERGENCY BRAKE ENGAGED: {}", 
                               result.emergency_reason.as_ref().unwrap_or(&"Unknown reason".to_string()));
                     }
                     if result.manual_brake_detected {
-                        info!("MANUAL BRAKE DETECTED: Driver intervention detected");
+                        info!(target: logging::ENGAGE, "MANUAL BRAKE DETECTED: Driver intervention detected");
                     }
-                    (result.acceleration, result.emergency_brake_engaged, result.manual_brake_detected, 
-                     result.cruise_should_disengage, result.cruise_can_reengage)
+                    (result, filtered_vel, closest_obstacle_distance)
                 },
+                Err(e @ PidError::NegativeDeltaTime { .. }) => {
+                    // Clock glitch: skip this tick, the next sample is measured against the new time
+                    warn!(target: logging::CONTROL, "PID computation skipped: {}", e);
+                    return;
+                }
+                Err(e @ PidError::NonFiniteInput { .. }) => {
+                    // Bad sample: nothing is published, the actuator holds the last command
+                    warn!(target: logging::CONTROL, "PID computation skipped: {}", e);
+                    return;
+                }
                 Err(e) => {
-                    error!("PID computation failed: {}", e);
+                    error!(target: logging::CONTROL, "PID computation failed: {}", e);
                     return;
                 }
             }
         };
-        
-        // Handle cruise control disengagement and re-engagement
-        if cruise_should_disengage {
-            let reason = if emergency_brake_engaged {
+
+        // An emergency stop counts once, not on every tick of its braking
+        #[cfg(feature = "metrics")]
+        let emergency_onset = result.emergency_brake_engaged
+            && state.lock().unwrap().last_regime != Some(ControlRegime::EmergencyBraking);
+
+        // Every state update of this tick under one guard, the messages are sent after it is released
+        let TickUpdate { result, reengage, at_target_changed, at_speed_changed, regime_changed, publish_due, actuation_payload, delta_time } = {
+            let mut state = state.lock().unwrap();
+
+            // Startup transients of the first ticks after activation do not reach the actuator
+            state.active_ticks += 1;
+            let result = if state.active_ticks <= warmup_ticks as u64 && !result.emergency_brake_engaged && !result.manual_brake_detected {
+                debug!(target: logging::CONTROL, "WARMUP: Tick {}/{} publishes zero instead of {:.3} m/s²", state.active_ticks, warmup_ticks, result.acceleration);
+                PIDResult {
+                    steer: result.steer,
+                    at_target: result.at_target,
+                    cruise_can_reengage: result.cruise_can_reengage,
+                    regime: result.regime,
+                    ..PIDResult::new(0.0)
+                }
+            } else {
+                result
+            };
+
+            // Cruise control disengagement and re-engagement
+            if result.cruise_should_disengage {
+                is_engaged.set(0);
+                state.pid_active = false;
+            }
+            let reengage = result.cruise_can_reengage && is_engaged.get() == 0;
+            if reengage {
+                is_engaged.set(1);
+                state.pid_active = true;
+            }
+
+            state.last_acceleration = Some(result.acceleration);
+            state.last_obstacle_distance = Some(closest_obstacle_distance).filter(|distance| *distance != NO_OBSTACLE_DISTANCE);
+            state.last_regime = Some(result.regime);
+            state.last_saturated = result.saturated;
+            state.last_requested_acceleration = Some(result.requested_acceleration);
+
+            // "At target" status is only published when it changes
+            let at_target_changed = state.at_target != result.at_target;
+            state.at_target = result.at_target;
+
+            // The operator "at speed" status changes once the band crossing has lasted the dwell time
+            let at_speed_changed = if at_speed_band > 0.0 {
+                let in_band = within_speed_band(desired_vel, current_vel, at_speed_band);
+                if in_band == state.at_speed {
+                    state.at_speed_pending = None;
+                    None
+                } else {
+                    let since = *state.at_speed_pending.get_or_insert(curr_time);
+                    if curr_time - since >= at_speed_dwell.as_secs_f64() {
+                        state.at_speed = in_band;
+                        state.at_speed_pending = None;
+                        Some(in_band)
+                    } else {
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            // The control regime changes once it has persisted for the debounce time
+            let regime_changed = if publish_regime {
+                let regime = result.regime;
+                if state.regime == Some(regime) {
+                    state.regime_pending = None;
+                    None
+                } else {
+                    let since = match state.regime_pending {
+                        Some((pending, since)) if pending == regime => since,
+                        _ => curr_time,
+                    };
+                    state.regime_pending = Some((regime, since));
+                    if regime == ControlRegime::EmergencyBraking || curr_time - since >= regime_debounce.as_secs_f64() {
+                        state.regime = Some(regime);
+                        state.regime_pending = None;
+                        Some(regime)
+                    } else {
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            // Rate limit actuation messages; emergency braking is always published immediately
+            let now = Instant::now();
+            let publish_due = result.emergency_brake_engaged || match state.last_actuation_publish {
+                Some(last) => now.duration_since(last) >= min_publish_interval,
+                None => true,
+            };
+            if publish_due {
+                state.last_actuation_publish = Some(now);
+            }
+
+            let actuation_payload = (publish_due && actuation_output.publishes_acceleration()).then(|| {
+                ActuationCommand::from_result(&result, curr_time)
+                    .with_speed(desired_vel)
+                    .to_payload(wire_schema, output_convention, acceleration_encoding)
+            });
+            if actuation_payload.is_some() {
+                state.last_actuation = actuation_payload.clone();
+            }
+
+            let delta_time = if state.previous_time > 0.0 { curr_time - state.previous_time } else { 0.0 };
+            state.previous_time = curr_time;
+
+            TickUpdate { result, reengage, at_target_changed, at_speed_changed, regime_changed, publish_due, actuation_payload, delta_time }
+        };
+
+        // The acceleration gauge follows what the actuator actually received
+        #[cfg(feature = "metrics")]
+        {
+            let m = crate::metrics::metrics();
+            if publish_due {
+                m.acceleration.set(result.acceleration);
+            }
+            if emergency_onset {
+                m.emergency_brake_events.inc();
+            }
+        }
+
+        if result.cruise_should_disengage {
+            let reason = if result.emergency_brake_engaged {
                 "Emergency brake triggered"
-            } else if manual_brake_detected {
+            } else if result.manual_brake_detected {
                 "Manual brake detected"
             } else {
                 "Safety intervention"
             };
             
-            info!("CRUISE CONTROL DISENGAGEMENT: {} - disengaging cruise control for safety", reason);
-            {
-                let mut engaged_state = is_engaged.lock().unwrap();
-                *engaged_state = 0; // Disengage cruise control
-            }
-            {
-                let mut active_state = pid_active.lock().unwrap();
-                *active_state = false; // Deactivate PID control
-            }
+            info!(target: logging::ENGAGE, "CRUISE CONTROL DISENGAGEMENT: {} - disengaging cruise control for safety", reason);
             
             // Publish disengage message to cruise control system
             let disengage_payload = "0";
@@ -395,325 +2037,808 @@ impl UProtocolHandler {
                 .build_with_payload(disengage_payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
                 .expect("Failed to build disengage message");
             
-            if let Err(e) = transport.send(disengage_message).await {
-                error!("Failed to send cruise control disengage message: {}", e);
+            if let Err(e) = Self::send_with_timeout(transport, disengage_message, send_timeout, dropped_sends).await {
+                error!(target: logging::ENGAGE, "Failed to send cruise control disengage message: {}", e);
             } else {
-                info!("Successfully sent cruise control disengage message due to {}", reason);
+                info!(target: logging::ENGAGE, "Successfully sent cruise control disengage message due to {}", reason);
             }
         }
         
-        // Handle cruise control re-engagement
-        if cruise_can_reengage {
-            let current_engaged = {
-                let engaged_state = is_engaged.lock().unwrap();
-                *engaged_state
-            };
+        if reengage {
+            info!(target: logging::ENGAGE, "CRUISE CONTROL RE-ENGAGEMENT: Conditions met - re-engaging cruise control");
             
-            if current_engaged == 0 {
-                info!("CRUISE CONTROL RE-ENGAGEMENT: Conditions met - re-engaging cruise control");
-                {
-                    let mut engaged_state = is_engaged.lock().unwrap();
-                    *engaged_state = 1; // Re-engage cruise control
-                }
-                {
-                    let mut active_state = pid_active.lock().unwrap();
-                    *active_state = true; // Reactivate PID control
-                }
-                
-                // Publish re-engage message to cruise control system
-                let engage_payload = "1";
-                let engage_message = UMessageBuilder::publish(engage_uri.clone())
-                    .build_with_payload(engage_payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
-                    .expect("Failed to build engage message");
-                
-                if let Err(e) = transport.send(engage_message).await {
-                    error!("Failed to send cruise control re-engage message: {}", e);
-                } else {
-                    info!("Successfully sent cruise control re-engage message");
-                }
+            // Publish re-engage message to cruise control system
+            let engage_payload = "1";
+            let engage_message = UMessageBuilder::publish(engage_uri.clone())
+                .build_with_payload(engage_payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+                .expect("Failed to build engage message");
+            
+            if let Err(e) = Self::send_with_timeout(transport, engage_message, send_timeout, dropped_sends).await {
+                error!(target: logging::ENGAGE, "Failed to send cruise control re-engage message: {}", e);
+            } else {
+                info!(target: logging::ENGAGE, "Successfully sent cruise control re-engage message");
             }
         }
         
-        if desired_vel < current_vel {
-            debug!("Deceleration required");
-        }
+        if at_target_changed {
+            let reached_target = result.at_target;
+            let at_target_payload = if reached_target { "1" } else { "0" };
+            let at_target_message = UMessageBuilder::publish(at_target_uri.clone())
+                .build_with_payload(at_target_payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+                .expect("Failed to build at target message");
 
-        // Create and publish uProtocol message
-        let actuation_cmd_payload = format!("{}", acceleration);
-        let message = UMessageBuilder::publish(actuation_uri)
-            .build_with_payload(actuation_cmd_payload.clone(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
-            .unwrap();
-        
-        if let Err(e) = transport.send(message).await {
-            error!("Failed to publish acceleration: {}", e);
-        } else {
-            debug!("Publishing Acceleration: {}", actuation_cmd_payload);
+            if let Err(e) = Self::send_with_timeout(transport, at_target_message, send_timeout, dropped_sends).await {
+                error!(target: logging::CONTROL, "Failed to publish at target status: {}", e);
+            } else {
+                info!(target: logging::CONTROL, "AT TARGET: Vehicle {} target speed", if reached_target { "reached" } else { "left" });
+            }
         }
 
-        // Store results for later analysis
-        {
-            let mut results_guard = results.lock().unwrap();
-            results_guard.get_mut("desired_velocity").unwrap().push(desired_vel);
-            results_guard.get_mut("current_velocity").unwrap().push(current_vel);
-            results_guard.get_mut("current_time").unwrap().push(curr_time);
-            results_guard.get_mut("acceleration").unwrap().push(acceleration);
+        if let Some(at_speed) = at_speed_changed {
+            let at_speed_message = UMessageBuilder::publish(at_speed_uri.clone())
+                .build_with_payload(if at_speed { "1" } else { "0" }.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+                .expect("Failed to build at speed message");
+
+            if let Err(e) = Self::send_with_timeout(transport, at_speed_message, send_timeout, dropped_sends).await {
+                error!(target: logging::CONTROL, "Failed to publish at speed status: {}", e);
+            } else {
+                info!(target: logging::CONTROL, "AT SPEED: Vehicle {} the {} band", if at_speed { "entered" } else { "left" }, logging::speed(at_speed_band));
+            }
+        }
+
+        if let Some(regime) = regime_changed {
+            let regime_status = RegimeStatus { regime, timestamp: curr_time };
+            let regime_message = UMessageBuilder::publish(regime_uri.clone())
+                .build_with_payload(serde_json::to_string(&regime_status).unwrap(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+                .expect("Failed to build regime message");
+
+            if let Err(e) = Self::send_with_timeout(transport, regime_message, send_timeout, dropped_sends).await {
+                error!(target: logging::CONTROL, "Failed to publish control regime: {}", e);
+            } else {
+                info!(target: logging::CONTROL, "REGIME: Controller is now in {:?}", regime);
+            }
+        }
+        
+        if desired_vel < current_vel {
+            debug!(target: logging::CONTROL, "Deceleration required");
+        }
+
+        if !publish_due {
+            debug!(target: logging::ACTUATION, "Actuation publish skipped by rate limiter ({:?} interval)", min_publish_interval);
+        }
+
+        // Create and publish uProtocol message
+        let mut actuation_failed = false;
+        if let Some((actuation_cmd_payload, payload_format)) = actuation_payload {
+            let message = UMessageBuilder::publish(actuation_uri.clone())
+                .build_with_payload(actuation_cmd_payload.clone(), payload_format)
+                .unwrap();
+            
+            if let Err(e) = Self::send_with_timeout(transport, message, send_timeout, dropped_sends).await {
+                error!(target: logging::ACTUATION, "Failed to publish acceleration: {}", e);
+                actuation_failed = true;
+            } else {
+                debug!(target: logging::ACTUATION, "Publishing Acceleration: {}", actuation_cmd_payload);
+            }
+        }
+
+        // Publish explicit pedal values, passing the (rate limited) steer input through
+        if publish_due && actuation_output.publishes_control_values() {
+            let pedal_command = ControlValues {
+                throttle: result.throttle,
+                steer: result.steer,
+                brake: result.brake,
+            };
+            let pedal_payload = serde_json::to_string(&pedal_command).unwrap();
+            let message = UMessageBuilder::publish(pedal_command_uri.clone())
+                .build_with_payload(pedal_payload.clone(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+                .unwrap();
+
+            if let Err(e) = Self::send_with_timeout(transport, message, send_timeout, dropped_sends).await {
+                error!(target: logging::ACTUATION, "Failed to publish pedal command: {}", e);
+                actuation_failed = true;
+            } else {
+                debug!(target: logging::ACTUATION, "Publishing Pedal Command: {}", pedal_payload);
+            }
+        }
+
+        // The actuator keeps a stale command while sends fail, so give up after N failed ticks
+        // (the only state update that has to wait for the sends)
+        let enter_fail_safe = publish_due && {
+            let mut state = state.lock().unwrap();
+            if actuation_failed {
+                state.actuation_send_failures += 1;
+                let enter = fail_safe_after > 0 && state.actuation_send_failures >= fail_safe_after && !state.degraded;
+                state.degraded |= enter;
+                enter
+            } else {
+                if state.degraded {
+                    info!(target: logging::EMERGENCY, "FAIL-SAFE: Actuation messages are sent again, leaving degraded state");
+                }
+                state.actuation_send_failures = 0;
+                state.degraded = false;
+                false
+            }
+        };
+        if enter_fail_safe {
+            Self::engage_fail_safe(
+                state, controller, transport, config, is_engaged, engage_uri, &actuation_uri, pedal_command_uri,
+                curr_time, dropped_sends,
+            ).await;
+        }
+
+        // Store results for later analysis
+        {
+            let mut results_guard = results.lock().unwrap();
+            results_guard.get_mut("desired_velocity").unwrap().push(desired_vel);
+            results_guard.get_mut("current_velocity").unwrap().push(current_vel);
+            results_guard.get_mut("filtered_velocity").unwrap().push(filtered_vel);
+            results_guard.get_mut("current_time").unwrap().push(curr_time);
+            results_guard.get_mut("acceleration").unwrap().push(result.acceleration);
+            results_guard.get_mut("closest_obstacle_distance").unwrap().push(closest_obstacle_distance);
+            results_guard.get_mut("requested_acceleration").unwrap().push(result.requested_acceleration);
         }
 
-        // Calculate and log delta time
-        let (_prev_time, delta_time) = {
-            let mut prev = previous_time.lock().unwrap();
-            let delta = if *prev > 0.0 { curr_time - *prev } else { 0.0 };
-            *prev = curr_time;
-            (*prev, delta)
-        };
-        
         if delta_time > 0.0 {
-            debug!("Delta time: {} seconds", delta_time);
+            debug!(target: logging::CONTROL, "Delta time: {} seconds", delta_time);
         }
     }
 
-    // Activation method
-    fn activate_pid(
-        pid_active: &Arc<Mutex<bool>>,
-        controller: &Arc<Mutex<PIDController>>,
-    ) {
-        {
-            let mut active = pid_active.lock().unwrap();
-            *active = true;
+    /// Start a new control session in `state`; the caller resets the controller with
+    /// `reset_controller` once the state lock is released
+    /// Move `desired_velocity` towards a pending driver target by at most `slew_rate` times the
+    /// time since the previous tick
+    fn slew_target(state: &mut ControlState, slew_rate: f64) {
+        let Some(requested) = state.requested_velocity else {
+            return;
+        };
+        let elapsed = if state.previous_time > 0.0 { (state.current_time - state.previous_time).max(0.0) } else { 0.0 };
+        state.desired_velocity = if slew_rate > 0.0 {
+            let max_change = slew_rate * elapsed;
+            requested.clamp(state.desired_velocity - max_change, state.desired_velocity + max_change)
+        } else {
+            requested
+        };
+        if state.desired_velocity == requested {
+            state.requested_velocity = None;
         }
-        {
+        debug!(target: logging::TARGET, "TARGET SLEW: Desired velocity {} towards {}", logging::speed(state.desired_velocity), logging::speed(requested));
+    }
+
+    fn activate_state(state: &mut ControlState) {
+        // A new session starts at the latest driver target
+        if let Some(requested) = state.requested_velocity.take() {
+            state.desired_velocity = requested;
+        }
+        state.pid_active = true;
+        state.active_ticks = 0;
+        state.last_actuation = None;
+        state.last_regime = None;
+        state.at_speed_pending = None;
+        state.regime = None;
+        state.regime_pending = None;
+    }
+
+    /// Reset the PID state after the control session was switched (`ACTIVATED` or `DEACTIVATED`)
+    fn reset_controller(controller: &Arc<Mutex<PIDController>>, switch: &str) {
+        let timestamp = {
             let mut pid = controller.lock().unwrap();
             pid.reset();
+            pid.wall_time().as_secs()
+        };
+        info!(target: logging::ENGAGE, "[INFO] PID controller {} at {}", switch, timestamp);
+    }
+
+    /// Best effort after repeated actuation send failures: command the fail-safe deceleration on
+    /// every actuation output and disengage cruise control
+    async fn engage_fail_safe(
+        state: &Arc<Mutex<ControlState>>,
+        controller: &Arc<Mutex<PIDController>>,
+        transport: &Arc<dyn UTransport>,
+        config: &Arc<Mutex<HandlerConfig>>,
+        is_engaged: &Arc<EngagedState>,
+        engage_uri: &UUri,
+        actuation_uri: &UUri,
+        pedal_command_uri: &UUri,
+        curr_time: f64,
+        dropped_sends: &Arc<AtomicU64>,
+    ) {
+        let config = config.lock().unwrap().clone();
+        let failures = state.lock().unwrap().actuation_send_failures;
+        error!(target: logging::EMERGENCY, "FAIL-SAFE: {} consecutive actuation send failures - commanding {:.2} m/s² and disengaging cruise control",
+               failures, config.fail_safe_acceleration);
+
+        let result = PIDResult::new(config.fail_safe_acceleration).with_regime(ControlRegime::EmergencyBraking);
+        if config.actuation_output.publishes_acceleration() {
+            let command = ActuationCommand::from_result(&result, curr_time);
+            let (payload, payload_format) = command.to_payload(config.wire_schema, config.output_convention, config.acceleration_encoding);
+            let message = UMessageBuilder::publish(actuation_uri.clone())
+                .build_with_payload(payload, payload_format)
+                .unwrap();
+            if let Err(e) = Self::send_with_timeout(transport, message, config.send_timeout, dropped_sends).await {
+                error!(target: logging::EMERGENCY, "FAIL-SAFE: Failed to publish fail-safe acceleration: {}", e);
+            }
+        }
+        if config.actuation_output.publishes_control_values() {
+            let pedal_command = ControlValues { throttle: result.throttle, steer: 0.0, brake: result.brake };
+            let message = UMessageBuilder::publish(pedal_command_uri.clone())
+                .build_with_payload(serde_json::to_string(&pedal_command).unwrap(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+                .unwrap();
+            if let Err(e) = Self::send_with_timeout(transport, message, config.send_timeout, dropped_sends).await {
+                error!(target: logging::EMERGENCY, "FAIL-SAFE: Failed to publish fail-safe pedal command: {}", e);
+            }
+        }
+
+        is_engaged.set(0);
+        Self::deactivate_pid(state, controller);
+        let disengage_message = UMessageBuilder::publish(engage_uri.clone())
+            .build_with_payload("0".to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .expect("Failed to build disengage message");
+        if let Err(e) = Self::send_with_timeout(transport, disengage_message, config.send_timeout, dropped_sends).await {
+            error!(target: logging::EMERGENCY, "FAIL-SAFE: Failed to send cruise control disengage message: {}", e);
         }
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        info!("[INFO] PID controller ACTIVATED at {}", timestamp);
     }
 
     // Deactivation method
     fn deactivate_pid(
-        pid_active: &Arc<Mutex<bool>>,
+        state: &Arc<Mutex<ControlState>>,
         controller: &Arc<Mutex<PIDController>>,
     ) {
-        {
-            let mut active = pid_active.lock().unwrap();
-            *active = false;
-        }
-        {
-            let mut pid = controller.lock().unwrap();
-            pid.reset();
-        }
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        info!("[INFO] PID controller DEACTIVATED at {}", timestamp);
+        state.lock().unwrap().pid_active = false;
+        Self::reset_controller(controller, "DEACTIVATED");
     }
     
     pub fn store_results(&self) {
-        let results = self.results.lock().unwrap();
+        // Failures are already logged
+        let _ = Self::write_results(&self.results, &self.controller, &self.config, self.run_id, RESULTS_DIR);
+    }
+
+    fn collect_run_metadata(
+        controller: &Arc<Mutex<PIDController>>,
+        config: &Arc<Mutex<HandlerConfig>>,
+        run_id: u128,
+    ) -> RunMetadata {
+        RunMetadata {
+            started_at_ms: run_id as u64,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: option_env!("GIT_COMMIT").map(str::to_string),
+            controller: controller.lock().unwrap().config_snapshot(),
+            handler: config.lock().unwrap().clone(),
+        }
+    }
+
+    // Static method writing the result series to `dir` (RESULTS_DIR outside the tests), returning the last failure.
+    // The results lock is held for the whole write, so periodic and manual writes never interleave.
+    fn write_results(
+        results: &Arc<Mutex<HashMap<String, Vec<f64>>>>,
+        controller: &Arc<Mutex<PIDController>>,
+        config: &Arc<Mutex<HandlerConfig>>,
+        run_id: u128,
+        dir: &str,
+    ) -> Result<(), String> {
+        let keep_log_runs = config.lock().unwrap().keep_log_runs;
+        // Collected before taking the results lock, so no two locks are held at once
+        let metadata = Self::collect_run_metadata(controller, config, run_id);
+        let results = results.lock().unwrap();
+        let mut outcome = Ok(());
         
         // Create logs directory if it doesn't exist
-        if let Err(e) = std::fs::create_dir_all("logs") {
-            error!("Failed to create logs directory: {}", e);
-            return;
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            error!(target: logging::RESULTS, "Failed to create {} directory: {}", dir, e);
+            return Err(format!("Failed to create {} directory: {}", dir, e));
         }
+
+        // With rotation enabled every run gets its own "-<unix_ms>" suffix
+        let suffix = if keep_log_runs > 0 {
+            format!("-{}", run_id)
+        } else {
+            String::new()
+        };
         
         // Store each result type in separate files
         for (key, values) in results.iter() {
-            let filename = format!("logs/{}.log", key);
+            let filename = format!("{}/{}{}.log", dir, key, suffix);
             let content = values.iter()
                 .map(|v| v.to_string())
                 .collect::<Vec<String>>()
                 .join("\n");
             
-            if let Err(e) = std::fs::write(&filename, content) {
-                error!("Failed to write {}: {}", filename, e);
+            if let Err(e) = Self::write_file_atomic(&filename, content) {
+                error!(target: logging::RESULTS, "Failed to write {}: {}", filename, e);
+                outcome = Err(format!("Failed to write {}: {}", filename, e));
             } else {
-                info!("Results saved to {}", filename);
+                info!(target: logging::RESULTS, "Results saved to {}", filename);
             }
         }
 
-        // Also save as JSON for compatibility
-        if let Ok(json) = serde_json::to_string(&*results) {
-            std::fs::write("logs/pid_results.json", json).unwrap_or_else(|e| {
-                error!("Failed to write JSON results: {}", e);
+        // Also save as JSON, with the metadata needed to compare runs later
+        let run_results = RunResults { metadata, series: &results };
+        if let Ok(json) = serde_json::to_string(&run_results) {
+            Self::write_file_atomic(&format!("{}/pid_results{}.json", dir, suffix), json).unwrap_or_else(|e| {
+                error!(target: logging::RESULTS, "Failed to write JSON results: {}", e);
+                outcome = Err(format!("Failed to write JSON results: {}", e));
             });
         }
+
+        if keep_log_runs > 0 {
+            Self::prune_log_runs(dir, keep_log_runs);
+        }
+        outcome
     }
-    
-    pub fn show_results(&self) {
-        let results = self.results.lock().unwrap();
-        
-        info!("PID Controller Results Summary:");
-        
-        if let (Some(desired), Some(current), Some(acceleration)) = (
-            results.get("desired_velocity"),
-            results.get("current_velocity"), 
-            results.get("acceleration")
-        ) {
-            let data_points = desired.len().min(current.len()).min(acceleration.len());
-            info!("Total data points: {}", data_points);
-            
-            if data_points > 0 {
-                let mut min_error = f64::MAX;
-                let mut max_error = f64::MIN;
-                let mut sum_error = 0.0;
-                
-                for i in 0..data_points {
-                    let error = desired[i] - current[i];
-                    min_error = min_error.min(error);
-                    max_error = max_error.max(error);
-                    sum_error += error;
-                }
-                
-                let avg_error = sum_error / data_points as f64;
-                
-                info!("Min error: {:.4}", min_error);
-                info!("Max error: {:.4}", max_error);
-                info!("Avg error: {:.4}", avg_error);
-                
-                if let Some(acc_values) = results.get("acceleration") {
-                    if !acc_values.is_empty() {
-                        let min_acc = acc_values.iter().fold(f64::INFINITY, |a, &b| a.min(b));
-                        let max_acc = acc_values.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
-                        let avg_acc = acc_values.iter().sum::<f64>() / acc_values.len() as f64;
-                        
-                        info!("Acceleration - Min: {:.4}, Max: {:.4}, Avg: {:.4}", min_acc, max_acc, avg_acc);
-                    }
+
+    /// Write to a temporary file and rename it over `path`, so readers never see a partially written file
+    fn write_file_atomic(path: &str, content: String) -> std::io::Result<()> {
+        let temporary = format!("{}.tmp", path);
+        std::fs::write(&temporary, content)?;
+        std::fs::rename(&temporary, path)
+    }
+
+    /// Delete timestamped result files of all but the newest `keep` runs
+    fn prune_log_runs(dir: &str, keep: usize) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!(target: logging::RESULTS, "Failed to list {} for log rotation: {}", dir, e);
+                return;
+            }
+        };
+
+        // Files written by store_results end in "-<unix_ms>.log" or "-<unix_ms>.json"
+        let run_files: Vec<(u128, std::path::PathBuf)> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| matches!(path.extension().and_then(|ext| ext.to_str()), Some("log") | Some("json")))
+            .filter_map(|path| {
+                let stem = path.file_stem()?.to_str()?;
+                let run_id = stem.rsplit_once('-')?.1.parse::<u128>().ok()?;
+                Some((run_id, path))
+            })
+            .collect();
+
+        let mut run_ids: Vec<u128> = run_files.iter().map(|(run_id, _)| *run_id).collect();
+        run_ids.sort_unstable_by(|a, b| b.cmp(a));
+        run_ids.dedup();
+        let Some(&oldest_kept) = run_ids.get(keep.saturating_sub(1)) else {
+            return;
+        };
+
+        for (run_id, path) in run_files {
+            if run_id < oldest_kept {
+                match std::fs::remove_file(&path) {
+                    Ok(()) => debug!(target: logging::RESULTS, "Removed old results file {}", path.display()),
+                    Err(e) => error!(target: logging::RESULTS, "Failed to remove old results file {}: {}", path.display(), e),
                 }
             }
-        } else {
-            info!("No data points available");
         }
     }
+    
+    /// Return the recorded series and start a fresh segment, atomically with respect to the control loop
+    pub fn take_results(&self) -> HashMap<String, Vec<f64>> {
+        let mut results = self.results.lock().unwrap();
+        results.iter_mut()
+            .map(|(key, values)| (key.clone(), std::mem::take(values)))
+            .collect()
+    }
+
+    /// Discard all recorded series, keeping the series names
+    pub fn clear_results(&self) {
+        Self::clear_result_series(&self.results);
+    }
+
+    fn clear_result_series(results: &Arc<Mutex<HashMap<String, Vec<f64>>>>) {
+        let mut results = results.lock().unwrap();
+        for values in results.values_mut() {
+            values.clear();
+        }
+    }
+
+    /// Compute tracking statistics over the stored results
+    pub fn tracking_metrics(&self) -> TrackingMetrics {
+        let results = self.results.lock().unwrap();
+        TrackingMetrics::from_results(&results)
+    }
+
+    pub fn show_results(&self) {
+        info!(target: logging::RESULTS, "PID Controller Results Summary:");
+
+        let mut decode_errors: Vec<(String, u64)> = self.decode_error_counts()
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        decode_errors.sort();
+        for (stream, count) in decode_errors {
+            warn!(target: logging::RESULTS, "Decode errors on {} stream: {}", stream, count);
+        }
+
+        let dropped_sends = self.dropped_send_count();
+        if dropped_sends > 0 {
+            warn!(target: logging::RESULTS, "Messages dropped after send timeout: {}", dropped_sends);
+        }
+
+        if self.is_degraded() {
+            warn!(target: logging::RESULTS, "Fail-safe still engaged: no actuation message was sent since the last send failures");
+        }
+
+        let latency = self.tick_latency();
+        if latency.ticks > 0 {
+            info!(target: logging::RESULTS, "Tick latency over {} ticks: min {:.3} ms, avg {:.3} ms, p99 {:.3} ms, max {:.3} ms",
+                  latency.ticks, latency.min_ms, latency.avg_ms, latency.p99_ms, latency.max_ms);
+        }
+
+        let mut out_of_order: Vec<(String, u64)> = self.out_of_order_counts()
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        out_of_order.sort();
+        for (stream, count) in out_of_order {
+            warn!(target: logging::RESULTS, "Out-of-order messages ignored on {} stream: {}", stream, count);
+        }
+
+        let metrics = self.tracking_metrics();
+        if metrics.data_points == 0 {
+            info!(target: logging::RESULTS, "No data points available");
+            return;
+        }
+
+        info!(target: logging::RESULTS, "Total data points: {}", metrics.data_points);
+        info!(target: logging::RESULTS, "Min error: {:.4}", metrics.min_error);
+        info!(target: logging::RESULTS, "Max error: {:.4}", metrics.max_error);
+        info!(target: logging::RESULTS, "Avg error: {:.4}", metrics.avg_error);
+        info!(target: logging::RESULTS, "RMS error: {:.4}", metrics.rms_error);
+        info!(target: logging::RESULTS, "Overshoot: {}, settling time: {:.2} s", logging::speed(metrics.overshoot), metrics.settling_time);
+        info!(target: logging::RESULTS, "Acceleration - Min: {:.4}, Max: {:.4}, Avg: {:.4}", 
+              metrics.min_acceleration, metrics.max_acceleration, metrics.avg_acceleration);
+    }
+
+    /// Whether the fail-safe has engaged after repeated actuation send failures and no actuation
+    /// message has been sent successfully since
+    pub fn is_degraded(&self) -> bool {
+        self.state.lock().unwrap().degraded
+    }
 
     // Additional helper method to get current PID status
     #[allow(dead_code)]    
     pub fn is_active(&self) -> bool {
-        let active = self.pid_active.lock().unwrap();
-        *active
+        self.state.lock().unwrap().pid_active
+    }
+
+    /// Whether cruise control is engaged (the driver-facing state, unlike the PID activity of `is_active`)
+    #[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+    pub fn is_engaged(&self) -> bool {
+        self.is_engaged.get() != 0
+    }
+
+    /// Channel receiving the new engaged state on every engage/disengage transition,
+    /// whether requested on the engage topic or triggered by a safety intervention
+    pub fn subscribe_engaged(&self) -> mpsc::Receiver<bool> {
+        self.is_engaged.subscribe()
     }
 
     // Get current state for debugging
     #[allow(dead_code)]    
     pub fn get_state(&self) -> (f64, f64, f64, bool) {
-        let current_vel = *self.current_velocity.lock().unwrap();
-        let desired_vel = *self.desired_velocity.lock().unwrap();
-        let current_time = *self.current_time.lock().unwrap();
-        let is_active = *self.pid_active.lock().unwrap();
-        
-        (current_vel, desired_vel, current_time, is_active)
+        let state = self.state.lock().unwrap();
+        (state.current_velocity, state.desired_velocity, state.current_time, state.pid_active)
+    }
+
+    /// Consistent copy of the whole control state, for tests inspecting fields without a getter
+    #[cfg(test)]
+    pub fn control_state(&self) -> ControlState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Everything a monitor needs in one consistent snapshot, taken under a single lock
+    #[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+    pub fn snapshot(&self) -> Telemetry {
+        let state = self.state.lock().unwrap();
+        Telemetry {
+            current_velocity: state.current_velocity,
+            desired_velocity: state.desired_velocity,
+            speed_limit: state.speed_limit,
+            current_time: state.current_time,
+            // Engage transitions happen under the state lock, so this matches `pid_active`
+            engaged: self.is_engaged(),
+            pid_active: state.pid_active,
+            acceleration: state.last_acceleration,
+            closest_obstacle_distance: state.last_obstacle_distance,
+            throttle: state.throttle,
+            steer: state.steer,
+            brake: state.brake,
+            regime: state.last_regime,
+            degraded: state.degraded,
+            saturated: state.last_saturated,
+            requested_acceleration: state.last_requested_acceleration,
+        }
     }
 
     // Get current control values (throttle, steer, brake)
     pub fn get_control_values(&self) -> (f64, f64, f64) {
-        let throttle = *self.throttle.lock().unwrap();
-        let steer = *self.steer.lock().unwrap();
-        let brake = *self.brake.lock().unwrap();
-        (throttle, steer, brake)
+        let state = self.state.lock().unwrap();
+        (state.throttle, state.steer, state.brake)
+    }
+
+    /// Number of out-of-order messages rejected per stream since startup
+    pub fn out_of_order_counts(&self) -> HashMap<String, u64> {
+        let mut counts = HashMap::new();
+        counts.insert("clock".to_string(), self.clock_freshness.rejected());
+        counts.insert("velocity".to_string(), self.velocity_freshness.rejected());
+        counts
+    }
+
+    /// Number of undecodable payloads received per stream since startup
+    pub fn decode_error_counts(&self) -> HashMap<String, u64> {
+        self.decode_errors.snapshot()
+    }
+
+    /// Every input stream with its URI, whether it is subscribed, and how many messages it received
+    /// when, e.g. to spot a silent lidar stream
+    #[cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+    pub fn subscription_status(&self) -> Vec<SubscriptionInfo> {
+        self.subscriptions.snapshot()
+    }
+
+    /// Check that the node is ready to engage: clock (unless the wall clock is used) and velocity produced a
+    /// valid message within the self-test timeout, every listener is registered and the PID config validates
+    pub fn self_test(&self) -> Result<(), SelfTestReport> {
+        Self::run_self_test(&self.state, &self.config, &self.subscriptions, &self.pid_config)
+    }
+
+    fn run_self_test(
+        state: &Mutex<ControlState>,
+        config: &Mutex<HandlerConfig>,
+        subscriptions: &SubscriptionTracker,
+        pid_config: &Mutex<Option<PidConfig>>,
+    ) -> Result<(), SelfTestReport> {
+        let (clock_source, timeout) = {
+            let config = config.lock().unwrap();
+            (config.clock_source, config.self_test_timeout)
+        };
+        let mut report = SelfTestReport::default();
+
+        {
+            let state = state.lock().unwrap();
+            let mut required = vec![("velocity", state.last_valid_velocity)];
+            if clock_source == ClockSource::External {
+                required.insert(0, ("clock", state.last_valid_clock));
+            }
+            for (stream, last_valid) in required {
+                if !last_valid.is_some_and(|received| received.elapsed() <= timeout) {
+                    report.stale_streams.push(stream.to_string());
+                }
+            }
+        }
+
+        report.unsubscribed_streams = subscriptions.snapshot().into_iter()
+            .filter(|info| !info.subscribed)
+            .map(|info| info.stream)
+            .collect();
+
+        report.config_error = match pid_config.lock().unwrap().as_ref() {
+            Some(pid_config) => pid_config.validate().err(),
+            None => Some("no PID config set".to_string()),
+        };
+
+        if report.passed() {
+            Ok(())
+        } else {
+            Err(report)
+        }
+    }
+
+    /// Latency of the control ticks from velocity receipt to the end of the actuation publish
+    pub fn tick_latency(&self) -> LatencySummary {
+        self.tick_latency.summary()
+    }
+
+    /// Number of outgoing messages dropped because the send timed out
+    pub fn dropped_send_count(&self) -> u64 {
+        self.dropped_sends.load(Ordering::Relaxed)
+    }
+
+    /// Send a message, giving up after `timeout` so a congested transport cannot stall the control loop
+    async fn send_with_timeout(
+        transport: &Arc<dyn UTransport>,
+        message: UMessage,
+        timeout: Duration,
+        dropped_sends: &AtomicU64,
+    ) -> Result<(), String> {
+        if timeout.is_zero() {
+            return transport.send(message).await.map_err(|e| e.to_string());
+        }
+
+        match tokio::time::timeout(timeout, transport.send(message)).await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(_) => {
+                dropped_sends.fetch_add(1, Ordering::Relaxed);
+                Err(format!("send timed out after {:?}, message dropped", timeout))
+            }
+        }
     }
 }
 
 // Listener implementations
 struct ClockListener {
-    current_time: Arc<Mutex<f64>>,
+    state: Arc<Mutex<ControlState>>,
+    config: Arc<Mutex<HandlerConfig>>,
+    decode_errors: Arc<DecodeErrorCounters>,
+    freshness: Arc<FreshnessGuard>,
+    format: LearnedFormat,
 }
 
 impl ClockListener {
-    fn new(current_time: Arc<Mutex<f64>>) -> Self {
-        Self { current_time }
+    fn new(
+        state: Arc<Mutex<ControlState>>,
+        config: Arc<Mutex<HandlerConfig>>,
+        decode_errors: Arc<DecodeErrorCounters>,
+        freshness: Arc<FreshnessGuard>,
+    ) -> Self {
+        Self { state, config, decode_errors, freshness, format: LearnedFormat::new("clock") }
+    }
+
+    fn apply(&self, time_value: f64) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.current_time = time_value;
+            state.last_valid_clock = Some(Instant::now());
+        }
+        debug!(target: logging::CLOCK, "Received current clock '{:.4}' seconds", time_value);
     }
 }
 
 #[async_trait::async_trait]
 impl UListener for ClockListener {
     async fn on_receive(&self, message: UMessage) {
+        if self.config.lock().unwrap().clock_source == ClockSource::WallClock {
+            debug!(target: logging::CLOCK, "Ignoring clock message, the wall clock is used");
+            return;
+        }
+        if !self.freshness.accept(&message) {
+            return;
+        }
         if let Some(payload) = message.payload {
-            let bytes = &payload[..];
-            
-            // Try to parse as text first (new format)
-            let time_value = if let Ok(payload_str) = std::str::from_utf8(&bytes) {
-                match payload_str.trim().parse::<f64>() {
-                    Ok(time) => time,
-                    Err(_) => {
-                        // Fall back to JSON format for backward compatibility
-                        if let Ok(clock_status) = serde_json::from_slice::<ClockStatus>(&bytes) {
-                            clock_status.time
-                        } else {
-                            error!("[ERROR] Timestamp processing failed as JSON");
-                            return;
-                        }
-                    }
-                }
-            } else {
-                error!("[ERROR] Timestamp processing failed as UTF-8");
+            // Text (new format) or JSON for backward compatibility
+            let Some(time_value) = self.format.decode(
+                &payload[..],
+                |text| text.parse::<f64>().ok(),
+                |bytes| serde_json::from_slice::<ClockStatus>(bytes).ok().map(|clock_status| clock_status.time),
+            ) else {
+                DecodeErrorCounters::increment(&self.decode_errors.clock);
+                error!(target: logging::CLOCK, "[ERROR] Timestamp processing failed as text and JSON");
+                return;
+            };
+            let Some(time_value) = finite_input("clock", time_value, &self.decode_errors.clock) else {
                 return;
             };
             
-            {
-                let mut clock = self.current_time.lock().unwrap();
-                *clock = time_value;
-            }
-            debug!("Received current clock '{:.4}' seconds", time_value);
+            self.apply(time_value);
         }
     }
 }
 
 struct VelocityListener {
-    current_velocity: Arc<Mutex<f64>>,
-    desired_velocity: Arc<Mutex<f64>>,
-    current_time: Arc<Mutex<f64>>,
-    previous_time: Arc<Mutex<f64>>,
-    pid_active: Arc<Mutex<bool>>,
+    state: Arc<Mutex<ControlState>>,
     controller: Arc<Mutex<PIDController>>,
     results: Arc<Mutex<HashMap<String, Vec<f64>>>>,
     actuation_uri: UUri,
-    transport: Arc<UPTransportZenoh>,
-    latest_lidar_data: Arc<Mutex<Option<LidarMeasurement>>>,
-    is_engaged: Arc<Mutex<u8>>,
+    transport: Arc<dyn UTransport>,
+    is_engaged: Arc<EngagedState>,
     engage_uri: UUri,
-    throttle: Arc<Mutex<f64>>,
-    steer: Arc<Mutex<f64>>,
-    brake: Arc<Mutex<f64>>,
+    at_target_uri: UUri,
+    at_speed_uri: UUri,
+    regime_uri: UUri,
+    decode_errors: Arc<DecodeErrorCounters>,
+    config: Arc<Mutex<HandlerConfig>>,
+    pedal_command_uri: UUri,
+    dropped_sends: Arc<AtomicU64>,
+    freshness: Arc<FreshnessGuard>,
+    latency: Arc<TickLatency>,
+    control_trigger: Arc<ControlTrigger>,
+    #[cfg(all(test, feature = "test-fault-injection"))]
+    faults: Arc<FaultInjector>,
+    format: LearnedFormat,
 }
 
 impl VelocityListener {
     fn new(
-        current_velocity: Arc<Mutex<f64>>,
-        desired_velocity: Arc<Mutex<f64>>,
-        current_time: Arc<Mutex<f64>>,
-        previous_time: Arc<Mutex<f64>>,
-        pid_active: Arc<Mutex<bool>>,
+        state: Arc<Mutex<ControlState>>,
         controller: Arc<Mutex<PIDController>>,
         results: Arc<Mutex<HashMap<String, Vec<f64>>>>,
         actuation_uri: UUri,
-        transport: Arc<UPTransportZenoh>,
-        latest_lidar_data: Arc<Mutex<Option<LidarMeasurement>>>,
-        is_engaged: Arc<Mutex<u8>>,
+        transport: Arc<dyn UTransport>,
+        is_engaged: Arc<EngagedState>,
         engage_uri: UUri,
-        throttle: Arc<Mutex<f64>>,
-        steer: Arc<Mutex<f64>>,
-        brake: Arc<Mutex<f64>>,
+        at_target_uri: UUri,
+        at_speed_uri: UUri,
+        regime_uri: UUri,
+        decode_errors: Arc<DecodeErrorCounters>,
+        config: Arc<Mutex<HandlerConfig>>,
+        pedal_command_uri: UUri,
+        dropped_sends: Arc<AtomicU64>,
+        freshness: Arc<FreshnessGuard>,
+        latency: Arc<TickLatency>,
+        control_trigger: Arc<ControlTrigger>,
+        #[cfg(all(test, feature = "test-fault-injection"))]
+        faults: Arc<FaultInjector>,
     ) -> Self {
         Self {
-            current_velocity,
-            desired_velocity,
-            current_time,
-            previous_time,
-            pid_active,
+            state,
             controller,
             results,
             actuation_uri,
             transport,
-            latest_lidar_data,
             is_engaged,
             engage_uri,
-            throttle,
-            steer,
-            brake,
+            at_target_uri,
+            at_speed_uri,
+            regime_uri,
+            decode_errors,
+            config,
+            pedal_command_uri,
+            dropped_sends,
+            freshness,
+            latency,
+            control_trigger,
+            #[cfg(all(test, feature = "test-fault-injection"))]
+            faults,
+            format: LearnedFormat::new("velocity"),
+        }
+    }
+
+    async fn apply(&self, velocity_value: f64, received: Instant) {
+        let (decimation_factor, clock_source, control_loop) = {
+            let config = self.config.lock().unwrap();
+            (config.decimation_factor.max(1) as u64, config.clock_source, config.control_loop)
+        };
+        let wall_time = match clock_source {
+            ClockSource::WallClock => Some(self.controller.lock().unwrap().wall_time().as_secs_f64()),
+            ClockSource::External => None,
+        };
+        let tick_due = {
+            let mut state = self.state.lock().unwrap();
+            state.current_velocity = velocity_value;
+            state.last_valid_velocity = Some(received);
+            if let Some(wall_time) = wall_time {
+                state.current_time = wall_time;
+            }
+            let due = state.velocity_samples % decimation_factor == 0;
+            state.velocity_samples += 1;
+            due
+        };
+        debug!(target: logging::VELOCITY, "Received current velocity '{}'", logging::speed(velocity_value));
+
+        if !tick_due {
+            return;
+        }
+
+        if control_loop == ControlLoop::Async && self.control_trigger.running.load(Ordering::Relaxed) {
+            self.control_trigger.trigger(received);
+        } else {
+            self.run_tick(received).await;
+        }
+    }
+
+    /// PID computation and actuation for the current state
+    async fn run_tick(&self, received: Instant) {
+        UProtocolHandler::publish_acc(
+            &self.state,
+            &self.controller,
+            &self.transport,
+            self.actuation_uri.clone(),
+            &self.results,
+            &self.is_engaged,
+            &self.engage_uri,
+            &self.at_target_uri,
+            &self.at_speed_uri,
+            &self.regime_uri,
+            &self.config,
+            &self.pedal_command_uri,
+            &self.dropped_sends,
+        ).await;
+
+        let latency = received.elapsed();
+        self.latency.record(latency);
+        let tick_budget = self.config.lock().unwrap().tick_budget;
+        if !tick_budget.is_zero() && latency > tick_budget {
+            warn!(target: logging::CONTROL, "SLOW TICK: Velocity to actuation took {:?}, budget is {:?}", latency, tick_budget);
         }
     }
 }
@@ -721,63 +2846,77 @@ impl VelocityListener {
 #[async_trait::async_trait]
 impl UListener for VelocityListener {
     async fn on_receive(&self, message: UMessage) {
+        #[cfg(all(test, feature = "test-fault-injection"))]
+        for message in self.faults.apply(message).await {
+            self.receive(message).await;
+        }
+        #[cfg(not(all(test, feature = "test-fault-injection")))]
+        self.receive(message).await;
+    }
+}
+
+impl VelocityListener {
+    async fn receive(&self, message: UMessage) {
+        let received = Instant::now();
+        if !self.freshness.accept(&message) {
+            return;
+        }
         if let Some(payload) = message.payload {
-            let bytes = &payload[..];
-            
-            // Try to parse as text first (new format)
-            let velocity_value = if let Ok(payload_str) = std::str::from_utf8(&bytes) {
-                match payload_str.trim().parse::<f64>() {
-                    Ok(velocity) => velocity,
-                    Err(_) => {
-                        // Fall back to JSON format for backward compatibility
-                        if let Ok(velocity_status) = serde_json::from_slice::<VelocityStatus>(&bytes) {
-                            velocity_status.velocity
-                        } else {
-                            error!("Failed to parse velocity payload");
-                            return;
-                        }
-                    }
-                }
-            } else {
-                error!("Failed to parse velocity payload as UTF-8");
+            // Text (new format) or JSON for backward compatibility
+            let Some(velocity_value) = self.format.decode(
+                &payload[..],
+                |text| text.parse::<f64>().ok(),
+                |bytes| serde_json::from_slice::<VelocityStatus>(bytes).ok().map(|velocity_status| velocity_status.velocity),
+            ) else {
+                DecodeErrorCounters::increment(&self.decode_errors.velocity);
+                error!(target: logging::VELOCITY, "Failed to parse velocity payload");
+                return;
+            };
+            let Some(velocity_value) = finite_input("velocity", velocity_value, &self.decode_errors.velocity) else {
                 return;
             };
             
-            {
-                let mut vel = self.current_velocity.lock().unwrap();
-                *vel = velocity_value;
-            }
-            debug!("Received current velocity '{:.2}'", velocity_value);
-            
-            // Trigger PID computation
-            UProtocolHandler::publish_acc(
-                &self.desired_velocity,
-                &self.current_velocity,
-                &self.current_time,
-                &self.previous_time,
-                &self.pid_active,
-                &self.controller,
-                &self.transport,
-                self.actuation_uri.clone(),
-                &self.results,
-                &self.latest_lidar_data,
-                &self.is_engaged,
-                &self.engage_uri,
-                &self.throttle,
-                &self.steer,
-                &self.brake,
-            ).await;
+            self.apply(velocity_value, received).await;
         }
     }
 }
 
 struct TargetSpeedListener {
-    desired_velocity: Arc<Mutex<f64>>,
+    state: Arc<Mutex<ControlState>>,
+    config: Arc<Mutex<HandlerConfig>>,
+    decode_errors: Arc<DecodeErrorCounters>,
+    format: LearnedFormat,
 }
 
 impl TargetSpeedListener {
-    fn new(desired_velocity: Arc<Mutex<f64>>) -> Self {
-        Self { desired_velocity }
+    fn new(state: Arc<Mutex<ControlState>>, config: Arc<Mutex<HandlerConfig>>, decode_errors: Arc<DecodeErrorCounters>) -> Self {
+        Self { state, config, decode_errors, format: LearnedFormat::new("target speed") }
+    }
+
+    fn apply(&self, speed_value: f64) {
+        let (policy, slew_rate) = {
+            let config = self.config.lock().unwrap();
+            (config.zero_target_policy, config.target_slew_rate)
+        };
+        let mut state = self.state.lock().unwrap();
+        if speed_value == 0.0 && state.pid_active {
+            match policy {
+                ZeroTargetPolicy::BrakeToStop => {}
+                ZeroTargetPolicy::Disengage => state.zero_target_disengage = true,
+                ZeroTargetPolicy::Ignore => {
+                    info!(target: logging::TARGET, "ZERO TARGET: Ignored while engaged, keeping desired velocity {:.2}", state.desired_velocity);
+                    return;
+                }
+            }
+        }
+        info!(target: logging::TARGET, "Received desired velocity '{}'", logging::speed(speed_value));
+        // While engaged the control ticks slew the stored target towards the new one
+        if slew_rate > 0.0 && state.pid_active && speed_value != state.desired_velocity {
+            state.requested_velocity = Some(speed_value);
+        } else {
+            state.requested_velocity = None;
+            state.desired_velocity = speed_value;
+        }
     }
 }
 
@@ -785,48 +2924,141 @@ impl TargetSpeedListener {
 impl UListener for TargetSpeedListener {
     async fn on_receive(&self, message: UMessage) {
         if let Some(payload) = message.payload {
-            let bytes = &payload[..];
-            
-            let speed_value = if let Ok(target_speed) = serde_json::from_slice::<TargetSpeed>(&bytes) {
-                target_speed.speed
-            } else if let Ok(payload_str) = std::str::from_utf8(&bytes) {
-                match payload_str.trim().parse::<f64>() {
-                    Ok(speed) => speed,
-                    Err(_) => {
-                        error!("Failed to parse target speed: {}", payload_str);
-                        return;
-                    }
-                }
-            } else {
-                error!("Failed to parse target speed payload");
+            let Some(speed_value) = parse_speed_payload(&self.format, &payload[..]) else {
+                DecodeErrorCounters::increment(&self.decode_errors.target_speed);
+                error!(target: logging::TARGET, "Failed to parse target speed: {}", String::from_utf8_lossy(&payload[..]));
+                return;
+            };
+            let Some(speed_value) = finite_input("target speed", speed_value, &self.decode_errors.target_speed) else {
                 return;
             };
             
-            {
-                let mut vel = self.desired_velocity.lock().unwrap();
-                *vel = speed_value;
-            }
-            info!("Received desired velocity '{:.2}'", speed_value);
+            self.apply(speed_value);
         }
     }
 }
 
-struct EngageListener {
-    is_engaged: Arc<Mutex<u8>>,
-    pid_active: Arc<Mutex<bool>>,
+/// Reject a NaN/Inf input value (`NaN` and `inf` parse as f64), counting it as a decode error of its stream
+fn finite_input(input: &str, value: f64, decode_errors: &AtomicU64) -> Option<f64> {
+    if value.is_finite() {
+        return Some(value);
+    }
+    DecodeErrorCounters::increment(decode_errors);
+    error!(target: logging::MESSAGES, "Rejected non-finite {} value {}", input, value);
+    None
+}
+
+/// Parse a speed given as text (`70.0`) or JSON (`{"speed": 70.0}`)
+fn parse_speed_payload(format: &LearnedFormat, bytes: &[u8]) -> Option<f64> {
+    format.decode(
+        bytes,
+        |text| text.parse::<f64>().ok(),
+        |bytes| serde_json::from_slice::<TargetSpeed>(bytes).ok().map(|target_speed| target_speed.speed),
+    )
+}
+
+struct SpeedLimitListener {
+    state: Arc<Mutex<ControlState>>,
+    decode_errors: Arc<DecodeErrorCounters>,
+    format: LearnedFormat,
+}
+
+impl SpeedLimitListener {
+    fn new(state: Arc<Mutex<ControlState>>, decode_errors: Arc<DecodeErrorCounters>) -> Self {
+        Self { state, decode_errors, format: LearnedFormat::new("speed limit") }
+    }
+}
+
+#[async_trait::async_trait]
+impl UListener for SpeedLimitListener {
+    async fn on_receive(&self, message: UMessage) {
+        if let Some(payload) = message.payload {
+            let Some(limit) = parse_speed_payload(&self.format, &payload[..]) else {
+                DecodeErrorCounters::increment(&self.decode_errors.speed_limit);
+                error!(target: logging::TARGET, "Failed to parse speed limit: {}", String::from_utf8_lossy(&payload[..]));
+                return;
+            };
+            let Some(limit) = finite_input("speed limit", limit, &self.decode_errors.speed_limit) else {
+                return;
+            };
+
+            self.state.lock().unwrap().speed_limit = Some(limit);
+            info!(target: logging::TARGET, "Received speed limit '{}'", logging::speed(limit));
+        }
+    }
+}
+
+struct EngageListener {
+    is_engaged: Arc<EngagedState>,
+    state: Arc<Mutex<ControlState>>,
     controller: Arc<Mutex<PIDController>>,
+    config: Arc<Mutex<HandlerConfig>>,
+    decode_errors: Arc<DecodeErrorCounters>,
+    subscriptions: Arc<SubscriptionTracker>,
+    pid_config: Arc<Mutex<Option<PidConfig>>>,
+    format: LearnedFormat,
 }
 
 impl EngageListener {
     fn new(
-        is_engaged: Arc<Mutex<u8>>,
-        pid_active: Arc<Mutex<bool>>,
+        is_engaged: Arc<EngagedState>,
+        state: Arc<Mutex<ControlState>>,
         controller: Arc<Mutex<PIDController>>,
+        config: Arc<Mutex<HandlerConfig>>,
+        decode_errors: Arc<DecodeErrorCounters>,
+        subscriptions: Arc<SubscriptionTracker>,
+        pid_config: Arc<Mutex<Option<PidConfig>>>,
     ) -> Self {
         Self {
             is_engaged,
-            pid_active,
+            state,
             controller,
+            config,
+            decode_errors,
+            subscriptions,
+            pid_config,
+            format: LearnedFormat::new("engage"),
+        }
+    }
+
+    fn apply(&self, engaged_value: u8, target: Option<f64>) {
+        // Leave the engage state untouched so the HMI can retry once the node is ready
+        let activating = engaged_value != 0 && !self.state.lock().unwrap().pid_active;
+        if activating && self.config.lock().unwrap().require_self_test {
+            if let Err(report) = UProtocolHandler::run_self_test(&self.state, &self.config, &self.subscriptions, &self.pid_config) {
+                warn!(target: logging::ENGAGE, "ENGAGE REJECTED: Self-test failed: {}", report);
+                return;
+            }
+        }
+
+        // Target, engage state and activation switch under one lock, so neither the PID nor a
+        // concurrent engage message sees a half-switched state
+        let enable = engaged_value != 0;
+        let switched = {
+            let mut state = self.state.lock().unwrap();
+            if let Some(target) = target {
+                state.desired_velocity = target;
+                state.requested_velocity = None;
+            }
+            self.is_engaged.set(engaged_value);
+            if enable && !state.pid_active {
+                UProtocolHandler::activate_state(&mut state);
+                Some("ACTIVATED")
+            } else if !enable && state.pid_active {
+                state.pid_active = false;
+                Some("DEACTIVATED")
+            } else {
+                None
+            }
+        };
+        
+        match target {
+            Some(target) => info!(target: logging::ENGAGE, "Received engage status: {} with target speed '{}'", engaged_value, logging::speed(target)),
+            None => info!(target: logging::ENGAGE, "Received engage status: {}", engaged_value),
+        }
+        
+        if let Some(switch) = switched {
+            UProtocolHandler::reset_controller(&self.controller, switch);
         }
     }
 }
@@ -835,63 +3067,108 @@ impl EngageListener {
 impl UListener for EngageListener {
     async fn on_receive(&self, message: UMessage) {
         if let Some(payload) = message.payload {
-            let bytes = &payload[..];
-            
-            // Try to parse as text first (new format)
-            let engaged_value = if let Ok(payload_str) = std::str::from_utf8(&bytes) {
-                match payload_str.trim().parse::<u8>() {
-                    Ok(engaged) => engaged,
-                    Err(_) => {
-                        // Fall back to JSON format for backward compatibility
-                        if let Ok(engage_status) = serde_json::from_slice::<EngageStatus>(&bytes) {
-                            engage_status.engaged
-                        } else {
-                            error!("Failed to parse engage status payload");
-                            return;
-                        }
-                    }
-                }
-            } else {
-                error!("Failed to parse engage status payload as UTF-8");
+            // Text (new format) or JSON, optionally carrying the target speed
+            let Some((engaged_value, target)) = self.format.decode(
+                &payload[..],
+                |text| text.parse::<u8>().ok().map(|engaged| (engaged, None)),
+                |bytes| serde_json::from_slice::<EngageStatus>(bytes).ok()
+                    .map(|engage_status| (engage_status.engaged, engage_status.target)),
+            ) else {
+                DecodeErrorCounters::increment(&self.decode_errors.engage);
+                error!(target: logging::ENGAGE, "Failed to parse engage status payload");
                 return;
             };
-            
-            let _was_engaged;
-            {
-                let mut engaged_state = self.is_engaged.lock().unwrap();
-                _was_engaged = *engaged_state;
-                *engaged_state = engaged_value;
+
+            // 0 disengages and 1 engages; anything else is most likely a producer bug
+            if engaged_value > 1 && !self.config.lock().unwrap().multi_state_engage {
+                DecodeErrorCounters::increment(&self.decode_errors.engage);
+                warn!(target: logging::ENGAGE, "Rejected engage status {}: only 0 and 1 are valid, engage state unchanged", engaged_value);
+                return;
             }
             
-            info!("Received engage status: {}", engaged_value);
-            
-            // Handle activation/deactivation
-            let enable = engaged_value != 0;
-            let was_active = {
-                let active = self.pid_active.lock().unwrap();
-                *active
+            self.apply(engaged_value, target);
+        }
+    }
+}
+
+struct FrictionListener {
+    controller: Arc<Mutex<PIDController>>,
+    decode_errors: Arc<DecodeErrorCounters>,
+    format: LearnedFormat,
+}
+
+impl FrictionListener {
+    fn new(controller: Arc<Mutex<PIDController>>, decode_errors: Arc<DecodeErrorCounters>) -> Self {
+        Self { controller, decode_errors, format: LearnedFormat::new("friction") }
+    }
+}
+
+#[async_trait::async_trait]
+impl UListener for FrictionListener {
+    async fn on_receive(&self, message: UMessage) {
+        if let Some(payload) = message.payload {
+            // Text (`0.4`) or JSON (`{"friction": 0.4}`)
+            let Some(friction) = self.format.decode(
+                &payload[..],
+                |text| text.parse::<f64>().ok(),
+                |bytes| serde_json::from_slice::<FrictionEstimate>(bytes).ok().map(|estimate| estimate.friction),
+            ) else {
+                DecodeErrorCounters::increment(&self.decode_errors.friction);
+                error!(target: logging::EMERGENCY, "Failed to parse friction estimate: {}", String::from_utf8_lossy(&payload[..]));
+                return;
             };
-            
-            if enable && !was_active {
-                UProtocolHandler::activate_pid(&self.pid_active, &self.controller);
-            } else if !enable && was_active {
-                UProtocolHandler::deactivate_pid(&self.pid_active, &self.controller);
+            let Some(friction) = finite_input("friction", friction, &self.decode_errors.friction) else {
+                return;
+            };
+            if friction <= 0.0 {
+                DecodeErrorCounters::increment(&self.decode_errors.friction);
+                error!(target: logging::EMERGENCY, "Rejected non-positive friction estimate {}", friction);
+                return;
             }
+
+            let mut pid = self.controller.lock().unwrap();
+            pid.set_friction_coefficient(friction);
+            info!(target: logging::EMERGENCY, "Received friction estimate '{:.2}', braking scaled by {:.2}", friction, pid.get_friction_coefficient());
         }
     }
 }
 
 // Lidar Listener struct
 struct LidarListener {
-    latest_lidar_data: Arc<Mutex<Option<LidarMeasurement>>>,
+    state: Arc<Mutex<ControlState>>,
+    decode_errors: Arc<DecodeErrorCounters>,
 }
 
 impl LidarListener {
-    fn new(latest_lidar_data: Arc<Mutex<Option<LidarMeasurement>>>) -> Self {
+    fn new(state: Arc<Mutex<ControlState>>, decode_errors: Arc<DecodeErrorCounters>) -> Self {
         Self {
-            latest_lidar_data,
+            state,
+            decode_errors,
         }
     }
+
+    fn apply(&self, mut lidar_measurement: LidarMeasurement) {
+        // Points with NaN/Inf coordinates are dropped, the rest of the scan is still usable
+        let total = lidar_measurement.detections.len();
+        lidar_measurement.detections.retain(|detection| detection.point.is_finite());
+        let dropped = total - lidar_measurement.detections.len();
+        if dropped > 0 {
+            DecodeErrorCounters::increment(&self.decode_errors.lidar);
+            error!(target: logging::LIDAR, "Dropped {} of {} lidar detections with non-finite coordinates", dropped, total);
+            lidar_measurement.len = lidar_measurement.detections.len() as u32;
+            lidar_measurement.is_empty = lidar_measurement.detections.is_empty();
+        }
+
+        // Optional: Print some sample detections for debugging
+        debug!(target: logging::LIDAR, "First few lidar detections (if any):");
+        for (i, detection) in lidar_measurement.detections.iter().take(3).enumerate() {
+            debug!(target: logging::LIDAR, "  Detection {}: x={:.2}, y={:.2}, z={:.2}, intensity={:.3}", 
+                   i, detection.point.x, detection.point.y, detection.point.z, detection.intensity);
+        }
+
+        // Store the latest lidar data
+        self.state.lock().unwrap().latest_lidar_data = Some(Arc::new(lidar_measurement));
+    }
 }
 
 #[async_trait::async_trait]
@@ -902,57 +3179,65 @@ impl UListener for LidarListener {
             
             // First, let's see what the JSON actually looks like
             if let Ok(json_str) = std::str::from_utf8(&bytes) {
-                debug!("Raw lidar JSON: {}", json_str.chars().take(500).collect::<String>());
+                debug!(target: logging::LIDAR, "Raw lidar JSON: {}", json_str.chars().take(500).collect::<String>());
                 
-                // Try to parse as our expected structure first
-                match serde_json::from_slice::<LidarMeasurement>(&bytes) {
-                    Ok(lidar_measurement) => {
-                        let detection_count = lidar_measurement.detections.len();                        
-                        // Store the latest lidar data
-                        {
-                            let mut lidar_data = self.latest_lidar_data.lock().unwrap();
-                            *lidar_data = Some(lidar_measurement);
-                        }
-                        
-                        // Optional: Print some sample detections for debugging
-                        debug!("First few lidar detections (if any):");
-                        if let Ok(lidar_data) = serde_json::from_slice::<LidarMeasurement>(&bytes) {
-                            for (i, detection) in lidar_data.detections.iter().take(3).enumerate() {
-                                debug!("  Detection {}: x={:.2}, y={:.2}, z={:.2}, intensity={:.3}", 
-                                       i, detection.point.x, detection.point.y, detection.point.z, detection.intensity);
-                            }
-                        }
-                    }
+                // Try to parse as our expected structure first, then as a flat CARLA raw_data array
+                match LidarMeasurement::from_json(&bytes) {
+                    Ok(lidar_measurement) => self.apply(lidar_measurement),
                     Err(e) => {
+                        DecodeErrorCounters::increment(&self.decode_errors.lidar);
                         // Try to parse as a generic JSON value to understand the structure
                         match serde_json::from_slice::<serde_json::Value>(&bytes) {
                             Ok(json_value) => {
-                                error!("Failed to parse as LidarMeasurement: {}. Structure: {:?}", 
+                                error!(target: logging::LIDAR, "Failed to parse as LidarMeasurement: {}. Structure: {:?}", 
                                        e, json_value.as_object().map(|obj| obj.keys().collect::<Vec<_>>()));
-                                debug!("Sample JSON structure: {}", serde_json::to_string_pretty(&json_value).unwrap_or_else(|_| "Could not pretty print".to_string()).chars().take(1000).collect::<String>());
+                                debug!(target: logging::LIDAR, "Sample JSON structure: {}", serde_json::to_string_pretty(&json_value).unwrap_or_else(|_| "Could not pretty print".to_string()).chars().take(1000).collect::<String>());
                             }
                             Err(_) => {
-                                error!("Failed to parse lidar measurement: {}", e);
+                                error!(target: logging::LIDAR, "Failed to parse lidar measurement: {}", e);
                             }
                         }
                     }
                 }
             } else {
-                error!("Lidar payload is not valid UTF-8");
+                DecodeErrorCounters::increment(&self.decode_errors.lidar);
+                error!(target: logging::LIDAR, "Lidar payload is not valid UTF-8");
             }
         }
     }
 }
 
 struct ControlValuesListener {
-    throttle: Arc<Mutex<f64>>,
-    steer: Arc<Mutex<f64>>,
-    brake: Arc<Mutex<f64>>,
+    state: Arc<Mutex<ControlState>>,
+    config: Arc<Mutex<HandlerConfig>>,
+    decode_errors: Arc<DecodeErrorCounters>,
 }
 
 impl ControlValuesListener {
-    fn new(throttle: Arc<Mutex<f64>>, steer: Arc<Mutex<f64>>, brake: Arc<Mutex<f64>>) -> Self {
-        Self { throttle, steer, brake }
+    fn new(
+        state: Arc<Mutex<ControlState>>,
+        config: Arc<Mutex<HandlerConfig>>,
+        decode_errors: Arc<DecodeErrorCounters>,
+    ) -> Self {
+        Self { state, config, decode_errors }
+    }
+
+    /// Apply the configured range policy; None means the message is rejected
+    fn accept(&self, control: ControlValues) -> Option<ControlValues> {
+        match control.validate() {
+            Ok(()) => Some(control),
+            Err(e) => {
+                let clamp = self.config.lock().unwrap().clamp_control_values;
+                let finite = control.throttle.is_finite() && control.steer.is_finite() && control.brake.is_finite();
+                if clamp && finite {
+                    warn!(target: logging::CONTROL, "Clamping control values: {}", e);
+                    Some(control.clamped())
+                } else {
+                    error!(target: logging::CONTROL, "Rejected control values, keeping last good values: {}", e);
+                    None
+                }
+            }
+        }
     }
 }
 
@@ -963,15 +3248,1505 @@ impl UListener for ControlValuesListener {
             let bytes = &payload[..];
             match serde_json::from_slice::<ControlValues>(bytes) {
                 Ok(control) => {
-                    *self.throttle.lock().unwrap() = control.throttle;
-                    *self.steer.lock().unwrap() = control.steer;
-                    *self.brake.lock().unwrap() = control.brake;
-                    info!("Received control values: throttle={:.3}, steer={:.3}, brake={:.3}", control.throttle, control.steer, control.brake);
+                    let Some(control) = self.accept(control) else {
+                        DecodeErrorCounters::increment(&self.decode_errors.control_values);
+                        return;
+                    };
+                    {
+                        let mut state = self.state.lock().unwrap();
+                        state.throttle = control.throttle;
+                        state.steer = control.steer;
+                        state.brake = control.brake;
+                    }
+                    info!(target: logging::CONTROL, "Received control values: throttle={:.3}, steer={:.3}, brake={:.3}", control.throttle, control.steer, control.brake);
                 },
                 Err(e) => {
-                    error!("Failed to parse control values JSON: {}", e);
+                    DecodeErrorCounters::increment(&self.decode_errors.control_values);
+                    error!(target: logging::CONTROL, "Failed to parse control values JSON: {}", e);
+                }
+            }
+        }
+    }
+}
+
+// Operator command listener, replies on the command status resource
+struct CommandListener {
+    controller: Arc<Mutex<PIDController>>,
+    results: Arc<Mutex<HashMap<String, Vec<f64>>>>,
+    config: Arc<Mutex<HandlerConfig>>,
+    run_id: u128,
+    transport: Arc<dyn UTransport>,
+    status_uri: UUri,
+    dropped_sends: Arc<AtomicU64>,
+    decode_errors: Arc<DecodeErrorCounters>,
+}
+
+impl CommandListener {
+    fn execute(&self, command: ControlCommand) -> Result<(), String> {
+        match command {
+            ControlCommand::Reset => self.controller.lock().unwrap().reset(),
+            ControlCommand::ClearResults => UProtocolHandler::clear_result_series(&self.results),
+            ControlCommand::StoreResults => UProtocolHandler::write_results(&self.results, &self.controller, &self.config, self.run_id, RESULTS_DIR)?,
+            ControlCommand::Suspend => self.controller.lock().unwrap().suspend_cruise_control(),
+            ControlCommand::Resume => self.controller.lock().unwrap().resume_cruise_control(),
+            ControlCommand::ApplyPreset { preset } => self.controller.lock().unwrap().apply_preset(preset),
+        }
+        Ok(())
+    }
+
+    async fn reply(&self, status: CommandStatus) {
+        let payload = serde_json::to_string(&status).unwrap();
+        let message = UMessageBuilder::publish(self.status_uri.clone())
+            .build_with_payload(payload, UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+            .expect("Failed to build command status message");
+        let send_timeout = self.config.lock().unwrap().send_timeout;
+
+        if let Err(e) = UProtocolHandler::send_with_timeout(&self.transport, message, send_timeout, &self.dropped_sends).await {
+            error!(target: logging::MESSAGES, "Failed to publish command status: {}", e);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl UListener for CommandListener {
+    async fn on_receive(&self, message: UMessage) {
+        if let Some(payload) = message.payload {
+            let command = match serde_json::from_slice::<ControlCommand>(&payload) {
+                Ok(command) => command,
+                Err(e) => {
+                    DecodeErrorCounters::increment(&self.decode_errors.command);
+                    error!(target: logging::MESSAGES, "Failed to parse command: {}", e);
+                    self.reply(CommandStatus { command: None, status: "error", message: Some(e.to_string()) }).await;
+                    return;
                 }
+            };
+
+            info!(target: logging::MESSAGES, "COMMAND: Executing {:?}", command);
+            let status = match self.execute(command) {
+                Ok(()) => CommandStatus { command: Some(command), status: "ok", message: None },
+                Err(e) => CommandStatus { command: Some(command), status: "error", message: Some(e) },
+            };
+            self.reply(status).await;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use up_rust::{UCode, UStatus};
+
+    /// In-memory transport: records every sent message and hands delivered messages to the
+    /// listeners registered for their URI. Sends fail while `set_failing(true)` is in effect
+    /// and never complete while `set_stalled(true)` is in effect.
+    #[derive(Default)]
+    pub(crate) struct MockTransport {
+        sent: Mutex<Vec<UMessage>>,
+        listeners: Mutex<Vec<(UUri, Arc<dyn UListener>)>>,
+        failing: AtomicBool,
+        stalled: AtomicBool,
+    }
+
+    impl MockTransport {
+        pub(crate) fn set_failing(&self, failing: bool) {
+            self.failing.store(failing, Ordering::Relaxed);
+        }
+
+        pub(crate) fn set_stalled(&self, stalled: bool) {
+            self.stalled.store(stalled, Ordering::Relaxed);
+        }
+
+        /// Payloads of the messages sent to `uri`, oldest first
+        pub(crate) fn sent_to(&self, uri: &UUri) -> Vec<String> {
+            self.sent.lock().unwrap().iter()
+                .filter(|message| message.source() == Some(uri))
+                .map(|message| String::from_utf8_lossy(message.payload.as_deref().unwrap_or_default()).into_owned())
+                .collect()
+        }
+
+        /// Payload formats of the messages sent to `uri`, oldest first
+        pub(crate) fn formats_sent_to(&self, uri: &UUri) -> Vec<Option<UPayloadFormat>> {
+            self.sent.lock().unwrap().iter()
+                .filter(|message| message.source() == Some(uri))
+                .map(UMessage::payload_format)
+                .collect()
+        }
+
+        /// Publish `payload` on `uri` to every listener registered for it
+        pub(crate) async fn deliver(&self, uri: &UUri, payload: &str) {
+            self.deliver_with_id(uri, payload, UUID::build()).await;
+        }
+
+        /// Like `deliver`, with the message id (and therefore creation time) given by the caller
+        pub(crate) async fn deliver_with_id(&self, uri: &UUri, payload: &str, id: UUID) {
+            let listeners: Vec<Arc<dyn UListener>> = self.listeners.lock().unwrap().iter()
+                .filter(|(filter, _)| filter == uri)
+                .map(|(_, listener)| Arc::clone(listener))
+                .collect();
+            assert!(!listeners.is_empty(), "no listener registered for {}", uri.to_uri(false));
+            for listener in listeners {
+                let message = UMessageBuilder::publish(uri.clone())
+                    .with_message_id(id.clone())
+                    .build_with_payload(payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+                    .unwrap();
+                listener.on_receive(message).await;
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl UTransport for MockTransport {
+        async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+            if self.stalled.load(Ordering::Relaxed) {
+                std::future::pending::<()>().await;
+            }
+            if self.failing.load(Ordering::Relaxed) {
+                return Err(UStatus::fail_with_code(UCode::UNAVAILABLE, "mock transport is failing"));
+            }
+            self.sent.lock().unwrap().push(message);
+            Ok(())
+        }
+
+        async fn register_listener(
+            &self,
+            source_filter: &UUri,
+            _sink_filter: Option<&UUri>,
+            listener: Arc<dyn UListener>,
+        ) -> Result<(), UStatus> {
+            self.listeners.lock().unwrap().push((source_filter.clone(), listener));
+            Ok(())
+        }
+    }
+
+    pub(crate) fn uri(authority: &str, resource_id: u16) -> UUri {
+        resources::uri(authority, resource_id).unwrap()
+    }
+
+    /// Handler on a mock transport, with the listeners registered as `start` does
+    /// (without its background tasks and config file)
+    pub(crate) async fn mock_handler(controller: PIDController) -> (UProtocolHandler, Arc<MockTransport>) {
+        let transport = Arc::new(MockTransport::default());
+        let handler = UProtocolHandler::with_transport(controller, transport.clone()).unwrap();
+        handler.setup_clock_subscriber().await.unwrap();
+        handler.setup_velocity_subscriber().await.unwrap();
+        handler.setup_target_subscriber().await.unwrap();
+        handler.setup_speed_limit_subscriber().await.unwrap();
+        handler.setup_engage_subscriber().await.unwrap();
+        handler.setup_lidar_subscriber().await.unwrap();
+        handler.setup_friction_subscriber().await.unwrap();
+        handler.setup_control_values_subscriber().await.unwrap();
+        handler.setup_command_subscriber().await.unwrap();
+        (handler, transport)
+    }
+
+    /// Deliver a clock and a velocity message, i.e. one control tick
+    pub(crate) async fn tick(transport: &MockTransport, time: f64, velocity: f64) {
+        transport.deliver(&uri(resources::VEHICLE, resources::CLOCK), &time.to_string()).await;
+        transport.deliver(&uri(resources::VEHICLE, resources::VELOCITY), &velocity.to_string()).await;
+    }
+
+    pub(crate) fn actuations(transport: &MockTransport) -> Vec<f64> {
+        transport.sent_to(&uri(resources::CRUISE_CONTROL, resources::ACTUATION)).iter()
+            .map(|payload| payload.parse().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn velocity_message_produces_actuation() {
+        let (_handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+
+        tick(&transport, 1.0, 10.0).await;
+        tick(&transport, 1.1, 10.0).await;
+
+        let actuations = actuations(&transport);
+        assert_eq!(actuations.len(), 2);
+        assert_eq!(actuations[0], 0.0, "the first tick has no time step yet");
+        assert!(actuations[1] > 0.0, "10 m/s below the target must accelerate, got {}", actuations[1]);
+    }
+
+    #[tokio::test]
+    async fn no_actuation_while_disengaged() {
+        let (_handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+
+        tick(&transport, 1.0, 10.0).await;
+        tick(&transport, 1.1, 10.0).await;
+
+        assert!(actuations(&transport).is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_actuation_sends_engage_fail_safe() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_fail_safe(3, -3.0);
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        tick(&transport, 1.0, 10.0).await;
+
+        transport.set_failing(true);
+        tick(&transport, 1.1, 10.0).await;
+        tick(&transport, 1.2, 10.0).await;
+        assert!(!handler.is_degraded(), "two failed sends are below the threshold");
+        assert!(handler.is_active());
+
+        tick(&transport, 1.3, 10.0).await;
+        assert!(handler.is_degraded());
+        assert!(!handler.is_active());
+        assert!(!handler.is_engaged());
+
+        // The next successful actuation send leaves the degraded state
+        transport.set_failing(false);
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        tick(&transport, 1.4, 10.0).await;
+        assert!(!handler.is_degraded());
+    }
+
+    /// Engaged handler with a target of 20 m/s after two ticks at 10 m/s
+    async fn engaged_handler() -> (UProtocolHandler, Arc<MockTransport>) {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        tick(&transport, 1.0, 10.0).await;
+        tick(&transport, 1.1, 10.0).await;
+        (handler, transport)
+    }
+
+    #[tokio::test]
+    async fn non_finite_clock_is_rejected() {
+        let (handler, transport) = engaged_handler().await;
+        transport.deliver(&uri(resources::VEHICLE, resources::CLOCK), "NaN").await;
+        assert_eq!(handler.control_state().current_time, 1.1);
+        assert_eq!(handler.decode_error_counts()["clock"], 1);
+    }
+
+    #[tokio::test]
+    async fn non_finite_velocity_holds_the_last_command() {
+        let (handler, transport) = engaged_handler().await;
+        let published = actuations(&transport).len();
+        transport.deliver(&uri(resources::VEHICLE, resources::VELOCITY), "inf").await;
+        assert_eq!(actuations(&transport).len(), published, "no command is published for a bad sample");
+        assert_eq!(handler.control_state().current_velocity, 10.0);
+        assert_eq!(handler.decode_error_counts()["velocity"], 1);
+    }
+
+    #[tokio::test]
+    async fn non_finite_target_is_rejected() {
+        let (handler, transport) = engaged_handler().await;
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "NaN").await;
+        assert_eq!(handler.control_state().desired_velocity, 20.0);
+        assert_eq!(handler.decode_error_counts()["target_speed"], 1);
+    }
+
+    #[tokio::test]
+    async fn non_finite_speed_limit_is_rejected() {
+        let (handler, transport) = engaged_handler().await;
+        transport.deliver(&uri(resources::HMI, resources::SPEED_LIMIT), "-inf").await;
+        assert_eq!(handler.control_state().speed_limit, None);
+        assert_eq!(handler.decode_error_counts()["speed_limit"], 1);
+    }
+
+    #[tokio::test]
+    async fn non_finite_friction_is_rejected() {
+        let (handler, transport) = engaged_handler().await;
+        let friction = handler.controller.lock().unwrap().get_friction_coefficient();
+        transport.deliver(&uri(resources::VEHICLE, resources::FRICTION), "NaN").await;
+        assert_eq!(handler.controller.lock().unwrap().get_friction_coefficient(), friction);
+        assert_eq!(handler.decode_error_counts()["friction"], 1);
+    }
+
+    #[tokio::test]
+    async fn non_finite_control_values_are_rejected_even_when_clamping() {
+        let (handler, _transport) = engaged_handler().await;
+        handler.set_clamp_control_values(true);
+        let listener = handler.control_values_listener();
+        let control = ControlValues { throttle: f64::NAN, steer: 0.0, brake: 0.0 };
+        assert!(listener.accept(control).is_none());
+        let control = ControlValues { throttle: 0.2, steer: f64::INFINITY, brake: 0.0 };
+        assert!(listener.accept(control).is_none());
+        assert_eq!(handler.get_control_values(), (0.0, 0.0, 0.0));
+    }
+
+    #[tokio::test]
+    async fn non_finite_lidar_points_are_dropped() {
+        let (handler, _transport) = engaged_handler().await;
+        let detection = |x: f64| LidarDetection { intensity: 1.0, point: PointCoords { x, y: 0.0, z: 1.0 } };
+        handler.lidar_listener().apply(LidarMeasurement {
+            channel_count: 1,
+            detections: vec![detection(f64::NAN), detection(12.0)],
+            horizontal_angle: 0.0,
+            is_empty: false,
+            len: 2,
+        });
+        let lidar = handler.get_latest_lidar_data().unwrap();
+        assert_eq!(lidar.len, 1);
+        assert_eq!(lidar.detections[0].point.x, 12.0);
+        assert_eq!(handler.decode_error_counts()["lidar"], 1);
+    }
+
+    #[tokio::test]
+    async fn scripted_engage_target_velocity() {
+        let transport = Arc::new(MockTransport::default());
+        let handler = UProtocolHandler::with_transport(PIDController::new(0.5, 0.0, 0.0), transport.clone()).unwrap();
+
+        handler.inject_engage(true);
+        handler.inject_target(15.0);
+        handler.inject_velocity(10.0, 1.0).await;
+        handler.inject_velocity(10.0, 1.1).await;
+        assert_eq!(handler.get_state(), (10.0, 15.0, 1.1, true));
+        assert!(actuations(&transport)[1] > 0.0);
+
+        // An obstacle right in front ends the session with emergency braking
+        handler.inject_lidar(crate::pid_controller::tests::obstacle_at(2.0));
+        handler.inject_velocity(10.0, 1.2).await;
+        assert!(*actuations(&transport).last().unwrap() < 0.0);
+        assert!(!handler.is_engaged());
+        assert_eq!(handler.snapshot().regime, Some(ControlRegime::EmergencyBraking));
+
+        let received: HashMap<String, u64> = handler.subscription_status().into_iter()
+            .map(|info| (info.stream, info.received))
+            .collect();
+        assert_eq!((received["engage"], received["target_speed"], received["velocity"], received["lidar"]), (1, 1, 3, 1));
+    }
+
+    #[tokio::test]
+    async fn replayed_recording_reproduces_the_actuations() {
+        let dir = std::env::temp_dir().join(format!("pid_recording_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (recorded, recorded_transport) = mock_handler(PIDController::new(0.5, 0.1, 0.0)).await;
+        recorded.record_to(&dir).unwrap();
+        let script = [
+            (resources::HMI, resources::TARGET_SPEED, "20.0"),
+            (resources::HMI, resources::ENGAGE, "1"),
+            (resources::VEHICLE, resources::CLOCK, "1.0"),
+            (resources::VEHICLE, resources::VELOCITY, "10.0"),
+            (resources::VEHICLE, resources::CLOCK, "1.1"),
+            (resources::VEHICLE, resources::VELOCITY, "{\"velocity\": 10.4}"),
+            (resources::VEHICLE, resources::CLOCK, "1.2"),
+            (resources::VEHICLE, resources::VELOCITY, "10.9"),
+        ];
+        for (authority, resource, payload) in script {
+            deliver_later(&recorded_transport, &uri(authority, resource), payload).await;
+        }
+
+        let transport = Arc::new(MockTransport::default());
+        let replayed = UProtocolHandler::with_transport(PIDController::new(0.5, 0.1, 0.0), transport.clone()).unwrap();
+        assert_eq!(replayed.replay_from(&dir).await.unwrap(), script.len());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(actuations(&transport).len(), 3);
+        assert_eq!(actuations(&transport), actuations(&recorded_transport));
+    }
+
+    /// Deliver after the receive timestamp has moved on, so the replay order is unambiguous
+    async fn deliver_later(transport: &MockTransport, uri: &UUri, payload: &str) {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        transport.deliver(uri, payload).await;
+    }
+
+    #[tokio::test]
+    async fn concurrent_snapshots_never_see_a_half_switched_engage() {
+        let (handler, _transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        let listener = handler.engage_listener();
+        std::thread::scope(|scope| {
+            let switcher = scope.spawn(move || {
+                for _ in 0..500 {
+                    listener.apply(1, Some(25.0));
+                    listener.apply(0, Some(10.0));
+                }
+            });
+            while !switcher.is_finished() {
+                let state = handler.control_state();
+                assert_eq!(state.pid_active, state.desired_velocity == 25.0, "{:?}", state);
+            }
+        });
+        assert!(!handler.is_active() && !handler.is_engaged());
+        assert_eq!(handler.control_state().desired_velocity, 10.0);
+    }
+
+    #[tokio::test]
+    async fn tick_updates_land_together() {
+        let (handler, transport) = engaged_handler().await;
+        let state = handler.control_state();
+        assert_eq!(state.active_ticks, 2);
+        assert_eq!(state.previous_time, 1.1);
+        assert_eq!(state.last_acceleration, actuations(&transport).last().copied());
+        assert_eq!(state.last_actuation.map(|(payload, _)| payload), transport.sent_to(&uri(resources::CRUISE_CONTROL, resources::ACTUATION)).pop());
+        assert_eq!(state.last_regime, Some(ControlRegime::Cruise));
+    }
+
+    #[tokio::test]
+    async fn at_target_status_follows_the_tolerance() {
+        let (handler, transport) = engaged_handler().await;
+        let at_target_uri = uri(resources::CRUISE_CONTROL, resources::AT_TARGET_STATUS);
+        let mut expected: Vec<&str> = Vec::new();
+        for (time, velocity, at_target, published) in [
+            (1.2, 17.0, false, None),
+            (1.3, 18.5, true, Some("1")),
+            (1.4, 19.5, true, None),
+            (1.5, 21.5, true, None),
+            (1.6, 22.5, false, Some("0")),
+        ] {
+            tick(&transport, time, velocity).await;
+            expected.extend(published);
+            assert_eq!(handler.control_state().at_target, at_target, "at {} m/s", velocity);
+            assert_eq!(transport.sent_to(&at_target_uri), expected, "at {} m/s", velocity);
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_payloads_are_counted_per_stream() {
+        let (handler, transport) = engaged_handler().await;
+        let published = actuations(&transport).len();
+        transport.deliver(&uri(resources::VEHICLE, resources::VELOCITY), "fast").await;
+        transport.deliver(&uri(resources::VEHICLE, resources::VELOCITY), "{\"velocity\": \"fast\"}").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "yes").await;
+
+        let counts = handler.decode_error_counts();
+        assert_eq!(counts["velocity"], 2);
+        assert_eq!(counts["engage"], 1);
+        assert!(counts.iter().filter(|(stream, _)| !["velocity", "engage"].contains(&stream.as_str())).all(|(_, count)| *count == 0));
+
+        // The handler keeps processing valid messages
+        assert!(handler.is_engaged());
+        tick(&transport, 1.2, 11.0).await;
+        assert_eq!(actuations(&transport).len(), published + 1);
+        assert_eq!(handler.control_state().current_velocity, 11.0);
+    }
+
+    #[tokio::test]
+    async fn pedal_command_matches_the_result() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_actuation_output(ActuationOutput::Both);
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        transport.deliver(&uri(resources::CRUISE_CONTROL, resources::CONTROL_VALUES), r#"{"throttle": 0.0, "steer": 0.3, "brake": 0.0}"#).await;
+        tick(&transport, 1.0, 10.0).await;
+        tick(&transport, 1.1, 10.0).await;
+
+        let pedal_commands: Vec<ControlValues> = transport.sent_to(&uri(resources::CRUISE_CONTROL, resources::PEDAL_COMMAND)).iter()
+            .map(|payload| serde_json::from_str(payload).unwrap())
+            .collect();
+        let acceleration = *actuations(&transport).last().unwrap();
+        let expected = PIDResult::new(acceleration);
+        let last = pedal_commands.last().unwrap();
+        assert_eq!(pedal_commands.len(), actuations(&transport).len());
+        assert_eq!((last.throttle, last.brake), (expected.throttle, expected.brake));
+        assert!(last.throttle > 0.0);
+        assert_eq!(last.steer, 0.3, "steer passes the last received input through");
+    }
+
+    #[tokio::test]
+    async fn actuation_output_selects_the_published_resources() {
+        for (output, acceleration_messages, pedal_messages) in [(ActuationOutput::Acceleration, 1, 0),
+                                                                (ActuationOutput::ControlValues, 0, 1),
+                                                                (ActuationOutput::Both, 1, 1)] {
+            let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+            handler.set_actuation_output(output);
+            transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+            transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+            tick(&transport, 1.0, 10.0).await;
+            tick(&transport, 1.1, 10.0).await;
+
+            let accelerations = actuations(&transport);
+            let pedal_commands: Vec<ControlValues> = transport.sent_to(&uri(resources::CRUISE_CONTROL, resources::PEDAL_COMMAND)).iter()
+                .map(|payload| serde_json::from_str(payload).unwrap())
+                .collect();
+            assert_eq!(accelerations.len(), 2 * acceleration_messages, "{:?}", output);
+            assert_eq!(pedal_commands.len(), 2 * pedal_messages, "{:?}", output);
+
+            let acceleration = handler.control_state().last_acceleration.unwrap();
+            assert!(acceleration > 0.0, "{:?}", output);
+            if let Some(last) = accelerations.last() {
+                assert_eq!(*last, acceleration, "{:?}", output);
+            }
+            if let Some(last) = pedal_commands.last() {
+                let expected = PIDResult::new(acceleration);
+                assert_eq!((last.throttle, last.brake), (expected.throttle, expected.brake), "{:?}", output);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_bounds_the_actuation_rate() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_min_publish_interval(Duration::from_millis(50));
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+
+        let started = Instant::now();
+        let mut velocity = 10.0;
+        for i in 0..40 {
+            velocity += 0.1;
+            tick(&transport, 1.0 + i as f64 * 0.01, velocity).await;
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let elapsed = started.elapsed().as_millis() as usize;
+
+        let sent = actuations(&transport).len();
+        assert!(sent >= 2, "the interval passed several times, got {} messages", sent);
+        assert!(sent <= elapsed / 50 + 1, "{} messages in {}ms exceed one per 50ms", sent, elapsed);
+        // The control state still follows every tick
+        assert_eq!(handler.snapshot().current_velocity, velocity);
+    }
+
+    #[test]
+    fn control_values_validate_their_ranges() {
+        let valid = ControlValues { throttle: 1.0, steer: -1.0, brake: 0.0 };
+        assert!(valid.validate().is_ok());
+        for invalid in [
+            ControlValues { throttle: 5.0, steer: 0.0, brake: 0.0 },
+            ControlValues { throttle: 0.0, steer: 1.5, brake: 0.0 },
+            ControlValues { throttle: 0.0, steer: 0.0, brake: -0.1 },
+            ControlValues { throttle: f64::NAN, steer: 0.0, brake: 0.0 },
+        ] {
+            assert!(invalid.validate().is_err(), "{:?}", invalid);
+        }
+        let clamped = ControlValues { throttle: 5.0, steer: -2.0, brake: -0.1 }.clamped();
+        assert_eq!((clamped.throttle, clamped.steer, clamped.brake), (1.0, -1.0, 0.0));
+    }
+
+    #[tokio::test]
+    async fn out_of_range_control_values_keep_the_last_good_values() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        let control_values = uri(resources::CRUISE_CONTROL, resources::CONTROL_VALUES);
+        transport.deliver(&control_values, r#"{"throttle": 0.4, "steer": 0.1, "brake": 0.0}"#).await;
+        transport.deliver(&control_values, r#"{"throttle": 5.0, "steer": 0.1, "brake": 0.0}"#).await;
+        transport.deliver(&control_values, r#"{"throttle": 0.0, "steer": 0.1, "brake": -1.0}"#).await;
+
+        assert_eq!(handler.get_control_values(), (0.4, 0.1, 0.0));
+        assert_eq!(handler.decode_error_counts()["control_values"], 2);
+    }
+
+    #[tokio::test]
+    async fn clamp_mode_accepts_out_of_range_control_values() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_clamp_control_values(true);
+        let control_values = uri(resources::CRUISE_CONTROL, resources::CONTROL_VALUES);
+        transport.deliver(&control_values, r#"{"throttle": 5.0, "steer": -3.0, "brake": 0.2}"#).await;
+        assert_eq!(handler.get_control_values(), (1.0, -1.0, 0.2));
+    }
+
+    #[tokio::test]
+    async fn stalled_sends_time_out_without_blocking_the_control_loop() {
+        let (handler, transport) = engaged_handler().await;
+        handler.set_send_timeout(Duration::from_millis(20));
+        transport.set_stalled(true);
+
+        let ticks = tokio::time::timeout(Duration::from_secs(2), async {
+            tick(&transport, 1.2, 11.0).await;
+            tick(&transport, 1.3, 12.0).await;
+        }).await;
+        assert!(ticks.is_ok(), "a stalled transport must not block the velocity listener");
+        assert_eq!(handler.dropped_send_count(), 2);
+        assert_eq!(handler.snapshot().current_velocity, 12.0);
+
+        // Once the transport recovers, actuation messages go out again
+        transport.set_stalled(false);
+        tick(&transport, 1.4, 13.0).await;
+        assert_eq!(actuations(&transport).len(), 3);
+        assert_eq!(handler.dropped_send_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn async_control_loop_keeps_the_velocity_listener_responsive() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_control_loop(ControlLoop::Async);
+        handler.start_control_loop();
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+
+        // Every tick blocks in the stalled send until the timeout, far longer than the messages take
+        handler.set_send_timeout(Duration::from_millis(500));
+        transport.set_stalled(true);
+        let started = Instant::now();
+        for (time, velocity) in [(1.0, 10.0), (1.1, 11.0), (1.2, 12.0)] {
+            tick(&transport, time, velocity).await;
+            tokio::task::yield_now().await;
+            assert_eq!(handler.snapshot().current_velocity, velocity, "the state is updated before the tick ran");
+        }
+        assert!(started.elapsed() < Duration::from_millis(250), "{:?}", started.elapsed());
+
+        // The control loop catches up once the transport recovers
+        transport.set_stalled(false);
+        tokio::time::timeout(Duration::from_secs(3), async {
+            while actuations(&transport).is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("the control loop publishes again");
+        assert!(handler.dropped_send_count() >= 1);
+    }
+
+    #[tokio::test]
+    async fn speed_arbitration_combines_driver_target_and_speed_limit() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        assert_eq!(handler.effective_target(), 20.0, "the driver target applies until a limit arrives");
+        transport.deliver(&uri(resources::HMI, resources::SPEED_LIMIT), "15.0").await;
+
+        for (arbitration, expected) in [(SpeedArbitration::Min, 15.0), (SpeedArbitration::Max, 20.0),
+                                        (SpeedArbitration::DriverPriority, 20.0)] {
+            handler.set_speed_arbitration(arbitration);
+            assert_eq!(handler.effective_target(), expected, "{:?}", arbitration);
+        }
+
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "0.0").await;
+        assert_eq!(handler.effective_target(), 15.0, "without a driver setpoint the limit is used");
+    }
+
+    #[tokio::test]
+    async fn control_tick_regulates_to_the_arbitrated_target() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_wire_schema(WireSchema::Ackermann);
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::SPEED_LIMIT), "15.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        tick(&transport, 1.0, 10.0).await;
+
+        handler.set_speed_arbitration(SpeedArbitration::Max);
+        tick(&transport, 1.1, 10.0).await;
+
+        let speeds: Vec<f64> = transport.sent_to(&uri(resources::CRUISE_CONTROL, resources::ACTUATION)).iter()
+            .map(|payload| serde_json::from_str::<serde_json::Value>(payload).unwrap()["drive"]["speed"].as_f64().unwrap())
+            .collect();
+        assert_eq!(speeds, vec![15.0, 20.0]);
+    }
+
+    #[tokio::test]
+    async fn take_results_returns_the_segment_and_empties_the_buffers() {
+        let (handler, transport) = engaged_handler().await;
+
+        let segment = handler.take_results();
+        assert_eq!(segment.len(), 7);
+        assert_eq!(segment["current_time"], vec![1.0, 1.1]);
+        assert_eq!(segment["current_velocity"], vec![10.0, 10.0]);
+        assert!(segment.values().all(|values| values.len() == 2));
+        assert!(handler.take_results().values().all(|values| values.is_empty()));
+
+        // The next segment starts from scratch, and clearing discards it
+        tick(&transport, 1.2, 11.0).await;
+        assert_eq!(handler.take_results()["current_velocity"], vec![11.0]);
+        tick(&transport, 1.3, 12.0).await;
+        handler.clear_results();
+        let cleared = handler.take_results();
+        assert_eq!(cleared.len(), 7, "clearing keeps the series names");
+        assert!(cleared.values().all(|values| values.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn rotated_runs_write_distinct_result_files() {
+        let dir = std::env::temp_dir().join(format!("pid_rotation_test_{}", std::process::id()));
+        let dir_name = dir.to_str().unwrap();
+        let (handler, _transport) = engaged_handler().await;
+        handler.set_keep_log_runs(2);
+        let write_run = |run_id: u128| {
+            UProtocolHandler::write_results(&handler.results, &handler.controller, &handler.config, run_id, dir_name).unwrap();
+        };
+        let exists = |name: &str| dir.join(name).exists();
+
+        write_run(1000);
+        write_run(2000);
+        assert!(exists("acceleration-1000.log") && exists("acceleration-2000.log"), "the second run must not overwrite the first");
+        assert!(exists("pid_results-1000.json") && exists("pid_results-2000.json"));
+
+        // Only the newest two runs are kept
+        write_run(3000);
+        assert!(!exists("acceleration-1000.log") && !exists("pid_results-1000.json"));
+        assert!(exists("acceleration-2000.log") && exists("acceleration-3000.log"));
+
+        // Without rotation a run keeps overwriting the same files
+        handler.set_keep_log_runs(0);
+        write_run(4000);
+        assert!(exists("acceleration.log") && !exists("acceleration-4000.log"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn results_json_carries_the_run_metadata_and_the_series() {
+        let dir = std::env::temp_dir().join(format!("pid_metadata_test_{}", std::process::id()));
+        let (handler, _transport) = engaged_handler().await;
+        {
+            let mut controller = handler.controller.lock().unwrap();
+            controller.set_emergency_config(4.0, 12.0, -6.0);
+            controller.set_manual_brake_config(0.3, 1.5);
+        }
+        UProtocolHandler::write_results(&handler.results, &handler.controller, &handler.config, 1234, dir.to_str().unwrap()).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("pid_results.json")).unwrap()).unwrap();
+        let metadata = &json["metadata"];
+        assert_eq!(metadata["started_at_ms"], 1234);
+        assert_eq!(metadata["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!((metadata["controller"]["kp"].as_f64(), metadata["controller"]["ki"].as_f64()), (Some(0.5), Some(0.0)));
+        assert_eq!(metadata["controller"]["emergency_stop_distance"], 4.0);
+        assert_eq!(metadata["controller"]["slow_down_distance"], 12.0);
+        assert_eq!(metadata["controller"]["max_braking_acceleration"], -6.0);
+        assert_eq!(metadata["controller"]["manual_brake_threshold"], 0.3);
+        assert_eq!(metadata["controller"]["target_speed_tolerance"], 1.5);
+        assert!(metadata["handler"].is_object());
+        assert_eq!(json["series"]["current_time"], serde_json::json!([1.0, 1.1]));
+        assert_eq!(json["series"]["current_velocity"], serde_json::json!([10.0, 10.0]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn engage_payload_can_carry_the_target_speed() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        let engage = uri(resources::HMI, resources::ENGAGE);
+        transport.deliver(&engage, r#"{"engaged": 1, "target": 27.0}"#).await;
+        assert!(handler.is_engaged() && handler.is_active());
+        assert_eq!(handler.control_state().desired_velocity, 27.0);
+
+        tick(&transport, 1.0, 20.0).await;
+        tick(&transport, 1.1, 20.0).await;
+        assert!(actuations(&transport)[1] > 0.0, "the PID drives toward the target of the engage message");
+
+        // The plain text format keeps working and leaves the target alone
+        transport.deliver(&engage, "0").await;
+        assert!(!handler.is_engaged());
+        transport.deliver(&engage, "1").await;
+        assert!(handler.is_engaged());
+        assert_eq!(handler.control_state().desired_velocity, 27.0);
+    }
+
+    #[tokio::test]
+    async fn older_velocity_after_a_newer_one_is_ignored() {
+        let (handler, transport) = engaged_handler().await;
+        let velocity_uri = uri(resources::VEHICLE, resources::VELOCITY);
+        let older = UUID::build();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        transport.deliver(&velocity_uri, "12.0").await;
+        transport.deliver_with_id(&velocity_uri, "11.0", older).await;
+        assert_eq!(handler.control_state().current_velocity, 12.0);
+        assert_eq!(handler.out_of_order_counts()["velocity"], 1);
+        assert_eq!(handler.out_of_order_counts()["clock"], 0);
+    }
+
+    #[tokio::test]
+    async fn older_clock_after_a_newer_one_is_ignored() {
+        let (handler, transport) = engaged_handler().await;
+        let clock_uri = uri(resources::VEHICLE, resources::CLOCK);
+        let older = UUID::build();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        transport.deliver(&clock_uri, "2.0").await;
+        transport.deliver_with_id(&clock_uri, "1.5", older).await;
+        assert_eq!(handler.control_state().current_time, 2.0);
+        assert_eq!(handler.out_of_order_counts()["clock"], 1);
+    }
+
+    /// Deliver an operator command and return the parsed status reply
+    async fn command(transport: &MockTransport, payload: &str) -> serde_json::Value {
+        transport.deliver(&uri(resources::CRUISE_CONTROL, resources::COMMAND), payload).await;
+        let replies = transport.sent_to(&uri(resources::CRUISE_CONTROL, resources::COMMAND_STATUS));
+        serde_json::from_str(replies.last().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn operator_commands_act_on_the_controller_and_results() {
+        let (handler, transport) = engaged_handler().await;
+        assert_eq!(command(&transport, r#"{"cmd": "suspend"}"#).await, serde_json::json!({"cmd": "suspend", "status": "ok"}));
+        assert!(handler.controller.lock().unwrap().is_cruise_suspended());
+        assert_eq!(command(&transport, r#"{"cmd": "resume"}"#).await["status"], "ok");
+        assert!(!handler.controller.lock().unwrap().is_cruise_suspended());
+
+        handler.controller.lock().unwrap().suspend_cruise_control();
+        assert_eq!(command(&transport, r#"{"cmd": "reset"}"#).await["status"], "ok");
+        assert!(!handler.controller.lock().unwrap().is_cruise_suspended(), "reset clears the suspension");
+
+        assert_eq!(command(&transport, r#"{"cmd": "clear_results"}"#).await["status"], "ok");
+        assert!(handler.take_results().values().all(|values| values.is_empty()));
+
+        let reply = command(&transport, r#"{"cmd": "apply_preset", "preset": "comfort"}"#).await;
+        assert_eq!(reply, serde_json::json!({"cmd": "apply_preset", "preset": "comfort", "status": "ok"}));
+        assert_eq!(handler.controller.lock().unwrap().get_soft_start(), 3.0);
+    }
+
+    #[tokio::test]
+    async fn unknown_command_replies_with_an_error() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        let reply = command(&transport, r#"{"cmd": "self_destruct"}"#).await;
+        assert_eq!(reply["status"], "error");
+        assert!(reply["message"].as_str().unwrap().contains("self_destruct"));
+        assert_eq!(handler.decode_error_counts()["command"], 1);
+    }
+
+    #[tokio::test]
+    async fn engaged_transitions_are_sent_to_subscribers() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_fail_safe(1, -3.0);
+        let transitions = handler.subscribe_engaged();
+        let engage = uri(resources::HMI, resources::ENGAGE);
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+
+        transport.deliver(&engage, "1").await;
+        transport.deliver(&engage, "1").await;
+        transport.deliver(&engage, "0").await;
+        transport.deliver(&engage, "1").await;
+        // A safety intervention disengages as well
+        tick(&transport, 1.0, 10.0).await;
+        transport.set_failing(true);
+        tick(&transport, 1.1, 10.0).await;
+
+        assert!(!handler.is_engaged());
+        assert_eq!(transitions.try_iter().collect::<Vec<_>>(), vec![true, false, true, false]);
+    }
+
+    #[test]
+    fn flat_raw_data_lidar_is_reconstructed() {
+        let fixture = br#"{"channel_count": 32, "horizontal_angle": 1.5,
+                           "raw_data": [5.0, 0.5, 1.0, 0.8, 12.0, -1.0, 0.7, 0.3]}"#;
+        let measurement = LidarMeasurement::from_json(fixture).unwrap();
+        assert_eq!((measurement.channel_count, measurement.horizontal_angle), (32, 1.5));
+        assert_eq!((measurement.len, measurement.is_empty), (2, false));
+        let points: Vec<(f64, f64, f64, f64)> = measurement.detections.iter()
+            .map(|detection| (detection.point.x, detection.point.y, detection.point.z, detection.intensity))
+            .collect();
+        assert_eq!(points, vec![(5.0, 0.5, 1.0, 0.8), (12.0, -1.0, 0.7, 0.3)]);
+    }
+
+    #[test]
+    fn lidar_parsing_prefers_the_detections_layout() {
+        let detections = br#"{"channel_count": 1, "horizontal_angle": 0.0, "is_empty": false, "len": 1,
+                              "detections": [{"intensity": 0.9, "point": {"x": 4.0, "y": 0.0, "z": 1.0}}]}"#;
+        let measurement = LidarMeasurement::from_json(detections).unwrap();
+        assert_eq!((measurement.detections[0].point.x, measurement.detections[0].intensity), (4.0, 0.9));
+
+        assert!(LidarMeasurement::from_json(br#"{"raw_data": [1.0, 2.0, 3.0]}"#).is_err(), "incomplete point");
+        assert!(LidarMeasurement::from_json(br#"{"points": []}"#).is_err());
+        let empty = LidarMeasurement::from_json(br#"{"raw_data": []}"#).unwrap();
+        assert!(empty.is_empty && empty.detections.is_empty());
+    }
+
+    #[tokio::test]
+    async fn decimation_runs_the_pid_on_every_nth_velocity_message() {
+        let (handler, transport) = mock_handler(PIDController::new(0.0, 1.0, 0.0)).await;
+        handler.set_decimation_factor(3);
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+
+        for i in 0..9 {
+            tick(&transport, 1.0 + i as f64 * 0.1, 10.0 + i as f64 * 0.01).await;
+        }
+
+        assert_eq!(actuations(&transport).len(), 3);
+        let times = handler.take_results()["current_time"].clone();
+        assert_eq!(times.len(), 3);
+        assert!(times.iter().zip([1.0, 1.3, 1.6]).all(|(time, expected)| (time - expected).abs() < 1e-9), "{:?}", times);
+        // The skipped messages still update the state, and the PID integrates over the full 0.3 s steps
+        assert_eq!(handler.control_state().current_velocity, 10.08);
+        let integral = handler.controller.lock().unwrap().integral_term();
+        assert!((integral - (0.3 * (20.0 - 10.03) + 0.3 * (20.0 - 10.06))).abs() < 1e-9, "integral {}", integral);
+    }
+
+    #[tokio::test]
+    async fn path_obstacles_json_lists_exactly_the_in_path_points() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        assert_eq!(handler.path_obstacles_json(), "[]", "no lidar frame yet");
+
+        // In path, beside the path, overhead, on the ground, behind the vehicle, and in path again
+        let cloud = r#"{"raw_data": [5.0, 0.5, 1.0, 0.8,   5.0, 4.0, 1.0, 0.8,   5.0, 0.0, 3.0, 0.8,
+                                     5.0, 0.0, 0.1, 0.8,  -3.0, 0.0, 1.0, 0.8,  12.0, -1.0, 0.7, 0.3]}"#;
+        transport.deliver(&uri(resources::VEHICLE, resources::LIDAR), cloud).await;
+
+        let obstacles: serde_json::Value = serde_json::from_str(&handler.path_obstacles_json()).unwrap();
+        assert_eq!(obstacles, serde_json::json!([
+            {"x": 5.0, "y": 0.5, "z": 1.0, "intensity": 0.8, "forward_distance": 5.0, "lateral_offset": 0.5},
+            {"x": 12.0, "y": -1.0, "z": 0.7, "intensity": 0.3, "forward_distance": 12.0, "lateral_offset": -1.0},
+        ]));
+    }
+
+    /// Decode with decoders that tell which format won; a bare number is valid text and JSON
+    fn decode_tagged(format: &LearnedFormat, payload: &str) -> Option<&'static str> {
+        format.decode(
+            payload.as_bytes(),
+            |text| text.parse::<f64>().ok().map(|_| "text"),
+            |bytes| serde_json::from_slice::<serde_json::Value>(bytes).ok().map(|_| "json"),
+        )
+    }
+
+    #[test]
+    fn learned_format_is_preferred_until_the_producer_switches() {
+        let format = LearnedFormat::new("velocity");
+        assert_eq!(*format.learned.lock().unwrap(), None);
+        assert_eq!(decode_tagged(&format, "5.0"), Some("text"), "text is tried first before anything is learned");
+        assert_eq!(*format.learned.lock().unwrap(), Some(PayloadEncoding::Text));
+
+        assert_eq!(decode_tagged(&format, r#"{"velocity": 5.0}"#), Some("json"), "the other format still works");
+        assert_eq!(*format.learned.lock().unwrap(), Some(PayloadEncoding::Json));
+        assert_eq!(decode_tagged(&format, "5.0"), Some("json"), "ambiguous payloads now decode as JSON");
+        assert_eq!(*format.learned.lock().unwrap(), Some(PayloadEncoding::Json));
+
+        assert_eq!(decode_tagged(&format, "not a number"), None);
+        assert_eq!(*format.learned.lock().unwrap(), Some(PayloadEncoding::Json), "failures do not unlearn the format");
+    }
+
+    #[test]
+    fn each_output_convention_formats_the_same_result() {
+        let result = PIDResult::new(-2.5);
+        let command = ActuationCommand::from_result(&result, 1.0);
+        let payload = |convention, encoding| command.to_payload(WireSchema::UProtocolText, convention, encoding);
+
+        assert_eq!(payload(OutputConvention::SignedAcceleration, PayloadEncoding::Text),
+                   ("-2.5".to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT));
+        assert_eq!(payload(OutputConvention::SignedAcceleration, PayloadEncoding::Json),
+                   (r#"{"acceleration":-2.5}"#.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_JSON));
+
+        let (split, format) = payload(OutputConvention::SplitThrottleBrake, PayloadEncoding::Text);
+        assert_eq!(format, UPayloadFormat::UPAYLOAD_FORMAT_JSON, "only the signed acceleration can be plain text");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&split).unwrap(),
+                   serde_json::json!({"throttle": result.throttle, "brake": result.brake}));
+        assert!(result.brake > 0.0 && result.throttle == 0.0);
+
+        let (positive, _) = payload(OutputConvention::PositiveBrake, PayloadEncoding::Text);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&positive).unwrap(),
+                   serde_json::json!({"acceleration": 0.0, "deceleration": 2.5}));
+    }
+
+    #[test]
+    fn ackermann_schema_maps_the_result_to_the_drive_fields() {
+        let mut result = PIDResult::new(1.2);
+        result.steer = 0.5;
+        let command = ActuationCommand::from_result(&result, 12.25).with_speed(20.0);
+
+        let (payload, format) = command.to_payload(WireSchema::Ackermann, OutputConvention::PositiveBrake, PayloadEncoding::Text);
+        assert_eq!(format, UPayloadFormat::UPAYLOAD_FORMAT_JSON, "the output convention does not apply");
+        let message: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(message["header"], serde_json::json!({"stamp": {"sec": 12, "nanosec": 250_000_000}, "frame_id": "base_link"}));
+        assert_eq!(message["drive"]["speed"], 20.0);
+        assert_eq!(message["drive"]["acceleration"], 1.2);
+        let steering_angle = message["drive"]["steering_angle"].as_f64().unwrap();
+        assert!((steering_angle - 35.0_f64.to_radians()).abs() < 1e-12, "{}", steering_angle);
+        assert_eq!(message["drive"]["steering_angle_velocity"], 0.0);
+        assert_eq!(message["drive"]["jerk"], 0.0);
+    }
+
+    #[tokio::test]
+    async fn output_convention_applies_to_the_published_actuation() {
+        let (handler, transport) = engaged_handler().await;
+        handler.set_output_convention(OutputConvention::PositiveBrake);
+        tick(&transport, 1.2, 30.0).await;
+        let published = transport.sent_to(&uri(resources::CRUISE_CONTROL, resources::ACTUATION));
+        let braking: serde_json::Value = serde_json::from_str(published.last().unwrap()).unwrap();
+        assert_eq!(braking["acceleration"], 0.0);
+        assert!(braking["deceleration"].as_f64().unwrap() > 0.0, "10 m/s over the target brakes: {}", braking);
+    }
+
+    #[tokio::test]
+    async fn periodic_results_are_written_within_the_interval() {
+        let dir = std::env::temp_dir().join(format!("pid_periodic_results_test_{}", std::process::id()));
+        let (handler, transport) = engaged_handler().await;
+        handler.set_results_interval(Duration::from_millis(50));
+        handler.start_periodic_results(dir.to_str().unwrap());
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(std::fs::read_to_string(dir.join("current_velocity.log")).unwrap(), "10\n10");
+
+        // Later ticks show up in the next periodic write
+        tick(&transport, 1.2, 11.0).await;
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(std::fs::read_to_string(dir.join("current_velocity.log")).unwrap(), "10\n10\n11");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Engaged state and engage decode error count after delivering `payload` to a disengaged handler
+    async fn engaged_after(payload: &str, multi_state: bool) -> (bool, u64) {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_multi_state_engage(multi_state);
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), payload).await;
+        (handler.is_engaged(), handler.decode_error_counts()["engage"])
+    }
+
+    #[tokio::test]
+    async fn engage_payloads_are_resolved_unambiguously() {
+        assert_eq!(engaged_after("0", false).await, (false, 0));
+        assert_eq!(engaged_after("1", false).await, (true, 0));
+        assert_eq!(engaged_after(" 1\n", false).await, (true, 0));
+        assert_eq!(engaged_after(r#"{"engaged": 1}"#, false).await, (true, 0));
+        assert_eq!(engaged_after(r#"{"engaged": 0}"#, false).await, (false, 0));
+        // Out-of-range and garbage payloads are rejected and leave the state untouched
+        assert_eq!(engaged_after("2", false).await, (false, 1));
+        assert_eq!(engaged_after(r#"{"engaged": 7}"#, false).await, (false, 1));
+        assert_eq!(engaged_after("yes", false).await, (false, 1));
+        assert_eq!(engaged_after("-1", false).await, (false, 1));
+        // Multi-state engage treats every non-zero value as engaged
+        assert_eq!(engaged_after("2", true).await, (true, 0));
+    }
+
+    #[test]
+    fn latency_summary_covers_min_max_avg_and_p99() {
+        let latency = TickLatency::default();
+        assert_eq!(latency.summary().ticks, 0);
+        for ms in 1..=100 {
+            latency.record(Duration::from_millis(ms));
+        }
+        let summary = latency.summary();
+        assert_eq!(summary.ticks, 100);
+        assert_eq!((summary.min_ms, summary.max_ms, summary.avg_ms, summary.p99_ms), (1.0, 100.0, 50.5, 99.0));
+    }
+
+    #[tokio::test]
+    async fn velocity_ticks_populate_the_latency_stats() {
+        let (handler, transport) = engaged_handler().await;
+        for i in 0..3 {
+            tick(&transport, 1.2 + i as f64 * 0.1, 10.0).await;
+        }
+        let summary = handler.tick_latency();
+        assert_eq!(summary.ticks, 5);
+        assert!(summary.min_ms > 0.0 && summary.min_ms <= summary.avg_ms && summary.avg_ms <= summary.max_ms, "{:?}", summary);
+        assert!(summary.p99_ms <= summary.max_ms);
+    }
+
+    #[tokio::test]
+    async fn acceleration_encoding_selects_the_published_payload() {
+        let (handler, transport) = engaged_handler().await;
+        handler.set_acceleration_encoding(PayloadEncoding::Json);
+        tick(&transport, 1.2, 10.0).await;
+
+        let actuation_uri = uri(resources::CRUISE_CONTROL, resources::ACTUATION);
+        let payloads = transport.sent_to(&actuation_uri);
+        let acceleration: f64 = payloads[1].parse().expect("text is the default");
+        assert_eq!(payloads[2], serde_json::json!({"acceleration": acceleration}).to_string());
+        assert_eq!(transport.formats_sent_to(&actuation_uri)[1..], [
+            Some(UPayloadFormat::UPAYLOAD_FORMAT_TEXT),
+            Some(UPayloadFormat::UPAYLOAD_FORMAT_JSON),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn warmup_ticks_publish_zero_while_the_pid_runs() {
+        let (handler, transport) = mock_handler(PIDController::new(0.1, 0.1, 0.0)).await;
+        handler.set_warmup_ticks(3);
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        for i in 0..5 {
+            tick(&transport, 1.0 + i as f64 * 0.1, 10.0).await;
+        }
+
+        let published = actuations(&transport);
+        assert_eq!(published[..3], [0.0, 0.0, 0.0], "no actuation during the warmup");
+        assert!(published[3] > 0.0 && published[4] > published[3], "{:?}", published);
+        assert!(handler.controller.lock().unwrap().integral_term() > 0.0, "the integral kept accumulating during the warmup");
+
+        // Engaging again starts a new warmup
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        tick(&transport, 2.0, 10.0).await;
+        tick(&transport, 2.1, 10.0).await;
+        assert_eq!(actuations(&transport)[5..], [0.0, 0.0]);
+    }
+
+    #[test]
+    fn tracking_metrics_measure_overshoot_and_settling_time() {
+        let results = HashMap::from([
+            ("desired_velocity".to_string(), vec![20.0; 5]),
+            ("current_velocity".to_string(), vec![17.0, 19.0, 20.8, 20.2, 20.1]),
+            ("acceleration".to_string(), vec![1.5, 1.0, -0.5, -0.2, 0.0]),
+            ("current_time".to_string(), vec![0.0, 1.0, 2.0, 3.0, 4.0]),
+        ]);
+
+        let metrics = TrackingMetrics::from_results(&results);
+
+        assert_eq!(metrics.data_points, 5);
+        assert!((metrics.overshoot - 0.8).abs() < 1e-9, "{}", metrics.overshoot);
+        // The 0.8 m/s overshoot at t=2 is the last error outside SETTLING_BAND
+        assert_eq!(metrics.settling_time, 3.0);
+    }
+
+    #[test]
+    fn comparison_reports_deltas_and_a_weighted_verdict() {
+        let baseline = TrackingMetrics {
+            rms_error: 2.0,
+            overshoot: 1.0,
+            settling_time: 10.0,
+            ..Default::default()
+        };
+        let candidate = TrackingMetrics {
+            rms_error: 1.5,
+            overshoot: 1.25,
+            settling_time: 6.0,
+            ..Default::default()
+        };
+
+        let comparison = baseline.compare(&candidate, &ComparisonWeights::default());
+        assert_eq!(comparison.delta_rms_error, -0.5);
+        assert_eq!(comparison.delta_overshoot, 0.25);
+        assert_eq!(comparison.delta_settling_time, -4.0);
+        assert!((comparison.score - -0.65).abs() < 1e-9, "{}", comparison.score);
+        assert!(comparison.improved);
+
+        // Weighting overshoot heavily turns the same change into a regression
+        let strict = ComparisonWeights { rms_error: 1.0, overshoot: 4.0, settling_time: 0.0 };
+        let comparison = baseline.compare(&candidate, &strict);
+        assert!((comparison.score - 0.5).abs() < 1e-9, "{}", comparison.score);
+        assert!(!comparison.improved);
+    }
+
+    #[tokio::test]
+    async fn heartbeat_republishes_the_last_acceleration_during_velocity_gaps() {
+        let (handler, transport) = engaged_handler().await;
+        handler.set_heartbeat_interval(Duration::from_millis(100));
+        handler.start_actuation_heartbeat();
+
+        // Velocity flowing faster than the heartbeat: only the ticks publish
+        let before = actuations(&transport).len();
+        for i in 0..5 {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            tick(&transport, 1.2 + i as f64 * 0.1, 10.0).await;
+        }
+        assert_eq!(actuations(&transport).len(), before + 5);
+
+        // Velocity gap of 350 ms: the last value is repeated about every 100 ms
+        let last = *actuations(&transport).last().unwrap();
+        let before = actuations(&transport).len();
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        let republished = actuations(&transport)[before..].to_vec();
+        assert!((2..=4).contains(&republished.len()), "{:?}", republished);
+        assert!(republished.iter().all(|&acceleration| acceleration == last), "{:?}", republished);
+    }
+
+    #[tokio::test]
+    async fn closest_obstacle_distance_series_follows_the_lidar() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        tick(&transport, 1.0, 10.0).await;
+        tick(&transport, 1.1, 10.0).await;
+
+        // In path at 25 m and 18 m, plus a closer point beside the path
+        let cloud = r#"{"raw_data": [25.0, 0.0, 1.0, 0.8,   18.0, 0.5, 1.0, 0.8,   6.0, 4.0, 1.0, 0.8]}"#;
+        transport.deliver(&uri(resources::VEHICLE, resources::LIDAR), cloud).await;
+        tick(&transport, 1.2, 10.0).await;
+
+        let results = handler.results.lock().unwrap();
+        let distances = &results["closest_obstacle_distance"];
+        assert!(!distances.is_empty());
+        for series in ["current_time", "current_velocity", "acceleration"] {
+            assert_eq!(results[series].len(), distances.len(), "{}", series);
+        }
+        assert_eq!(distances[distances.len() - 2], NO_OBSTACLE_DISTANCE);
+        assert_eq!(distances[distances.len() - 1], 18.0);
+    }
+
+    #[test]
+    fn actuation_command_round_trips() {
+        let mut result = PIDResult::new(0.1 + 0.2).with_regime(ControlRegime::Cruise);
+        result.steer = -0.25;
+        let command = ActuationCommand::from_result(&result, 12.5).with_speed(20.0);
+
+        let json = serde_json::to_string(&command).unwrap();
+        assert_eq!(serde_json::from_str::<ActuationCommand>(&json).unwrap(), command);
+
+        // The text payload keeps the full precision of the acceleration
+        let (text, _) = command.to_payload(WireSchema::UProtocolText, OutputConvention::SignedAcceleration, PayloadEncoding::Text);
+        assert_eq!(text.parse::<f64>().unwrap(), command.acceleration);
+    }
+
+    #[tokio::test]
+    async fn subscription_status_shows_which_streams_received_data() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        tick(&transport, 1.0, 10.0).await;
+        tick(&transport, 1.1, 10.0).await;
+
+        let status = handler.subscription_status();
+        assert!(status.iter().all(|info| info.subscribed), "{:?}", status);
+        let stream = |name: &str| status.iter().find(|info| info.stream == name).unwrap();
+
+        let velocity = stream("velocity");
+        assert_eq!(velocity.uri, uri(resources::VEHICLE, resources::VELOCITY).to_uri(false));
+        assert_eq!(velocity.received, 2);
+        assert!(velocity.last_received_ms.is_some());
+        assert_eq!(stream("clock").received, 2);
+
+        let lidar = stream("lidar");
+        assert_eq!((lidar.received, lidar.last_received_ms), (0, None), "the lidar stream is silent");
+    }
+
+    /// Engaged handler at 20 m/s target after a zero target under `policy` and one more tick
+    async fn after_zero_target(policy: ZeroTargetPolicy) -> (UProtocolHandler, Arc<MockTransport>) {
+        let (handler, transport) = engaged_handler().await;
+        handler.set_zero_target_policy(policy);
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "0").await;
+        tick(&transport, 1.2, 10.0).await;
+        (handler, transport)
+    }
+
+    #[tokio::test]
+    async fn zero_target_brakes_to_stop_by_default() {
+        assert_eq!(HandlerConfig::default().zero_target_policy, ZeroTargetPolicy::BrakeToStop);
+        let (handler, transport) = after_zero_target(ZeroTargetPolicy::BrakeToStop).await;
+        assert!(handler.is_engaged());
+        assert_eq!(handler.get_state().1, 0.0);
+        assert!(*actuations(&transport).last().unwrap() < 0.0);
+    }
+
+    #[tokio::test]
+    async fn zero_target_disengages_under_the_disengage_policy() {
+        let (handler, transport) = after_zero_target(ZeroTargetPolicy::Disengage).await;
+        assert!(!handler.is_engaged());
+        assert_eq!(transport.sent_to(&uri(resources::HMI, resources::ENGAGE)), vec!["0"]);
+        assert_eq!(actuations(&transport).len(), 2, "no actuation for the disengaging tick");
+    }
+
+    #[tokio::test]
+    async fn zero_target_is_ignored_under_the_ignore_policy() {
+        let (handler, transport) = after_zero_target(ZeroTargetPolicy::Ignore).await;
+        assert!(handler.is_engaged());
+        assert_eq!(handler.get_state().1, 20.0);
+        assert!(*actuations(&transport).last().unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn at_speed_status_flips_only_after_the_dwell() {
+        let (handler, transport) = engaged_handler().await;
+        handler.set_at_speed_band(1.0, Duration::from_millis(300));
+        let at_speed_uri = uri(resources::CRUISE_CONTROL, resources::AT_SPEED_STATUS);
+        let mut expected: Vec<&str> = Vec::new();
+        for (time, velocity, published) in [
+            (1.2, 19.5, None),      // Inside the band, dwell starts
+            (1.3, 19.6, None),
+            (1.4, 19.7, None),
+            (1.5, 19.8, Some("1")), // 0.3 s inside
+            (1.6, 18.5, None),      // Outside the 1 m/s band
+            (1.7, 19.2, None),      // Back inside before the dwell, no flicker
+            (1.8, 18.5, None),
+            (1.9, 18.6, None),
+            (2.0, 18.7, None),
+            (2.1, 18.8, Some("0")), // 0.3 s outside
+        ] {
+            tick(&transport, time, velocity).await;
+            expected.extend(published);
+            assert_eq!(transport.sent_to(&at_speed_uri), expected, "at {} s", time);
+        }
+    }
+
+    #[tokio::test]
+    async fn regime_is_published_on_debounced_transitions_only() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_regime_publishing(true, Duration::from_millis(300));
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        let lidar = |x: f64, y: f64| format!(r#"{{"raw_data": [{}, {}, 1.0, 0.8]}}"#, x, y);
+        let regimes = || transport.sent_to(&uri(resources::CRUISE_CONTROL, resources::REGIME)).iter()
+            .map(|payload| serde_json::from_str::<serde_json::Value>(payload).unwrap()["regime"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+
+        for (time, cloud, expected) in [
+            (1.0, None, vec![]),
+            (1.1, None, vec![]),
+            (1.2, None, vec![]),
+            (1.3, None, vec!["cruise"]),                                     // Cruise persisted for 0.3 s
+            (1.4, None, vec!["cruise"]),                                     // Identical ticks publish nothing
+            (1.5, Some(lidar(12.0, 0.0)), vec!["cruise"]),                   // Single slow-down tick
+            (1.6, Some(lidar(12.0, 5.0)), vec!["cruise"]),                   // Path clear again, debounced away
+            (1.7, Some(lidar(2.0, 0.0)), vec!["cruise", "emergency_braking"]), // Emergencies skip the debounce
+        ] {
+            if let Some(cloud) = cloud {
+                transport.deliver(&uri(resources::VEHICLE, resources::LIDAR), &cloud).await;
+            }
+            tick(&transport, time, 10.0).await;
+            if time == 1.5 {
+                assert_eq!(handler.control_state().last_regime, Some(ControlRegime::ObstacleSlowdown));
             }
+            assert_eq!(regimes(), expected, "at {} s", time);
+        }
+    }
+
+    #[tokio::test]
+    async fn snapshot_reflects_the_injected_state() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        let idle = handler.snapshot();
+        assert_eq!((idle.engaged, idle.pid_active, idle.acceleration, idle.regime), (false, false, None, None));
+
+        handler.inject_target(20.0);
+        handler.inject_engage(true);
+        transport.deliver(&uri(resources::CRUISE_CONTROL, resources::CONTROL_VALUES), r#"{"throttle": 0.0, "steer": 0.05, "brake": 0.0}"#).await;
+        handler.inject_lidar(crate::pid_controller::tests::obstacle_at(25.0));
+        handler.inject_velocity(10.0, 1.0).await;
+        handler.inject_velocity(10.0, 1.1).await;
+
+        let snapshot = handler.snapshot();
+        assert_eq!((snapshot.current_velocity, snapshot.desired_velocity, snapshot.current_time), (10.0, 20.0, 1.1));
+        assert!(snapshot.engaged && snapshot.pid_active);
+        assert_eq!((snapshot.throttle, snapshot.steer, snapshot.brake), (0.0, 0.05, 0.0));
+        assert_eq!(snapshot.acceleration, actuations(&transport).last().copied());
+        assert_eq!(snapshot.closest_obstacle_distance, Some(25.0));
+        assert_eq!(snapshot.regime, Some(ControlRegime::Cruise));
+
+        // The piecemeal getters agree with the snapshot
+        assert_eq!(handler.get_state(), (snapshot.current_velocity, snapshot.desired_velocity, snapshot.current_time, snapshot.engaged));
+        assert_eq!(handler.get_control_values(), (snapshot.throttle, snapshot.steer, snapshot.brake));
+    }
+
+    #[tokio::test]
+    async fn rapid_target_taps_slew_the_stored_target() {
+        // Without the setpoint ramp, so the engage ramp plays no part
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_target_slew_rate(2.0);
+        let target_uri = uri(resources::HMI, resources::TARGET_SPEED);
+        transport.deliver(&target_uri, "10.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+        assert_eq!(handler.get_state().1, 10.0, "the target before engaging applies immediately");
+        tick(&transport, 1.0, 10.0).await;
+
+        let mut stored = Vec::new();
+        for (i, target) in ["12", "14", "16", "18", "20", "20", "20"].into_iter().enumerate() {
+            transport.deliver(&target_uri, target).await;
+            tick(&transport, 1.1 + i as f64 * 0.1, 10.0).await;
+            stored.push(handler.get_state().1);
+        }
+
+        // 2 m/s per second over 0.1 s ticks, although the target jumped by 2 m/s per tick
+        assert!(stored.iter().zip([10.2, 10.4, 10.6, 10.8, 11.0, 11.2, 11.4]).all(|(target, expected)| (target - expected).abs() < 1e-9),
+                "{:?}", stored);
+        assert_eq!(handler.effective_target(), stored[6]);
+        assert_eq!(handler.controller.lock().unwrap().ramped_setpoint(), None);
+
+        // Once the taps stop the stored target reaches the last one and stays there
+        for i in 0..50 {
+            tick(&transport, 1.8 + i as f64 * 0.1, 10.0).await;
+        }
+        assert_eq!(handler.get_state().1, 20.0);
+
+        // The default applies a new target immediately
+        handler.set_target_slew_rate(0.0);
+        transport.deliver(&target_uri, "15.0").await;
+        assert_eq!(handler.get_state().1, 15.0);
+    }
+
+    #[tokio::test]
+    async fn wall_clock_advances_time_without_clock_messages() {
+        let clock = Arc::new(crate::pid_controller::MockClock::new(Duration::from_secs(100)));
+        let mut controller = PIDController::new(0.5, 0.1, 0.0);
+        controller.set_time_source(clock.clone());
+        let (handler, transport) = mock_handler(controller).await;
+        handler.set_clock_source(ClockSource::WallClock);
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        transport.deliver(&uri(resources::HMI, resources::ENGAGE), "1").await;
+
+        let velocity_uri = uri(resources::VEHICLE, resources::VELOCITY);
+        let mut times = Vec::new();
+        for _ in 0..3 {
+            transport.deliver(&velocity_uri, "10.0").await;
+            times.push(handler.control_state().current_time);
+            clock.advance(Duration::from_millis(100));
+        }
+        // A clock message is ignored in favour of the wall clock
+        transport.deliver(&uri(resources::VEHICLE, resources::CLOCK), "5.0").await;
+
+        assert_eq!(times, vec![100.0, 100.1, 100.2]);
+        assert_eq!(handler.control_state().current_time, 100.2);
+        let integral = handler.controller.lock().unwrap().integral_term();
+        assert!((integral - 0.1 * 2.0).abs() < 1e-9, "two 0.1 s steps with 10 m/s error, got {}", integral);
+        assert!(actuations(&transport)[1..].iter().all(|&acceleration| acceleration > 0.0));
+    }
+
+    #[tokio::test]
+    async fn engage_is_rejected_until_the_self_test_passes() {
+        let (handler, transport) = mock_handler(PIDController::new(0.5, 0.0, 0.0)).await;
+        handler.set_self_test(true, Duration::from_secs(2));
+        let engage = uri(resources::HMI, resources::ENGAGE);
+
+        let report = handler.self_test().unwrap_err();
+        assert_eq!(report.stale_streams, vec!["clock", "velocity"]);
+        assert!(report.unsubscribed_streams.is_empty(), "{:?}", report);
+        assert!(report.config_error.is_some(), "no PID config was set");
+        transport.deliver(&engage, "1").await;
+        assert!(!handler.is_active(), "engage is refused before the streams delivered data");
+
+        handler.set_pid_config(PidConfig::default());
+        transport.deliver(&uri(resources::VEHICLE, resources::CLOCK), "1.0").await;
+        let report = handler.self_test().unwrap_err();
+        assert_eq!((report.stale_streams, report.config_error), (vec!["velocity".to_string()], None));
+        transport.deliver(&engage, "1").await;
+        assert!(!handler.is_active());
+
+        transport.deliver(&uri(resources::VEHICLE, resources::VELOCITY), "10.0").await;
+        assert_eq!(handler.self_test(), Ok(()));
+        transport.deliver(&engage, "1").await;
+        assert!(handler.is_active(), "engage is accepted once every check passed");
+    }
+
+    #[cfg(feature = "test-fault-injection")]
+    mod fault_injection {
+        use super::*;
+
+        #[tokio::test]
+        async fn dropped_velocity_is_never_processed() {
+            let (handler, transport) = engaged_handler().await;
+            let published = actuations(&transport).len();
+            handler.inject_velocity_faults(VelocityFault::Drop, 1);
+            tick(&transport, 1.2, 12.0).await;
+            assert_eq!(handler.velocity_faults.remaining(), 0);
+            assert_eq!(actuations(&transport).len(), published);
+            assert_eq!(handler.control_state().current_velocity, 10.0);
+        }
+
+        #[tokio::test]
+        async fn delayed_velocity_is_rejected_as_out_of_order() {
+            let (handler, transport) = engaged_handler().await;
+            handler.inject_velocity_faults(VelocityFault::Delay(Duration::from_millis(50)), 1);
+            let velocity_uri = uri(resources::VEHICLE, resources::VELOCITY);
+            tokio::join!(
+                transport.deliver(&velocity_uri, "11.0"),
+                async {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    transport.deliver(&velocity_uri, "12.0").await;
+                },
+            );
+            assert_eq!(handler.out_of_order_counts()["velocity"], 1);
+            assert_eq!(handler.control_state().current_velocity, 12.0);
         }
+
+        #[tokio::test]
+        async fn nan_velocity_is_rejected() {
+            let (handler, transport) = engaged_handler().await;
+            let published = actuations(&transport).len();
+            handler.inject_velocity_faults(VelocityFault::NaN, 2);
+            tick(&transport, 1.2, 12.0).await;
+            assert_eq!(handler.velocity_faults.remaining(), 1);
+            tick(&transport, 1.3, 12.0).await;
+            assert_eq!(handler.decode_error_counts()["velocity"], 2);
+            assert_eq!(actuations(&transport).len(), published, "the actuator holds the last command");
+        }
+
+        #[tokio::test]
+        async fn duplicate_timestamp_runs_a_minimum_time_step() {
+            let (handler, transport) = engaged_handler().await;
+            let published = actuations(&transport).len();
+            handler.inject_velocity_faults(VelocityFault::DuplicateTimestamp, 1);
+            tick(&transport, 1.2, 10.0).await;
+            let actuations = actuations(&transport);
+            assert_eq!(actuations.len(), published + 2);
+            assert!(actuations.iter().all(|acceleration| acceleration.is_finite()));
+            assert_eq!(handler.take_results()["current_time"], vec![1.0, 1.1, 1.2, 1.2]);
+        }
+    }
+
+    #[tokio::test]
+    async fn emergency_stop_profile_brakes_until_standstill_before_disengaging() {
+        let mut pid = PIDController::new(0.5, 0.0, 0.0);
+        pid.set_emergency_brake_profile(0.5);
+        let (handler, transport) = mock_handler(pid).await;
+        let engage_uri = uri(resources::HMI, resources::ENGAGE);
+        transport.deliver(&engage_uri, "1").await;
+        transport.deliver(&uri(resources::HMI, resources::TARGET_SPEED), "20.0").await;
+        tick(&transport, 1.0, 10.0).await;
+
+        handler.inject_lidar(crate::pid_controller::tests::obstacle_at(2.0));
+        for (time, velocity) in [(1.1, 10.0), (1.4, 7.0), (1.7, 3.0), (2.0, 0.5)] {
+            tick(&transport, time, velocity).await;
+            assert!(handler.is_engaged() && handler.is_active(), "still braking at {} m/s", velocity);
+        }
+        let braking = actuations(&transport);
+        assert!(braking[1..].windows(2).all(|pair| pair[1] <= pair[0]), "{:?}", braking);
+        assert!(transport.sent_to(&engage_uri).is_empty());
+
+        tick(&transport, 2.1, 0.0).await;
+        assert_eq!(*actuations(&transport).last().unwrap(), 0.0, "the brake is released at standstill");
+        assert!(!handler.is_engaged() && !handler.is_active());
+        assert_eq!(transport.sent_to(&engage_uri), vec!["0"]);
     }
 }